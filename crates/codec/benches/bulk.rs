@@ -0,0 +1,97 @@
+// Echidna - Codec - benches
+
+// Encoding and decoding of numeric vectors, which copy all items at once,
+// against the same data wrapped in a newtype, which goes item by item.
+//
+// Run with `cargo bench --bench bulk`.
+
+use {
+    codec::{
+        Codec,
+        Encode,
+    },
+    std::{
+        hint::black_box,
+        time::{
+            Duration,
+            Instant,
+        },
+    },
+};
+
+#[derive(Codec,Clone,Copy)]
+struct U8(u8);
+
+#[derive(Codec,Clone,Copy)]
+struct U16(u16);
+
+#[derive(Codec,Clone,Copy)]
+struct F32(f32);
+
+#[derive(Codec,Clone,Copy)]
+struct F64(f64);
+
+const BYTES: usize = 4 * 1024 * 1024;
+
+// Average time of `f`, over at least half a second of runs.
+fn measure<F: FnMut()>(mut f: F) -> Duration {
+    f();
+    let start = Instant::now();
+    let mut runs = 0u32;
+    while start.elapsed() < Duration::from_millis(500) {
+        f();
+        runs += 1;
+    }
+    start.elapsed() / runs
+}
+
+fn report(name: &str,bulk: Duration,items: Duration) {
+    let rate = |d: Duration| BYTES as f64 / d.as_secs_f64() / 1.0e9;
+    println!(
+        "{:<16} bulk {:>9.3} ms {:>7.2} GB/s   per item {:>9.3} ms {:>7.2} GB/s   {:>6.1}x",
+        name,
+        bulk.as_secs_f64() * 1000.0,
+        rate(bulk),
+        items.as_secs_f64() * 1000.0,
+        rate(items),
+        items.as_secs_f64() / bulk.as_secs_f64(),
+    );
+}
+
+fn bench<T: Codec,W: Codec>(name: &str,source: Vec<T>,wrapped: Vec<W>) {
+    let mut buffer = Vec::<u8>::with_capacity(BYTES + 4);
+    let bulk = measure(|| {
+        buffer.clear();
+        black_box(&source).encode(&mut buffer);
+    });
+    let mut buffer = Vec::<u8>::with_capacity(BYTES + 4);
+    let items = measure(|| {
+        buffer.clear();
+        black_box(&wrapped).encode(&mut buffer);
+    });
+    report(&format!("encode {}",name),bulk,items);
+
+    assert!(Vec::<T>::decode(&buffer).is_ok() && Vec::<W>::decode(&buffer).is_ok());
+    let bulk = measure(|| {
+        black_box(Vec::<T>::decode(black_box(&buffer)).is_ok());
+    });
+    let items = measure(|| {
+        black_box(Vec::<W>::decode(black_box(&buffer)).is_ok());
+    });
+    report(&format!("decode {}",name),bulk,items);
+}
+
+fn main() {
+    let source: Vec<u8> = (0..BYTES).map(|i| i as u8).collect();
+    let wrapped = source.iter().map(|a| U8(*a)).collect();
+    bench("Vec<u8>",source,wrapped);
+    let source: Vec<u16> = (0..BYTES / 2).map(|i| i as u16).collect();
+    let wrapped = source.iter().map(|a| U16(*a)).collect();
+    bench("Vec<u16>",source,wrapped);
+    let source: Vec<f32> = (0..BYTES / 4).map(|i| i as f32).collect();
+    let wrapped = source.iter().map(|a| F32(*a)).collect();
+    bench("Vec<f32>",source,wrapped);
+    let source: Vec<f64> = (0..BYTES / 8).map(|i| i as f64).collect();
+    let wrapped = source.iter().map(|a| F64(*a)).collect();
+    bench("Vec<f64>",source,wrapped);
+}
@@ -0,0 +1,34 @@
+// Echidna - Codec - fuzz
+
+// Write the seed corpus for every fuzz target.
+
+use {
+    codec::testing::Rng,
+    std::{
+        fs,
+        path::Path,
+    },
+};
+
+fn write_corpus(target: &str,seeds: Vec<Vec<u8>>) {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("corpus").join(target);
+    fs::create_dir_all(&dir).expect("cannot create corpus directory");
+    for (i,seed) in seeds.iter().enumerate() {
+        fs::write(dir.join(format!("seed-{:03}",i)),seed).expect("cannot write seed");
+    }
+    println!("{}: {} seeds",target,seeds.len());
+}
+
+fn main() {
+    let mut rng = Rng::new(1);
+    write_corpus("primitives",codec_fuzz::primitive_seeds(&mut rng));
+    let mut text = codec_fuzz::text_seeds(&mut rng);
+    write_corpus("containers",codec_fuzz::container_seeds(&mut rng));
+    write_corpus("std",codec_fuzz::std_seeds(&mut rng));
+
+    // drawn last, so the seeds above stay the same
+    text.append(&mut codec_fuzz::parse_seeds(&mut rng));
+    write_corpus("text",text);
+    write_corpus("wrappers",codec_fuzz::wrapper_seeds(&mut rng));
+    write_corpus("values",codec_fuzz::value_seeds(&mut rng));
+}
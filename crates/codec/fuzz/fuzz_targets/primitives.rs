@@ -0,0 +1,13 @@
+#![no_main]
+
+use {
+    codec::testing::PeakAllocator,
+    libfuzzer_sys::fuzz_target,
+};
+
+#[global_allocator]
+static ALLOCATOR: PeakAllocator = PeakAllocator;
+
+fuzz_target!(|data: &[u8]| {
+    codec_fuzz::decode_primitive(data);
+});
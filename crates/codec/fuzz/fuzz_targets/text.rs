@@ -0,0 +1,14 @@
+#![no_main]
+
+use {
+    codec::testing::PeakAllocator,
+    libfuzzer_sys::fuzz_target,
+};
+
+#[global_allocator]
+static ALLOCATOR: PeakAllocator = PeakAllocator;
+
+fuzz_target!(|data: &[u8]| {
+    codec_fuzz::decode_text(data);
+    codec_fuzz::parse_text(data);
+});
@@ -0,0 +1,206 @@
+// Echidna - Codec - fuzz
+
+//! Decoders for the fuzz targets.
+//!
+//! The first byte of the fuzz input picks the type, the rest is decoded as
+//! that type by `fuzz_decode`, by `fuzz_decode_unordered` for maps and sets,
+//! or by `fuzz_decode_value_equal` for compressed values. `decode_values`
+//! decodes it as a `Value` of the type instead, and `parse_text` reads it
+//! as text. The seeds for the corpus are encodings of random values, or
+//! their text, prefixed the same way.
+
+use {
+    codec::{
+        BigEndian,
+        Checked,
+        Compressed,
+        Encode,
+        Varint,
+        Timestamp,
+        to_text,
+        testing::*,
+    },
+    std::{
+        borrow::Cow,
+        collections::{
+            BTreeMap,
+            BTreeSet,
+            HashMap,
+            HashSet,
+            VecDeque,
+        },
+        net::{
+            IpAddr,
+            Ipv4Addr,
+            Ipv6Addr,
+            SocketAddr,
+            SocketAddrV4,
+            SocketAddrV6,
+        },
+        rc::Rc,
+        sync::Arc,
+        time::{
+            Duration,
+            SystemTime,
+        },
+    },
+};
+
+// Number of seeds for each type.
+const SEEDS_PER_TYPE: usize = 8;
+
+// Type the seeds are made from, when it is not the decoded type itself.
+macro_rules! seed_type {
+    ($ty:ty) => { $ty };
+    ($ty:ty,$seed_ty:ty) => { $seed_ty };
+}
+
+// Seed from the encoding of `value`.
+fn encoded<T: Encode>(value: &T,seed: &mut Vec<u8>) {
+    value.encode(seed);
+}
+
+// Seed from the text of `value`.
+fn text<T: Encode>(value: &T,seed: &mut Vec<u8>) {
+    seed.extend_from_slice(to_text(value).expect("seed type without text").as_bytes());
+}
+
+macro_rules! fuzz_types {
+    ($decode:ident,$seeds:ident,$seed:ident { $($selector:literal => $check:ident::<$ty:ty> $(,$seed_ty:ty)?;)+ }) => {
+        pub fn $decode(data: &[u8]) {
+            if let Some((selector,data)) = data.split_first() {
+                match selector {
+                    $($selector => $check::<$ty>(data),)+
+                    _ => { },
+                }
+            }
+        }
+
+        pub fn $seeds(rng: &mut Rng) -> Vec<Vec<u8>> {
+            let mut seeds = Vec::<Vec<u8>>::new();
+            $(
+                for _ in 0..SEEDS_PER_TYPE {
+                    let mut seed = vec![$selector];
+                    $seed(&<seed_type!($ty $(,$seed_ty)?) as Random>::random(rng),&mut seed);
+                    seeds.push(seed);
+                }
+            )+
+            seeds
+        }
+    };
+}
+
+fuzz_types!(decode_primitive,primitive_seeds,encoded {
+    0 => fuzz_decode::<bool>;
+    1 => fuzz_decode::<u8>;
+    2 => fuzz_decode::<u16>;
+    3 => fuzz_decode::<u32>;
+    4 => fuzz_decode::<u64>;
+    5 => fuzz_decode::<u128>;
+    6 => fuzz_decode::<usize>;
+    7 => fuzz_decode::<i8>;
+    8 => fuzz_decode::<i16>;
+    9 => fuzz_decode::<i32>;
+    10 => fuzz_decode::<i64>;
+    11 => fuzz_decode::<i128>;
+    12 => fuzz_decode::<isize>;
+    13 => fuzz_decode::<f32>;
+    14 => fuzz_decode::<f64>;
+    15 => fuzz_decode::<char>;
+    16 => fuzz_decode::<()>;
+    17 => fuzz_decode::<Varint<u16>>;
+    18 => fuzz_decode::<Varint<u32>>;
+    19 => fuzz_decode::<Varint<u64>>;
+    20 => fuzz_decode::<Varint<i16>>;
+    21 => fuzz_decode::<Varint<i32>>;
+    22 => fuzz_decode::<Varint<i64>>;
+});
+
+fuzz_types!(decode_text,text_seeds,encoded {
+    0 => fuzz_decode::<String>;
+    1 => fuzz_decode::<&str>,String;
+    2 => fuzz_decode::<&[u8]>,Vec<u8>;
+    3 => fuzz_decode::<Cow<str>>,String;
+    4 => fuzz_decode::<Cow<[u8]>>,Vec<u8>;
+    5 => fuzz_decode::<Vec<u8>>;
+    6 => fuzz_decode::<Vec<char>>;
+    7 => fuzz_decode::<Vec<String>>;
+});
+
+fuzz_types!(decode_container,container_seeds,encoded {
+    0 => fuzz_decode::<Option<u32>>;
+    1 => fuzz_decode::<Option<String>>;
+    2 => fuzz_decode::<Vec<u16>>;
+    3 => fuzz_decode::<Vec<Vec<u8>>>;
+    4 => fuzz_decode::<VecDeque<u32>>;
+    5 => fuzz_decode::<[u16; 4]>;
+    6 => fuzz_decode::<[String; 2]>;
+    7 => fuzz_decode::<(u8,String,u64)>;
+    8 => fuzz_decode::<Box<u32>>;
+    9 => fuzz_decode::<Rc<String>>;
+    10 => fuzz_decode::<Arc<Vec<u8>>>;
+    11 => fuzz_decode::<Vec<Option<Box<(u8,i64)>>>>;
+    12 => fuzz_decode_unordered::<HashMap<u32,String>>;
+    13 => fuzz_decode_unordered::<BTreeMap<u16,Vec<u8>>>;
+    14 => fuzz_decode_unordered::<HashSet<u64>>;
+    15 => fuzz_decode_unordered::<BTreeSet<String>>;
+});
+
+fuzz_types!(decode_std,std_seeds,encoded {
+    0 => fuzz_decode::<Ipv4Addr>;
+    1 => fuzz_decode::<Ipv6Addr>;
+    2 => fuzz_decode::<IpAddr>;
+    3 => fuzz_decode::<SocketAddrV4>;
+    4 => fuzz_decode::<SocketAddrV6>;
+    5 => fuzz_decode::<SocketAddr>;
+    6 => fuzz_decode::<Duration>;
+    7 => fuzz_decode::<SystemTime>;
+    8 => fuzz_decode::<Timestamp>;
+});
+
+fuzz_types!(decode_wrapper,wrapper_seeds,encoded {
+    0 => fuzz_decode::<BigEndian<u16>>;
+    1 => fuzz_decode::<BigEndian<i64>>;
+    2 => fuzz_decode::<BigEndian<u128>>;
+    3 => fuzz_decode::<BigEndian<f64>>;
+    4 => fuzz_decode::<Checked<u32>>;
+    5 => fuzz_decode::<Checked<Vec<String>>>;
+    6 => fuzz_decode_value_equal::<Compressed<u32>>;
+    7 => fuzz_decode_value_equal::<Compressed<Vec<u8>>>;
+    8 => fuzz_decode_value_equal::<Compressed<Vec<String>>>;
+    9 => fuzz_decode_value_equal::<Checked<Compressed<Vec<u16>>>>;
+    10 => fuzz_decode_value_equal::<Compressed<Checked<String>>>;
+});
+
+fuzz_types!(decode_values,value_seeds,encoded {
+    0 => fuzz_value::<(bool,char,i16,u64,f32)>;
+    1 => fuzz_value::<Option<Option<u8>>>;
+    2 => fuzz_value::<Vec<String>>;
+    3 => fuzz_value::<Vec<u8>>;
+    4 => fuzz_value::<[u16; 3]>;
+    5 => fuzz_value::<HashMap<u16,String>>;
+    6 => fuzz_value::<BTreeSet<u32>>;
+    7 => fuzz_value::<IpAddr>;
+    8 => fuzz_value::<SocketAddr>;
+    9 => fuzz_value::<(Duration,SystemTime,Timestamp)>;
+    10 => fuzz_value::<Varint<i64>>;
+    11 => fuzz_value::<BigEndian<f32>>;
+    12 => fuzz_value::<Checked<Vec<u16>>>;
+    13 => fuzz_value::<Compressed<Vec<String>>>;
+    14 => fuzz_value::<Checked<Compressed<Vec<u8>>>>;
+});
+
+fuzz_types!(parse_text,parse_seeds,text {
+    0 => fuzz_from_text::<String>;
+    1 => fuzz_from_text::<Vec<u8>>;
+    2 => fuzz_from_text::<(bool,char,i64,u128)>;
+    3 => fuzz_from_text::<Option<Option<u16>>>;
+    4 => fuzz_from_text::<Vec<Option<()>>>;
+    5 => fuzz_from_text::<HashMap<String,u32>>;
+    6 => fuzz_from_text::<BTreeSet<u16>>;
+    7 => fuzz_from_text::<IpAddr>;
+    8 => fuzz_from_text::<(SocketAddr,Duration)>;
+    9 => fuzz_from_text::<Varint<i32>>;
+    10 => fuzz_from_text::<BigEndian<u32>>;
+    11 => fuzz_from_text::<Checked<Compressed<Vec<String>>>>;
+});
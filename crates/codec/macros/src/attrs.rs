@@ -0,0 +1,232 @@
+// Echidna - Codec - Macros
+
+use crate::*;
+
+// What the `#[codec(...)]` attributes of a struct or enum ask for.
+pub(crate) struct ContainerAttrs {
+    pub(crate) tag_type: Option<String>,
+    pub(crate) versioned: bool,
+    pub(crate) big_endian: Option<Span>,
+}
+
+// What the `#[codec(...)]` attributes of a field ask for.
+pub(crate) struct FieldAttrs {
+    pub(crate) skip: bool,
+    pub(crate) default: Option<String>,
+    pub(crate) with: Option<String>,
+    pub(crate) since: Option<u16>,
+    pub(crate) since_span: Span,
+    pub(crate) big_endian: bool,
+}
+
+// What the `#[codec(...)]` attributes of an enum item ask for.
+pub(crate) struct ItemAttrs {
+    pub(crate) tag: Option<u32>,
+    pub(crate) span: Span,
+}
+
+// One `name` or `name = literal` inside `#[codec(...)]`.
+struct CodecArg {
+    name: String,
+    value: Option<String>,
+    span: Span,
+}
+
+impl Lexer {
+
+    // CodecAttr = `codec` `(` CodecArg { `,` CodecArg } [ `,` ] `)` .
+    // CodecArg = IDENTIFIER [ `=` LITERAL ] .
+    fn parse_codec_args(&mut self) -> Vec<CodecArg> {
+        let mut args = Vec::<CodecArg>::new();
+        if self.parse_ident("codec") {
+            if let Some(group) = self.parse_paren_group() {
+                let mut lexer = Lexer::new_in(&group);
+                loop {
+                    let span = lexer.span();
+                    if let Some(name) = lexer.parse_some_ident() {
+                        let value = if lexer.parse_punct('=') {
+                            if let Some(literal) = lexer.parse_some_literal() {
+                                Some(literal)
+                            }
+                            else {
+                                abort!(lexer.span(),"literal expected after `{} =`",name);
+                            }
+                        }
+                        else {
+                            None
+                        };
+                        args.push(CodecArg { name: name, value: value, span: span, });
+                        lexer.parse_punct(',');
+                    }
+                    else {
+                        break;
+                    }
+                }
+                if lexer.token.is_some() {
+                    abort!(lexer.span(),"`codec` attribute corrupt");
+                }
+            }
+            else {
+                abort!(self.span(),"`(` expected after `codec`");
+            }
+        }
+        args
+    }
+}
+
+// All `codec` arguments in `attrs`, other attributes are left alone.
+fn codec_args(attrs: &Vec<Group>) -> Vec<CodecArg> {
+    let mut args = Vec::<CodecArg>::new();
+    for attr in attrs {
+        let mut lexer = Lexer::new_in(attr);
+        args.append(&mut lexer.parse_codec_args());
+    }
+    args
+}
+
+fn string_value(arg: &CodecArg) -> String {
+    if let Some(value) = &arg.value {
+        if (value.len() >= 2) && value.starts_with('"') && value.ends_with('"') {
+            return value[1..value.len() - 1].to_string();
+        }
+    }
+    abort!(arg.span,"`{}` expects a string, like `{} = \"...\"`",arg.name,arg.name);
+}
+
+fn u32_value(arg: &CodecArg) -> u32 {
+    if let Some(value) = &arg.value {
+        if let Some(value) = parse_integer(value) {
+            if value <= u32::MAX as u64 {
+                return value as u32;
+            }
+        }
+    }
+    abort!(arg.span,"`{}` expects an integer that fits a u32, like `{} = 3`",arg.name,arg.name);
+}
+
+fn u16_value(arg: &CodecArg) -> u16 {
+    let value = u32_value(arg);
+    if value > u16::MAX as u32 {
+        abort!(arg.span,"`{}` expects an integer that fits a u16, like `{} = 2`",arg.name,arg.name);
+    }
+    value as u16
+}
+
+pub(crate) fn container_attrs(attrs: &Vec<Group>,is_enum: bool) -> ContainerAttrs {
+    let mut result = ContainerAttrs { tag_type: None, versioned: false, big_endian: None, };
+    for arg in codec_args(attrs) {
+        match arg.name.as_str() {
+            "tag_type" if is_enum => {
+                let tag_type = string_value(&arg);
+                match tag_type.as_str() {
+                    "u8" | "u16" | "u32" => { },
+                    _ => { abort!(arg.span,"`tag_type` should be \"u8\", \"u16\" or \"u32\""); },
+                }
+                result.tag_type = Some(tag_type);
+            },
+            "tag_type" => {
+                abort!(arg.span,"`tag_type` only applies to enums");
+            },
+            "versioned" if !is_enum => {
+                if arg.value.is_some() {
+                    abort!(arg.span,"`versioned` takes no value");
+                }
+                result.versioned = true;
+            },
+            "versioned" => {
+                abort!(arg.span,"`versioned` only applies to structs");
+            },
+            "big_endian" => {
+                if arg.value.is_some() {
+                    abort!(arg.span,"`big_endian` takes no value");
+                }
+                result.big_endian = Some(arg.span);
+            },
+            name => {
+                abort!(arg.span,"unknown attribute `codec({})`",name);
+            },
+        }
+    }
+    result
+}
+
+pub(crate) fn field_attrs(attrs: &Vec<Group>) -> FieldAttrs {
+    let mut result = FieldAttrs { skip: false, default: None, with: None, since: None, since_span: Span::call_site(), big_endian: false, };
+    let mut default_span = None;
+    for arg in codec_args(attrs) {
+        match arg.name.as_str() {
+            "skip" => {
+                if arg.value.is_some() {
+                    abort!(arg.span,"`skip` takes no value");
+                }
+                if result.with.is_some() {
+                    abort!(arg.span,"`skip` and `with` cannot be combined");
+                }
+                if result.since.is_some() {
+                    abort!(arg.span,"`skip` and `since` cannot be combined");
+                }
+                if result.big_endian {
+                    abort!(arg.span,"`skip` and `big_endian` cannot be combined");
+                }
+                result.skip = true;
+            },
+            "default" => {
+                result.default = Some(string_value(&arg));
+                default_span = Some(arg.span);
+            },
+            "with" => {
+                if result.skip {
+                    abort!(arg.span,"`skip` and `with` cannot be combined");
+                }
+                if result.big_endian {
+                    abort!(arg.span,"`with` and `big_endian` cannot be combined");
+                }
+                result.with = Some(string_value(&arg));
+            },
+            "since" => {
+                if result.skip {
+                    abort!(arg.span,"`skip` and `since` cannot be combined");
+                }
+                result.since = Some(u16_value(&arg));
+                result.since_span = arg.span;
+            },
+            "big_endian" => {
+                if arg.value.is_some() {
+                    abort!(arg.span,"`big_endian` takes no value");
+                }
+                if result.skip {
+                    abort!(arg.span,"`skip` and `big_endian` cannot be combined");
+                }
+                if result.with.is_some() {
+                    abort!(arg.span,"`with` and `big_endian` cannot be combined");
+                }
+                result.big_endian = true;
+            },
+            name => {
+                abort!(arg.span,"unknown field attribute `codec({})`",name);
+            },
+        }
+    }
+    if let Some(span) = default_span {
+        if !result.skip && result.since.is_none() {
+            abort!(span,"`default` only applies to fields with `skip` or `since`, like `#[codec(skip,default = \"...\")]`");
+        }
+    }
+    result
+}
+
+pub(crate) fn item_attrs(attrs: &Vec<Group>) -> ItemAttrs {
+    let mut result = ItemAttrs { tag: None, span: Span::call_site(), };
+    for arg in codec_args(attrs) {
+        match arg.name.as_str() {
+            "tag" => {
+                result.tag = Some(u32_value(&arg));
+                result.span = arg.span;
+            },
+            name => {
+                abort!(arg.span,"unknown enum item attribute `codec({})`",name);
+            },
+        }
+    }
+    result
+}
@@ -2,11 +2,47 @@
 
 use crate::*;
 
-pub(crate) struct Expr { }
+pub(crate) enum Expr {
+    Integer(u64),
+    Other(String),
+}
 
 impl fmt::Display for Expr {
     fn fmt(&self,f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f,"Expr")
+        match self {
+            Expr::Integer(value) => write!(f,"Expr::Integer({})",value),
+            Expr::Other(expr) => write!(f,"Expr::Other({})",expr),
+        }
+    }
+}
+
+// Value of an integer literal like `12`, `0x0C`, `0b1100`, `0o14` or
+// `1_000u32`.
+pub(crate) fn parse_integer(literal: &str) -> Option<u64> {
+    let literal = literal.replace('_',"");
+    let (digits,radix) = if literal.starts_with("0x") {
+        (&literal[2..],16)
+    }
+    else if literal.starts_with("0o") {
+        (&literal[2..],8)
+    }
+    else if literal.starts_with("0b") {
+        (&literal[2..],2)
+    }
+    else {
+        (&literal[..],10)
+    };
+    let end = digits.find(|c: char| !c.is_digit(radix)).unwrap_or(digits.len());
+    match &digits[end..] {
+        "" | "u8" | "u16" | "u32" | "u64" | "u128" | "usize" | "i8" | "i16" | "i32" | "i64" | "i128" | "isize" => {
+            if end > 0 {
+                u64::from_str_radix(&digits[..end],radix).ok()
+            }
+            else {
+                None
+            }
+        },
+        _ => None,
     }
 }
 
@@ -34,21 +70,44 @@ impl fmt::Display for Visibility {
 
 impl Lexer {
 
+    // Expr = any tokens up to the next `,` .
+    //
+    // Integer literals are evaluated, anything else is kept as is.
     pub(crate) fn parse_expr(&mut self) -> Option<Expr> {
-        None
+        let mut tokens = Vec::<TokenTree>::new();
+        while let Some(token) = &self.token {
+            if self.is_punct(',') {
+                break;
+            }
+            tokens.push(token.clone());
+            self.step();
+        }
+        if tokens.len() == 0 {
+            None
+        }
+        else {
+            if tokens.len() == 1 {
+                if let TokenTree::Literal(literal) = &tokens[0] {
+                    if let Some(value) = parse_integer(&literal.to_string()) {
+                        return Some(Expr::Integer(value));
+                    }
+                }
+            }
+            Some(Expr::Other(tokens.into_iter().collect::<TokenStream>().to_string()))
+        }
     }
 
     // Visibility = `pub` [ `(` `crate` | `self` | `super` | ( `in` Path ) `)` ] .
     pub(crate) fn parse_visibility(&mut self) -> Visibility {
         if self.parse_ident("pub") {
             if let Some(group) = self.parse_paren_group() {
-                let mut lexer = Lexer::new(group.stream());
+                let mut lexer = Lexer::new_in(&group);
                 if lexer.parse_ident("in") {
                     if let Some(path) = lexer.parse_path() {
                         Visibility::PubIn(path)
                     }
                     else {
-                        panic!("crate path expected after `in`");
+                        abort!(lexer.span(),"crate path expected after `in`");
                     }
                 }
                 else if lexer.parse_ident("crate") {
@@ -83,6 +83,7 @@ pub(crate) struct DiscrItem {
     pub(crate) visibility: Visibility,
     pub(crate) ident: String,
     pub(crate) expr: Expr,
+    pub(crate) span: Span,
 }
 
 impl fmt::Display for DiscrItem {
@@ -190,12 +191,15 @@ impl Lexer {
         let visibility = self.parse_visibility();
         if let Some(ident) = self.parse_some_ident() {
             if let Some(group) = self.parse_paren_group() {
-                let mut lexer = Lexer::new(group.stream());
+                let mut lexer = Lexer::new_in(&group);
                 let mut fields = Vec::<TupleField>::new();
                 while let Some(field) = lexer.parse_tuple_field() {
                     fields.push(field);
                     lexer.parse_punct(',');
                 }
+                if lexer.token.is_some() {
+                    abort!(lexer.span(),"unsupported field type, expected a path, tuple, array, slice or reference");
+                }
                 Some(EnumItem::Tuple(TupleItem {
                     attrs: attrs,
                     visibility: visibility,
@@ -204,12 +208,15 @@ impl Lexer {
                 }))
             }
             else if let Some(group) = self.parse_brace_group() {
-                let mut lexer = Lexer::new(group.stream());
+                let mut lexer = Lexer::new_in(&group);
                 let mut fields = Vec::<StructField>::new();
                 while let Some(field) = lexer.parse_struct_field() {
                     fields.push(field);
                     lexer.parse_punct(',');
                 }
+                if lexer.token.is_some() {
+                    abort!(lexer.span(),"struct field expected");
+                }
                 Some(EnumItem::Struct(StructItem {
                     attrs: attrs,
                     visibility: visibility,
@@ -218,16 +225,18 @@ impl Lexer {
                 }))
             }
             else if self.parse_punct('=') {
+                let span = self.span();
                 if let Some(expr) = self.parse_expr() {
                     Some(EnumItem::Discr(DiscrItem {
                         attrs: attrs,
                         visibility: visibility,
                         ident: ident,
                         expr: expr,
+                        span: span,
                     }))
                 }
                 else {
-                    panic!("expression expected after `=`");
+                    abort!(self.span(),"expression expected after `=`");
                 }
             }
             else {
@@ -250,12 +259,15 @@ impl Lexer {
                 let generics = self.parse_generics();
                 let wheres = self.parse_wheres();
                 if let Some(group) = self.parse_brace_group() {
-                    let mut lexer = Lexer::new(group.stream());
+                    let mut lexer = Lexer::new_in(&group);
                     let mut items = Vec::<EnumItem>::new();
                     while let Some(item) = lexer.parse_enum_item() {
                         items.push(item);
                         lexer.parse_punct(',');
                     }
+                    if lexer.token.is_some() {
+                        abort!(lexer.span(),"enum item expected");
+                    }
                     Some(Enum {
                         attrs: attrs,
                         visibility: visibility,
@@ -266,11 +278,11 @@ impl Lexer {
                     })
                 }
                 else {
-                    panic!("{}","`{` expected to describe enum");
+                    abort!(self.span(),"{}","`{` expected to describe enum");
                 }
             }
             else {
-                panic!("identifier expected after `enum`");
+                abort!(self.span(),"identifier expected after `enum`");
             }
         }
         else {
@@ -57,6 +57,10 @@ pub(crate) enum Generic {
         bounds: Vec<TypeParamBound>,
         ty: Option<Box<Type>>,
     },
+    Const {
+        ident: String,
+        ty: Box<Type>,
+    },
 }
 
 impl fmt::Display for Generic {
@@ -64,16 +68,19 @@ impl fmt::Display for Generic {
         match self {
             Generic::Lifetime { ident,bounds } => {
                 let mut a = String::new();
-                a += &format!("'{}: ",ident);
-                let mut first = true;
-                for bound in bounds {
-                    if first {
-                        first = false;
-                    }
-                    else {
-                        a += " + ";
+                a += &format!("'{}",ident);
+                if bounds.len() > 0 {
+                    a += ": ";
+                    let mut first = true;
+                    for bound in bounds {
+                        if first {
+                            first = false;
+                        }
+                        else {
+                            a += " + ";
+                        }
+                        a += &format!("'{}",bound);
                     }
-                    a += &format!("'{}",bound);
                 }
                 write!(f,"{}",a)
             },
@@ -98,6 +105,9 @@ impl fmt::Display for Generic {
                 }
                 write!(f,"{}",a)
             },
+            Generic::Const { ident,ty } => {
+                write!(f,"const {}: {}",ident,ty)
+            },
         }
     }
 }
@@ -116,17 +126,17 @@ impl Lexer {
                             for_lifetimes.push(ident);
                         }
                         else {
-                            panic!("identifier expected after `'`");
+                            abort!(self.span(),"identifier expected after `'`");
                         }
                         self.parse_punct(',');
                     }
                     else {
-                        panic!("lifetime expected in `for <` `>`");
+                        abort!(self.span(),"lifetime expected in `for <` `>`");
                     }
                 }
             }
             else {
-                panic!("`<` expected after `for`");
+                abort!(self.span(),"`<` expected after `for`");
             }
         }
         if let Some(path) = self.parse_path() {
@@ -148,17 +158,17 @@ impl Lexer {
                 Some(TypeParamBound::Lifetime(ident.to_string()))
             }
             else {
-                panic!("identifier expected after `'`");
+                abort!(self.span(),"identifier expected after `'`");
             }
         }
         else {
             if let Some(group) = self.parse_paren_group() {
-                let mut lexer = Lexer::new(group.stream());
+                let mut lexer = Lexer::new_in(&group);
                 if let Some(trait_bound) = lexer.parse_trait_bound() {
                     Some(TypeParamBound::Trait(trait_bound))
                 }
                 else {
-                    panic!("trait bound expected");
+                    abort!(lexer.span(),"trait bound expected");
                 }
             }
             else {
@@ -174,7 +184,8 @@ impl Lexer {
 
     // TypeParam = IDENTIFIER [ `:` [ TypeParamBound { `+` TypeParamBound } [ `+` ] ] ] [ `=` Type ] .
     // LifetimeParam = `'` IDENTIFIER [ `:` `'` IDENTIFIER { `+` `'` IDENTIFIER } [ `+` ] ] .
-    // Generic = LifetimeParam | TypeParam .
+    // ConstParam = `const` IDENTIFIER `:` Type [ `=` [ `-` ] ( LITERAL | IDENTIFIER | BlockExpr ) ] .
+    // Generic = LifetimeParam | ConstParam | TypeParam .
     // Generics = `<` Generic { `,` Generic } [ `,` ] `>` .
     pub(crate) fn parse_generics(&mut self) -> Vec<Generic> {
         if self.parse_punct('<') {
@@ -187,11 +198,11 @@ impl Lexer {
                         let mut bounds = Vec::<String>::new();
                         if self.parse_punct(':') {
                             while self.parse_punct('\'') {
-                                if self.is_some_ident() {
-                                    bounds.push(ident.to_string());
+                                if let Some(bound) = self.parse_some_ident() {
+                                    bounds.push(bound);
                                 }
                                 else {
-                                    panic!("identifier expected after `'`");
+                                    abort!(self.span(),"identifier expected after `'`");
                                 }
                                 self.parse_punct('+');
                             }
@@ -202,7 +213,34 @@ impl Lexer {
                         });
                     }
                     else {
-                        panic!("identifier expected after `'`");
+                        abort!(self.span(),"identifier expected after `'`");
+                    }
+                }
+
+                // ConstParam, the default is not needed for the impls
+                else if self.parse_ident("const") {
+                    if let Some(ident) = self.parse_some_ident() {
+                        if self.parse_punct(':') {
+                            if let Some(ty) = self.parse_type() {
+                                if self.parse_punct('=') {
+                                    self.parse_punct('-');
+                                    self.step();
+                                }
+                                params.push(Generic::Const {
+                                    ident: ident,
+                                    ty: Box::new(ty),
+                                });
+                            }
+                            else {
+                                abort!(self.span(),"type expected after `:`");
+                            }
+                        }
+                        else {
+                            abort!(self.span(),"`:` expected after const parameter");
+                        }
+                    }
+                    else {
+                        abort!(self.span(),"identifier expected after `const`");
                     }
                 }
 
@@ -215,15 +253,18 @@ impl Lexer {
                             if let Some(bound) = self.parse_type_param_bound() {
                                 bounds.push(bound);
                             }
-                            self.parse_punct('+');
-                        }
-                        if self.parse_punct('=') {
-                            if let Some(t) = self.parse_type() {
-                                ty = Some(Box::new(t));
-                            }
                             else {
-                                panic!("type expected after `=`");
+                                abort!(self.span(),"bound expected after `:`");
                             }
+                            self.parse_punct('+');
+                        }
+                    }
+                    if self.parse_punct('=') {
+                        if let Some(t) = self.parse_type() {
+                            ty = Some(Box::new(t));
+                        }
+                        else {
+                            abort!(self.span(),"type expected after `=`");
                         }
                     }
                     params.push(Generic::Type {
@@ -232,6 +273,10 @@ impl Lexer {
                         ty: ty,
                     });
                 }
+                else {
+                    abort!(self.span(),"lifetime or type parameter expected");
+                }
+                self.parse_punct(',');
             }
             self.parse_punct('>');
             params
@@ -5,6 +5,7 @@ use crate::*;
 pub struct Lexer {
     pub token: Option<TokenTree>,
     pub stream: IntoIter,
+    pub end: Span,
 }
 
 impl Lexer {
@@ -15,6 +16,25 @@ impl Lexer {
         Lexer {
             token: token,
             stream: stream,
+            end: Span::call_site(),
+        }
+    }
+
+    // Lexer over the inside of a group, errors at the end point at the
+    // closing delimiter.
+    pub fn new_in(group: &Group) -> Lexer {
+        let mut lexer = Lexer::new(group.stream());
+        lexer.end = group.span_close();
+        lexer
+    }
+
+    // Where to point errors at: the current token, or the end.
+    pub fn span(&self) -> Span {
+        if let Some(token) = &self.token {
+            token.span()
+        }
+        else {
+            self.end
         }
     }
 
@@ -46,6 +66,17 @@ impl Lexer {
         }
     }
 
+    pub fn is_punct2(&self,c0: char,c1: char) -> bool {
+        if let Some(TokenTree::Punct(punct)) = &self.token {
+            if (punct.as_char() == c0) && (punct.spacing() == Spacing::Joint) {
+                if let Some(TokenTree::Punct(punct)) = self.stream.clone().next() {
+                    return punct.as_char() == c1;
+                }
+            }
+        }
+        false
+    }
+
     pub fn parse_punct2(&mut self,c0: char,c1: char) -> bool {
         if let Some(TokenTree::Punct(punct)) = &self.token {
             if (punct.as_char() == c0) && (punct.spacing() == Spacing::Joint) {
@@ -191,4 +222,15 @@ impl Lexer {
             None
         }
     }
+
+    pub fn parse_some_literal(&mut self) -> Option<String> {
+        if let Some(TokenTree::Literal(literal)) = &self.token {
+            let literal = literal.to_string();
+            self.step();
+            Some(literal)
+        }
+        else {
+            None
+        }
+    }
 }
@@ -8,6 +8,12 @@ use {
         token_stream::IntoIter,
         Spacing,
         Group,
+        Span,
+    },
+    proc_macro_error::{
+        abort,
+        abort_call_site,
+        proc_macro_error,
     },
     std::fmt,
 };
@@ -36,6 +42,9 @@ use r#struct::*;
 mod r#enum;
 use r#enum::*;
 
+mod attrs;
+use attrs::*;
+
 pub(crate) enum Item {
     Struct(Struct),
     Tuple(Tuple),
@@ -114,9 +123,12 @@ impl Lexer {
                 }
             }
             else {
-                panic!("struct corrupt");
+                abort!(self.span(),"struct corrupt");
             }
         }
+        else if self.is_ident("union") {
+            abort!(self.span(),"`#[derive(Codec)]` does not support unions, the active field is not known");
+        }
         else if let Some(e) = self.parse_enum(attrs,visibility) {
             Some(Item::Enum(e))
         }
@@ -126,8 +138,11 @@ impl Lexer {
     }
 }
 
-fn render_expr(_: &Expr) -> String {
-    String::new()
+fn render_expr(expr: &Expr) -> String {
+    match expr {
+        Expr::Integer(value) => value.to_string(),
+        Expr::Other(expr) => expr.clone(),
+    }
 }
 
 fn render_path(path: &Path) -> String {
@@ -172,6 +187,9 @@ fn render_path(path: &Path) -> String {
                             r += " as ";
                             r += &render_path(path.as_ref());
                         },
+                        GenericArg::Const(expr) => {
+                            r += &expr;
+                        },
                     }
                 }
                 r += ">";
@@ -203,25 +221,602 @@ fn render_type(ty: &Type) -> String {
             r += "]";
             r
         },
+        Type::Slice(ty) => {
+            let mut r = "[".to_string();
+            r += &render_type(ty);
+            r += "]";
+            r
+        },
+        Type::Ref { lifetime,mutable,ty } => {
+            let mut r = "&".to_string();
+            if let Some(lifetime) = lifetime {
+                r += "'";
+                r += &lifetime;
+                r += " ";
+            }
+            if *mutable {
+                r += "mut ";
+            }
+            r += &render_type(ty);
+            r
+        },
+    }
+}
+
+fn render_bounds(bounds: &Vec<TypeParamBound>) -> String {
+    let mut r = String::new();
+    for bound in bounds {
+        if r.len() > 0 {
+            r += " + ";
+        }
+        match bound {
+            TypeParamBound::Lifetime(ident) => {
+                r += "'";
+                r += &ident;
+            },
+            TypeParamBound::Trait(t) => {
+                if t.question {
+                    r += "?";
+                }
+                if t.for_lifetimes.len() > 0 {
+                    r += "for<";
+                    for lt in &t.for_lifetimes {
+                        r += "'";
+                        r += &lt;
+                        r += ",";
+                    }
+                    r += "> ";
+                }
+                r += &render_path(&t.path);
+            },
+        }
+    }
+    r
+}
+
+fn render_lifetime_bounds(bounds: &Vec<String>) -> String {
+    let mut r = String::new();
+    for bound in bounds {
+        if r.len() > 0 {
+            r += " + ";
+        }
+        r += "'";
+        r += &bound;
+    }
+    r
+}
+
+// Opening of the decode and encode impls, and how to decode a field.
+//
+// The impls take over the generic parameters, their bounds and the where
+// clause of the item, and every type parameter gets a `Codec` bound for
+// decoding and an `Encode` bound for encoding. Items with lifetime
+// parameters may borrow from the buffer, so they get a `BorrowDecode` impl
+// for the buffer lifetime `'__de` instead of a `Codec` impl, which needs
+// `BorrowDecode<'__de>` of the type parameters.
+struct Header {
+    decode: String,
+    encode: String,
+    call: String,
+}
+
+fn render_header(ident: &str,generics: &Vec<Generic>,wheres: &Vec<Where>) -> Header {
+
+    // parameters of the impl, and arguments of the item
+    let mut params = Vec::<String>::new();
+    let mut args = Vec::<String>::new();
+    let mut lifetimes = Vec::<String>::new();
+    let mut types = Vec::<String>::new();
+    for generic in generics {
+        match generic {
+            Generic::Lifetime { ident,bounds } => {
+                let mut param = format!("'{}",ident);
+                if bounds.len() > 0 {
+                    param += ": ";
+                    param += &render_lifetime_bounds(bounds);
+                }
+                params.push(param);
+                args.push(format!("'{}",ident));
+                lifetimes.push(ident.clone());
+            },
+            Generic::Type { ident,bounds,.. } => {
+                let mut param = ident.clone();
+                if bounds.len() > 0 {
+                    param += ": ";
+                    param += &render_bounds(bounds);
+                }
+                params.push(param);
+                args.push(ident.clone());
+                types.push(ident.clone());
+            },
+            Generic::Const { ident,ty } => {
+                params.push(format!("const {}: {}",ident,render_type(ty)));
+                args.push(ident.clone());
+            },
+        }
+    }
+    let mut item = ident.to_string();
+    if args.len() > 0 {
+        item += "<";
+        item += &args.join(",");
+        item += ">";
+    }
+
+    // the where clause of the item
+    let mut user_wheres = Vec::<String>::new();
+    for w in wheres {
+        match w {
+            Where::Lifetime { ident,bounds } => {
+                user_wheres.push(format!("'{}: {}",ident,render_lifetime_bounds(bounds)));
+            },
+            Where::Type { for_lifetimes,ty,bounds } => {
+                let mut r = String::new();
+                if for_lifetimes.len() > 0 {
+                    r += "for<";
+                    for lt in for_lifetimes {
+                        r += "'";
+                        r += &lt;
+                        r += ",";
+                    }
+                    r += "> ";
+                }
+                r += &render_type(ty);
+                r += ": ";
+                r += &render_bounds(bounds);
+                user_wheres.push(r);
+            },
+        }
+    }
+
+    let borrow = lifetimes.len() > 0;
+    let mut decode_params = params.clone();
+    let mut decode_wheres = Vec::<String>::new();
+    let mut encode_wheres = Vec::<String>::new();
+    if borrow {
+        decode_params.insert(0,"'__de".to_string());
+        for lifetime in &lifetimes {
+            decode_wheres.push(format!("'__de: '{}",lifetime));
+        }
+    }
+    for ty in &types {
+        if borrow {
+            decode_wheres.push(format!("{}: codec::BorrowDecode<'__de>",ty));
+        }
+        else {
+            decode_wheres.push(format!("{}: codec::Codec",ty));
+        }
+        encode_wheres.push(format!("{}: codec::Encode",ty));
+    }
+    decode_wheres.extend(user_wheres.iter().cloned());
+    encode_wheres.extend(user_wheres.iter().cloned());
+
+    let mut decode = "impl".to_string();
+    if decode_params.len() > 0 {
+        decode += "<";
+        decode += &decode_params.join(",");
+        decode += ">";
+    }
+    decode += if borrow { " codec::BorrowDecode<'__de> for " } else { " codec::Codec for " };
+    decode += &item;
+    if decode_wheres.len() > 0 {
+        decode += " where ";
+        decode += &decode_wheres.join(",");
+    }
+    decode += if borrow {
+        " { fn borrow_decode_with(b: &'__de [u8],ctx: &mut codec::DecodeContext) -> Result<(usize,Self),codec::DecodeError> { "
+    }
+    else {
+        " { fn decode_with(b: &[u8],ctx: &mut codec::DecodeContext) -> Result<(usize,Self),codec::DecodeError> { "
+    };
+
+    let mut encode = "impl".to_string();
+    if params.len() > 0 {
+        encode += "<";
+        encode += &params.join(",");
+        encode += ">";
+    }
+    encode += " codec::Encode for ";
+    encode += &item;
+    if encode_wheres.len() > 0 {
+        encode += " where ";
+        encode += &encode_wheres.join(",");
+    }
+    encode += " { ";
+
+    Header {
+        decode: decode,
+        encode: encode,
+        call: if borrow { " as codec::BorrowDecode<'__de>>::borrow_decode_with(".to_string() } else { " as codec::Codec>::decode_with(".to_string() },
+    }
+}
+
+// Integer and float types, the fields `#[codec(big_endian)]` on a struct or
+// enum applies to.
+const NUMBER_TYPES: [&str; 12] = ["u8","u16","u32","u64","u128","i8","i16","i32","i64","i128","f32","f64"];
+
+// Types without multi-byte numbers in them. `char` is not one of them, it is
+// encoded as a little-endian u32.
+const PLAIN_TYPES: [&str; 3] = ["bool","u8","i8"];
+
+// Types that start with their length as a little-endian u32.
+const PREFIXED_TYPES: [&str; 8] = ["str","String","Vec","VecDeque","HashSet","BTreeSet","HashMap","BTreeMap"];
+
+// Wrappers that choose the byte order of what they hold themselves.
+const ORDERED_TYPES: [&str; 2] = ["BigEndian","Varint"];
+
+// Containers whose type arguments are all there is to them.
+const CONTAINER_TYPES: [&str; 6] = ["Option","Box","Rc","Arc","Cow","PhantomData"];
+
+// Name of the integer or float type `ty`, also when written like
+// `std::primitive::u16`.
+fn number_type(ty: &Type) -> Option<&str> {
+    if let Type::Path(path) = ty {
+        let idents: Vec<&str> = path.segs.iter().filter_map(|seg| if let PathSeg::Ident(ident) = seg { Some(ident.as_str()) } else { None }).collect();
+        if idents.len() == path.segs.len() {
+            match idents.as_slice() {
+                [name] | ["std","primitive",name] | ["core","primitive",name] if NUMBER_TYPES.contains(name) => { return Some(name); },
+                _ => { },
+            }
+        }
+    }
+    None
+}
+
+// Whether `ty` has a length in it somewhere, like `String` or `&[u8]`.
+fn prefixed_type(ty: &Type) -> bool {
+    match ty {
+        Type::Path(path) => path.segs.iter().any(|seg| match seg {
+            PathSeg::Ident(ident) => PREFIXED_TYPES.contains(&ident.as_str()),
+            PathSeg::Generic(args) => args.iter().any(|arg| match arg {
+                GenericArg::Type(ty) => prefixed_type(ty),
+                GenericArg::Binding { ty,.. } => prefixed_type(ty),
+                _ => false,
+            }),
+        }),
+        Type::Tuple(types) => types.iter().any(prefixed_type),
+        Type::Slice(_) => true,
+        Type::Array { ty,.. } | Type::Ref { ty,.. } => prefixed_type(ty),
+    }
+}
+
+// Whether `ty` is the same in both byte orders, as far as the derive can see:
+// `Option<(bool,[u8; 4])>` is, `Option<u16>`, types with a length like
+// `String` and types it doesn't know, like other structs or aliases, are not.
+fn plain_type(ty: &Type) -> bool {
+    match ty {
+        Type::Path(path) => {
+            let mut name = None;
+            for seg in &path.segs {
+                match seg {
+                    PathSeg::Ident(ident) => name = Some(ident.as_str()),
+                    PathSeg::Generic(args) => {
+                        let name = name.unwrap_or("");
+                        if ORDERED_TYPES.contains(&name) {
+                            return true;
+                        }
+                        if !CONTAINER_TYPES.contains(&name) || !args.iter().all(|arg| match arg {
+                            GenericArg::Type(ty) => plain_type(ty),
+                            GenericArg::Binding { ty,.. } => plain_type(ty),
+                            _ => true,
+                        }) {
+                            return false;
+                        }
+                    },
+                }
+            }
+            name.is_some_and(|name| PLAIN_TYPES.contains(&name) || CONTAINER_TYPES.contains(&name))
+        },
+        Type::Tuple(types) => types.iter().all(plain_type),
+        Type::Array { ty,.. } | Type::Ref { ty,.. } => plain_type(ty),
+        Type::Slice(_) => false,
+    }
+}
+
+// Attributes of a field of type `ty`, `big_endian` also for the integer and
+// float fields of a `#[codec(big_endian)]` struct or enum. Other fields with
+// numbers in them, lengths included, can't be made big-endian from here, so
+// they are refused.
+fn container_field_attrs(attrs: &Vec<Group>,ty: &Type,big_endian: Option<Span>) -> FieldAttrs {
+    let mut result = field_attrs(attrs);
+    if let Some(span) = big_endian {
+        if !result.skip && result.with.is_none() && !result.big_endian {
+            if number_type(ty).is_some() {
+                result.big_endian = true;
+            }
+            else if render_type(ty) == "char" {
+                abort!(span,"`big_endian` on the type can't make `char` big-endian, it is always a little-endian u32, put `big_endian` on the number fields instead");
+            }
+            else if prefixed_type(ty) {
+                abort!(span,"`big_endian` on the type can't make the length of `{}` big-endian, it is always a little-endian u32, put `big_endian` on the number fields instead",render_type(ty));
+            }
+            else if !plain_type(ty) {
+                abort!(span,"`big_endian` on the type can't reach the numbers in `{}`, wrap them in `codec::BigEndian`, or put `big_endian` on the number fields instead",render_type(ty));
+            }
+        }
+    }
+    result
+}
+
+// Type a field goes on the wire as, big-endian fields as `codec::BigEndian`.
+fn render_field_type(ty: &Type,attrs: &FieldAttrs) -> String {
+    if attrs.big_endian {
+        format!("codec::BigEndian<{}>",render_type(ty))
+    }
+    else {
+        render_type(ty)
+    }
+}
+
+// Reference to the value of a field as it goes on the wire, `value` is a
+// reference to the field.
+fn render_field_value(value: &str,attrs: &FieldAttrs) -> String {
+    if attrs.big_endian {
+        format!("&codec::BigEndian(*{})",value)
+    }
+    else {
+        value.to_string()
+    }
+}
+
+// Value of a field that is not on the wire, from `Default` or from the
+// function given by `default`.
+fn render_field_default(ty: &Type,attrs: &FieldAttrs) -> String {
+    if let Some(default) = &attrs.default {
+        format!("{}()",default)
+    }
+    else {
+        format!("<{} as Default>::default()",render_type(ty))
+    }
+}
+
+// Decode a field into `name`. Skipped fields are not on the wire, and fields
+// added `since` a later version than the encoder's are missing, both are
+// rebuilt with `render_field_default`. Fields of a versioned struct are
+// decoded from its `body`, which has all its bytes, so running out of them
+// is an error in the length.
+fn render_field_decode(h: &Header,versioned: bool,name: &str,ty: &Type,attrs: &FieldAttrs) -> String {
+    if attrs.skip {
+        return format!("let {} = {}; ",name,render_field_default(ty,attrs));
+    }
+    let mut r = "let (l,".to_string();
+    r += name;
+    r += ") = ";
+    if let Some(with) = &attrs.with {
+        r += with;
+        r += "::decode_with(";
+    }
+    else {
+        r += "<";
+        r += &render_field_type(ty,attrs);
+        r += &h.call;
+    }
+    if versioned {
+        r += "&body[ofs..],ctx).map_err(|e| codec::versioned_field_error(e,ofs,length))?; ofs += l; ";
+    }
+    else {
+        r += "&b[ofs..],ctx).map_err(|e| e.at(ofs))?; ofs += l; ";
+    }
+    if attrs.big_endian {
+        r += &format!("let {} = {}.0; ",name,name);
+    }
+    if let Some(since) = attrs.since {
+        format!("let {} = if version < {} {{ {} }} else {{ {}{} }}; ",name,since,render_field_default(ty,attrs),r,name)
+    }
+    else {
+        r
+    }
+}
+
+// Version of a `#[codec(versioned)]` struct, the highest `since` of its
+// fields. Fields added in a later version have to come after the others,
+// so older decoders find the fields they know at the start.
+fn struct_version(versioned: bool,attrs: &[FieldAttrs]) -> u16 {
+    let mut version = 0u16;
+    let mut last = None;
+    for attrs in attrs {
+        if let Some(since) = attrs.since {
+            if !versioned {
+                abort!(attrs.since_span,"`since` only applies to fields of `#[codec(versioned)]` structs");
+            }
+            if since < version {
+                abort!(attrs.since_span,"fields have to be in the order of their `since` versions, this field is older than the one before");
+            }
+            version = since;
+            last = Some(attrs.since_span);
+        }
+        else if let Some(span) = last {
+            if !attrs.skip {
+                abort!(span,"the fields after a field with `since` need a `since` too");
+            }
+        }
+    }
+    version
+}
+
+// Start of `decode_with`, the fields are decoded one level deeper in
+// `ctx.nested`. A versioned struct starts with the version of the encoder
+// (u16) and the length of its fields (u32), only the fields within that
+// length are decoded.
+fn render_decode_start(versioned: bool,version: u16) -> String {
+    if versioned {
+        format!("ctx.nested(|ctx| {{ let (_,{}) = <u16 as codec::Codec>::decode_with(b,ctx)?; let (_,length) = <u32 as codec::Codec>::decode_with(&b[2..],ctx).map_err(|e| e.at(2))?; let end = 6usize.checked_add(length as usize).ok_or(codec::DecodeError::LengthOverflow {{ offset: 2, length: length as u64, }})?; codec::ensure(b,end)?; let body = &b[..end]; let mut ofs = 6usize; ",if version > 0 { "version" } else { "_" })
+    }
+    else {
+        "ctx.nested(|ctx| { let mut ofs = 0usize; ".to_string()
+    }
+}
+
+// Start of `encode`, the version and the length of the fields of a
+// versioned struct. The length is a placeholder that is filled in at the
+// end, so fields like `Compressed` that only know their size after encoding
+// are encoded once.
+fn render_encode_start(versioned: bool,version: u16) -> String {
+    if !versioned {
+        "let mut ofs = 0usize; ".to_string()
+    }
+    else {
+        format!("let start = b.len(); let mut ofs = codec::Encode::encode(&{}u16,b); ofs += codec::Encode::encode(&0u32,b); ",version)
+    }
+}
+
+// End of `encode`, fills in the length of the fields of a versioned struct.
+// A `Sink` may have passed bytes on already, so versioned structs don't
+// implement `encode_to`, the default one encodes them whole with `encode`.
+fn render_encode_end(versioned: bool) -> String {
+    if versioned {
+        "b[start + 2..start + 6].copy_from_slice(&((ofs - 6) as u32).to_le_bytes()); ofs } ".to_string()
+    }
+    else {
+        "ofs } ".to_string()
+    }
+}
+
+// `FIXED_SIZE` of a field, skipped fields take no bytes, and the size of
+// fields encoded `with` a module is not known.
+fn render_field_fixed_size(ty: &Type,attrs: &FieldAttrs) -> Option<String> {
+    if attrs.skip {
+        None
+    }
+    else if attrs.with.is_some() {
+        Some("None".to_string())
+    }
+    else {
+        Some(format!("<{} as codec::Encode>::FIXED_SIZE",render_field_type(ty,attrs)))
+    }
+}
+
+// `codec::fixed_size_sum(&[...])` of fields.
+fn render_fixed_size_sum<'a>(fields: impl Iterator<Item = (&'a Type,&'a FieldAttrs)>) -> String {
+    let sizes: Vec<String> = fields.filter_map(|(ty,attrs)| render_field_fixed_size(ty,attrs)).collect();
+    format!("codec::fixed_size_sum(&[{}])",sizes.join(","))
+}
+
+// `MIN_SIZE` of a field, skipped fields take no bytes, and nothing is known
+// about fields encoded `with` a module.
+fn render_field_min_size(ty: &Type,attrs: &FieldAttrs) -> Option<String> {
+    if attrs.skip {
+        None
+    }
+    else if attrs.with.is_some() {
+        Some("0".to_string())
+    }
+    else {
+        Some(format!("<{} as codec::Encode>::MIN_SIZE",render_field_type(ty,attrs)))
+    }
+}
+
+// `codec::min_size_sum(&[...])` of fields.
+fn render_min_size_sum<'a>(fields: impl Iterator<Item = (&'a Type,&'a FieldAttrs)>) -> String {
+    let sizes: Vec<String> = fields.filter_map(|(ty,attrs)| render_field_min_size(ty,attrs)).collect();
+    format!("codec::min_size_sum(&[{}])",sizes.join(","))
+}
+
+// `codec::Field` of a field, skipped fields are not on the wire, and fields
+// encoded `with` a module do not describe their layout.
+fn render_field_schema(name: &str,ty: &Type,attrs: &FieldAttrs) -> Option<String> {
+    let name = name.trim_start_matches("r#");
+    if attrs.skip {
+        None
+    }
+    else if let Some(with) = &attrs.with {
+        Some(format!("codec::Field {{ name: \"{}\".to_string(), schema: codec::Schema::Opaque(\"{}\".to_string()), }}",name,with))
+    }
+    else {
+        Some(format!("codec::Field {{ name: \"{}\".to_string(), schema: <{} as codec::Encode>::schema(), }}",name,render_field_type(ty,attrs)))
+    }
+}
+
+// `fn schema()` of a struct or enum called `ident`, recursive types refer
+// back to themselves with `Schema::Recursive`.
+fn render_schema(ident: &str,schema: &str) -> String {
+    format!("fn schema() -> codec::Schema {{ codec::named_schema::<Self>(\"{}\",|| {}) }} ",ident,schema)
+}
+
+// Encode a field, `value` is a reference to it.
+fn render_field_encode(value: &str,attrs: &FieldAttrs) -> String {
+    if attrs.skip {
+        String::new()
+    }
+    else if let Some(with) = &attrs.with {
+        format!("ofs += {}::encode({},b); ",with,value)
+    }
+    else {
+        format!("ofs += codec::Encode::encode({},b); ",render_field_value(value,attrs))
+    }
+}
+
+fn render_field_size(value: &str,attrs: &FieldAttrs) -> String {
+    if attrs.skip {
+        String::new()
+    }
+    else if let Some(with) = &attrs.with {
+        format!("ofs += {}::size({}); ",with,value)
+    }
+    else {
+        format!("ofs += codec::Encode::size({}); ",render_field_value(value,attrs))
+    }
+}
+
+fn render_field_encode_to(value: &str,attrs: &FieldAttrs) -> String {
+    if attrs.skip {
+        String::new()
+    }
+    else if let Some(with) = &attrs.with {
+        format!("ofs += {}::encode({},s.buffer()); s.commit()?; ",with,value)
+    }
+    else {
+        format!("ofs += codec::Encode::encode_to({},s)?; ",render_field_value(value,attrs))
+    }
+}
+
+// `FIXED_SIZE` of a struct whose fields sum up to `sum`, a versioned struct
+// adds 6 bytes of version and length.
+fn render_struct_fixed_size(versioned: bool,sum: &str) -> String {
+    if versioned {
+        format!("codec::fixed_size_sum(&[Some(6),{}])",sum)
+    }
+    else {
+        sum.to_string()
+    }
+}
+
+// `MIN_SIZE` of a struct, a versioned struct adds 6 bytes of version and
+// length, and only the fields without `since` are there in every version.
+fn render_struct_min_size<'a>(versioned: bool,fields: impl Iterator<Item = (&'a Type,&'a FieldAttrs)>) -> String {
+    if versioned {
+        format!("codec::min_size_sum(&[6,{}])",render_min_size_sum(fields.filter(|(_,attrs)| attrs.since.is_none())))
+    }
+    else {
+        render_min_size_sum(fields)
+    }
+}
+
+// `codec::Schema::Struct` of a struct with `fields`, wrapped in
+// `codec::Schema::Versioned` for a versioned struct. The fields without
+// `since` are there in every version.
+fn render_struct_schema(versioned: bool,version: u16,attrs: &[FieldAttrs],ident: &str,fields: &[String]) -> String {
+    let schema = format!("codec::Schema::Struct {{ name: \"{}\".to_string(), fields: vec![{}], }}",ident,fields.join(","));
+    if versioned {
+        let base = attrs.iter().filter(|attrs| !attrs.skip && attrs.since.is_none()).count();
+        format!("codec::Schema::Versioned({},{},Box::new({}))",version,base,schema)
+    }
+    else {
+        schema
     }
 }
 
 fn render_struct(s: &Struct) -> String {
-    let mut r = "impl Codec for ".to_string();
-    r += &s.ident;
-    r += " { fn decode(b: &[u8]) -> Option<(usize,Self)> { let mut ofs = 0usize; ";
-    for field in &s.fields {
-        r += "let f_";
-        r += &field.ident;
-        r += " = if let Some((l,";
-        r += &field.ident;
-        r += ")) = ";
-        r += &render_type(field.ty.as_ref());
-        r += "::decode(&b[ofs..]) { ofs += l; ";
-        r += &field.ident;
-        r += " } else { return None; }; ";
+    let container = container_attrs(&s.attrs,false);
+    let versioned = container.versioned;
+    let h = render_header(&s.ident,&s.generics,&s.wheres);
+    let attrs: Vec<FieldAttrs> = s.fields.iter().map(|field| container_field_attrs(&field.attrs,field.ty.as_ref(),container.big_endian)).collect();
+    let version = struct_version(versioned,&attrs);
+    let mut r = h.decode.clone();
+    r += &render_decode_start(versioned,version);
+    for (field,attrs) in s.fields.iter().zip(attrs.iter()) {
+        r += &render_field_decode(&h,versioned,&format!("f_{}",field.ident),field.ty.as_ref(),attrs);
     }
-    r += " Some((ofs,";
+    r += if versioned { "Ok((end," } else { "Ok((ofs," };
     r += &s.ident;
     r += " { ";
     for field in &s.fields {
@@ -230,35 +825,52 @@ fn render_struct(s: &Struct) -> String {
         r += &field.ident;
         r += ", ";
     }
-    r += "})) } fn encode(&self,b: &mut Vec<u8>) -> usize { let mut ofs = 0usize; ";
-    for field in &s.fields {
-        r += "ofs += self.";
-        r += &field.ident;
-        r += ".encode(b); "
+    r += "})) }) } } ";
+    r += &h.encode;
+    r += "const FIXED_SIZE: Option<usize> = ";
+    r += &render_struct_fixed_size(versioned,&render_fixed_size_sum(s.fields.iter().zip(attrs.iter()).map(|(field,attrs)| (field.ty.as_ref(),attrs))));
+    r += "; ";
+    r += "const MIN_SIZE: usize = ";
+    r += &render_struct_min_size(versioned,s.fields.iter().zip(attrs.iter()).map(|(field,attrs)| (field.ty.as_ref(),attrs)));
+    r += "; ";
+    let fields: Vec<String> = s.fields.iter().zip(attrs.iter()).filter_map(|(field,attrs)| render_field_schema(&field.ident,field.ty.as_ref(),attrs)).collect();
+    r += &render_schema(&s.ident,&render_struct_schema(versioned,version,&attrs,&s.ident,&fields));
+    r += "fn encode(&self,b: &mut Vec<u8>) -> usize { ";
+    r += &render_encode_start(versioned,version);
+    for (field,attrs) in s.fields.iter().zip(attrs.iter()) {
+        r += &render_field_encode(&format!("&self.{}",field.ident),attrs);
     }
-    r += "ofs } fn size(&self) -> usize { let mut ofs = 0usize; ";
-    for field in &s.fields {
-        r += "ofs += self.";
-        r += &field.ident;
-        r += ".size(); ";
+    r += &render_encode_end(versioned);
+    r += "fn size(&self) -> usize { if let Some(size) = <Self as codec::Encode>::FIXED_SIZE { return size; } ";
+    r += if versioned { "let mut ofs = 6usize; " } else { "let mut ofs = 0usize; " };
+    for (field,attrs) in s.fields.iter().zip(attrs.iter()) {
+        r += &render_field_size(&format!("&self.{}",field.ident),attrs);
     }
-    r += "ofs } }";
+    r += "ofs } ";
+    if !versioned {
+        r += "fn encode_to(&self,s: &mut codec::Sink<'_>) -> std::io::Result<usize> { let mut ofs = 0usize; ";
+        for (field,attrs) in s.fields.iter().zip(attrs.iter()) {
+            r += &render_field_encode_to(&format!("&self.{}",field.ident),attrs);
+        }
+        r += "Ok(ofs) } ";
+    }
+    r += "}";
     //eprintln!("{}",r);
     r
 }
 
 fn render_tuple(t: &Tuple) -> String {
-    let mut r = "impl Codec for ".to_string();
-    r += &t.ident;
-    r += " { fn decode(b: &[u8]) -> Option<(usize,Self)> { let mut ofs = 0usize; ";
-    for i in 0..t.fields.len() {
-        r += "let f_";
-        r += &i.to_string();
-        r += " = if let Some((l,f)) = ";
-        r += &render_type(t.fields[i].ty.as_ref());
-        r += "::decode(&b[ofs..]) { ofs += l; f } else { return None; }; ";
+    let container = container_attrs(&t.attrs,false);
+    let versioned = container.versioned;
+    let h = render_header(&t.ident,&t.generics,&t.wheres);
+    let attrs: Vec<FieldAttrs> = t.fields.iter().map(|field| container_field_attrs(&field.attrs,field.ty.as_ref(),container.big_endian)).collect();
+    let version = struct_version(versioned,&attrs);
+    let mut r = h.decode.clone();
+    r += &render_decode_start(versioned,version);
+    for (i,(field,attrs)) in t.fields.iter().zip(attrs.iter()).enumerate() {
+        r += &render_field_decode(&h,versioned,&format!("f_{}",i),field.ty.as_ref(),attrs);
     }
-    r += " Some((ofs,";
+    r += if versioned { "Ok((end," } else { "Ok((ofs," };
     r += &t.ident;
     r += "(";
     for i in 0..t.fields.len() {
@@ -266,226 +878,303 @@ fn render_tuple(t: &Tuple) -> String {
         r += &i.to_string();
         r += ", ";
     }
-    r += "))) } fn encode(&self,b: &mut Vec<u8>) -> usize { let mut ofs = 0usize; ";
-    for i in 0..t.fields.len() {
-        r += "ofs += self.";
-        r += &i.to_string();
-        r += ".encode(b); ";
+    r += "))) }) } } ";
+    r += &h.encode;
+    r += "const FIXED_SIZE: Option<usize> = ";
+    r += &render_struct_fixed_size(versioned,&render_fixed_size_sum(t.fields.iter().zip(attrs.iter()).map(|(field,attrs)| (field.ty.as_ref(),attrs))));
+    r += "; ";
+    r += "const MIN_SIZE: usize = ";
+    r += &render_struct_min_size(versioned,t.fields.iter().zip(attrs.iter()).map(|(field,attrs)| (field.ty.as_ref(),attrs)));
+    r += "; ";
+    let fields: Vec<String> = t.fields.iter().zip(attrs.iter()).enumerate().filter_map(|(i,(field,attrs))| render_field_schema(&i.to_string(),field.ty.as_ref(),attrs)).collect();
+    r += &render_schema(&t.ident,&render_struct_schema(versioned,version,&attrs,&t.ident,&fields));
+    r += "fn encode(&self,b: &mut Vec<u8>) -> usize { ";
+    r += &render_encode_start(versioned,version);
+    for (i,attrs) in attrs.iter().enumerate() {
+        r += &render_field_encode(&format!("&self.{}",i),attrs);
     }
-    r += "ofs } fn size(&self) -> usize { let mut ofs = 0usize; ";
-    for i in 0..t.fields.len() {
-        r += "ofs += self.";
-        r += &i.to_string();
-        r += ".size(); ";
+    r += &render_encode_end(versioned);
+    r += "fn size(&self) -> usize { if let Some(size) = <Self as codec::Encode>::FIXED_SIZE { return size; } ";
+    r += if versioned { "let mut ofs = 6usize; " } else { "let mut ofs = 0usize; " };
+    for (i,attrs) in attrs.iter().enumerate() {
+        r += &render_field_size(&format!("&self.{}",i),attrs);
+    }
+    r += "ofs } ";
+    if !versioned {
+        r += "fn encode_to(&self,s: &mut codec::Sink<'_>) -> std::io::Result<usize> { let mut ofs = 0usize; ";
+        for (i,attrs) in attrs.iter().enumerate() {
+            r += &render_field_encode_to(&format!("&self.{}",i),attrs);
+        }
+        r += "Ok(ofs) } ";
     }
-    r += " ofs } }";
+    r += "}";
     //eprintln!("{}",r);
     r
 }
 
-fn render_enum(e: &Enum) -> String {
-    let mut r = "impl Codec for ".to_string();
-    r += &e.ident;
-    r += " { fn decode(b: &[u8]) -> Option<(usize,Self)> { if let Some((_,a)) = u32::decode(b) { match a { ";
-    for i in 0..e.items.len() {
-        r += &i.to_string();
-        r += " => ";
-        match &e.items[i] {
-            EnumItem::Bare(b) => {
-                r += "Some((4,";
-                r += &e.ident;
-                r += "::";
-                r += &b.ident;
-                r += "))";
-            },
-            EnumItem::Struct(s) => {
-                r += "{ let mut ofs = 4; ";
-                for k in 0..s.fields.len() {
-                    r += "let ";
-                    r += &s.fields[k].ident;
-                    r += " = if let Some((l,";
-                    r += &s.fields[k].ident;
-                    r += ")) = ";
-                    r += &render_type(s.fields[k].ty.as_ref());
-                    r += "::decode(&b[ofs..]) { ofs += l; ";
-                    r += &s.fields[k].ident;
-                    r += " } else { return None; };"
+// `Enum::Item { a: f_a, b: f_b, }` or `Enum::Item(f0,f1)`, used both to build
+// and to match a variant. When matching, skipped fields are not bound.
+fn render_enum_pattern(e: &Enum,item: &EnumItem,matching: bool) -> String {
+    let mut r = e.ident.clone();
+    r += "::";
+    match item {
+        EnumItem::Bare(b) => {
+            r += &b.ident;
+        },
+        EnumItem::Struct(s) => {
+            r += &s.ident;
+            r += " { ";
+            for field in &s.fields {
+                r += &field.ident;
+                if matching && field_attrs(&field.attrs).skip {
+                    r += ": _, ";
                 }
-                r += "Some((ofs,";
-                r += &e.ident;
-                r += "::";
-                r += &s.ident;
-                r += " { ";
-                for k in 0..s.fields.len() {
-                    r += &s.fields[k].ident;
-                    r += ": ";
-                    r += &s.fields[k].ident;
+                else {
+                    r += ": f_";
+                    r += &field.ident;
                     r += ", ";
                 }
-                r += "})) }";
-            },
-            EnumItem::Tuple(t) => {
-                r += "{ let mut ofs = 4; ";
-                for k in 0..t.fields.len() {
-                    r += "let f";
-                    r += &k.to_string();
-                    r += " = if let Some((l,f)) = ";
-                    r += &render_type(t.fields[k].ty.as_ref());
-                    r += "::decode(&b[ofs..]) { ofs += l; f } else { return None; }; ";
+            }
+            r += "}";
+        },
+        EnumItem::Tuple(t) => {
+            r += &t.ident;
+            r += "(";
+            for k in 0..t.fields.len() {
+                if k > 0 {
+                    r += ",";
                 }
-                r += "Some((ofs,";
-                r += &e.ident;
-                r += "::";
-                r += &t.ident;
-                r += "(";
-                let mut first = true;
-                for k in 0..t.fields.len() {
-                    if first {
-                        first = false;
-                    }
-                    else {
-                        r += ",";
-                    }
+                if matching && field_attrs(&t.fields[k].attrs).skip {
+                    r += "_";
+                }
+                else {
                     r += "f";
                     r += &k.to_string();
                 }
-                r += "))) }";
-            },
-            EnumItem::Discr(_) => { },
+            }
+            r += ")";
+        },
+        EnumItem::Discr(d) => {
+            r += &d.ident;
+        },
+    }
+    r
+}
+
+// Names, types and attributes of the fields of a variant, as bound by
+// `render_enum_pattern`.
+fn enum_item_fields(item: &EnumItem,big_endian: Option<Span>) -> Vec<(String,&Type,FieldAttrs)> {
+    let mut fields = Vec::<(String,&Type,FieldAttrs)>::new();
+    match item {
+        EnumItem::Struct(s) => {
+            for field in &s.fields {
+                fields.push((format!("f_{}",field.ident),field.ty.as_ref(),container_field_attrs(&field.attrs,field.ty.as_ref(),big_endian)));
+            }
+        },
+        EnumItem::Tuple(t) => {
+            for k in 0..t.fields.len() {
+                fields.push((format!("f{}",k),t.fields[k].ty.as_ref(),container_field_attrs(&t.fields[k].attrs,t.fields[k].ty.as_ref(),big_endian)));
+            }
+        },
+        _ => { },
+    }
+    fields
+}
+
+fn enum_item_attrs(item: &EnumItem) -> ItemAttrs {
+    match item {
+        EnumItem::Bare(b) => item_attrs(&b.attrs),
+        EnumItem::Struct(s) => item_attrs(&s.attrs),
+        EnumItem::Tuple(t) => item_attrs(&t.attrs),
+        EnumItem::Discr(d) => item_attrs(&d.attrs),
+    }
+}
+
+// Wire tags of the variants. Like Rust discriminants, a variant gets its
+// `#[codec(tag = N)]`, or else its explicit discriminant, or else the tag of
+// the previous variant plus one, starting at 0.
+fn enum_tags(e: &Enum,tag_type: &str) -> Vec<u32> {
+    let max = match tag_type {
+        "u8" => u8::MAX as u32,
+        "u16" => u16::MAX as u32,
+        _ => u32::MAX,
+    };
+    let mut tags = Vec::<u32>::new();
+    let mut next = Some(0u32);
+    for item in &e.items {
+        let ident = enum_item_ident(item);
+        let attrs = enum_item_attrs(item);
+        let mut span = attrs.span;
+        let tag = if let Some(tag) = attrs.tag {
+            tag
         }
-        r += ", ";
+        else if let EnumItem::Discr(d) = item {
+            span = d.span;
+            match &d.expr {
+                Expr::Integer(value) if *value <= u32::MAX as u64 => *value as u32,
+                Expr::Integer(value) => abort!(span,"discriminant {} of `{}::{}` does not fit a u32",value,e.ident,ident),
+                Expr::Other(expr) => abort!(span,"discriminant `{}` of `{}::{}` is not an integer literal, pin the wire tag with `#[codec(tag = N)]`",expr,e.ident,ident),
+            }
+        }
+        else if let Some(tag) = next {
+            tag
+        }
+        else {
+            abort!(span,"tag of `{}::{}` overflows u32",e.ident,ident);
+        };
+        if tag > max {
+            abort!(span,"tag {} of `{}::{}` does not fit tag type {}",tag,e.ident,ident,tag_type);
+        }
+        if tags.contains(&tag) {
+            abort!(span,"tag {} of `{}::{}` is already used",tag,e.ident,ident);
+        }
+        tags.push(tag);
+        next = tag.checked_add(1);
     }
-    r += "_ => None } } else { None } } fn encode(&self,b: &mut Vec<u8>) -> usize { match self { ";
-    for i in 0..e.items.len() {
-        match &e.items[i] {
-            EnumItem::Bare(b) => {
-                r += &e.ident;
-                r += "::";
-                r += &b.ident;
-                r += " => { u32::encode(&";
-                r += &i.to_string();
-                r += ",b); 4 }";
-            },
-            EnumItem::Struct(s) => {
-                r += &e.ident;
-                r += "::";
-                r += &s.ident;
-                r += " { ";
-                for k in 0..s.fields.len() {
-                    r += &s.fields[k].ident;
-                    r += ", ";
-                }
-                r += "} => { u32::encode(&";
-                r += &i.to_string();
-                r += ",b); let mut ofs = 4; ";
-                for k in 0..s.fields.len() {
-                    r += "ofs += ";
-                    r += &s.fields[k].ident;
-                    r += ".encode(b); ";
-                }
-                r += "ofs }";
-            },
-            EnumItem::Tuple(t) => {
-                r += &e.ident;
-                r += "::";
-                r += &t.ident;
-                r += "(";
-                let mut first = true;
-                for k in 0..t.fields.len() {
-                    if first {
-                        first = false;
-                    }
-                    else {
-                        r += ",";
-                    }
-                    r += "f";
-                    r += &k.to_string();
-                }
-                r += ") => { u32::encode(&";
-                r += &i.to_string();
-                r += ",b); let mut ofs = 4; ";
-                for k in 0..t.fields.len() {
-                    r += "ofs += f";
-                    r += &k.to_string();
-                    r += ".encode(b); ";
-                }
-                r += "ofs }";
-            },
-            EnumItem::Discr(_) => { },
+    tags
+}
+
+// `codec::Schema::Enum` of the enum, with the wire tags of the variants.
+fn render_enum_schema(e: &Enum,tag_size: usize,tags: &[u32],big_endian: Option<Span>) -> String {
+    let mut variants = Vec::<String>::new();
+    for (item,tag) in e.items.iter().zip(tags.iter()) {
+        let fields: Vec<String> = match item {
+            EnumItem::Struct(s) => s.fields.iter().filter_map(|field| render_field_schema(&field.ident,field.ty.as_ref(),&container_field_attrs(&field.attrs,field.ty.as_ref(),big_endian))).collect(),
+            EnumItem::Tuple(t) => t.fields.iter().enumerate().filter_map(|(i,field)| render_field_schema(&i.to_string(),field.ty.as_ref(),&container_field_attrs(&field.attrs,field.ty.as_ref(),big_endian))).collect(),
+            _ => Vec::new(),
+        };
+        variants.push(format!("codec::Variant {{ name: \"{}\".to_string(), tag: {}, fields: vec![{}], }}",enum_item_ident(item).trim_start_matches("r#"),tag,fields.join(",")));
+    }
+    render_schema(&e.ident,&format!("codec::Schema::Enum {{ name: \"{}\".to_string(), tag_size: {}, big_endian: {}, variants: vec![{}], }}",e.ident,tag_size,big_endian.is_some(),variants.join(",")))
+}
+
+fn enum_item_ident(item: &EnumItem) -> &str {
+    match item {
+        EnumItem::Bare(b) => &b.ident,
+        EnumItem::Struct(s) => &s.ident,
+        EnumItem::Tuple(t) => &t.ident,
+        EnumItem::Discr(d) => &d.ident,
+    }
+}
+
+fn render_enum(e: &Enum) -> String {
+    let h = render_header(&e.ident,&e.generics,&e.wheres);
+    let container = container_attrs(&e.attrs,true);
+    let big_endian = container.big_endian;
+    let tag_type = container.tag_type.unwrap_or("u32".to_string());
+    let tag_size = match tag_type.as_str() {
+        "u8" => 1,
+        "u16" => 2,
+        _ => 4,
+    };
+    let tags = enum_tags(e,&tag_type);
+
+    // the tag follows the byte order of the enum
+    let (tag_codec,tag_pattern,tag_value) = if big_endian.is_some() {
+        (format!("codec::BigEndian<{}>",tag_type),"codec::BigEndian(a)","codec::BigEndian")
+    }
+    else {
+        (tag_type.clone(),"a","")
+    };
+    let mut r = h.decode.clone();
+
+    // an enum without variants has no values, so decoding always fails and
+    // there is nothing to encode
+    if e.items.len() == 0 {
+        r += &format!("let (_,{}) = <{} as codec::Codec>::decode_with(b,ctx)?; Err(codec::DecodeError::InvalidTag {{ offset: 0, tag: a as u32, }}) }} }} ",tag_pattern,tag_codec);
+        r += &h.encode;
+        r += &render_enum_schema(e,tag_size,&tags,big_endian);
+        r += "fn encode(&self,_: &mut Vec<u8>) -> usize { match *self { } } fn size(&self) -> usize { match *self { } } fn encode_to(&self,_: &mut codec::Sink<'_>) -> std::io::Result<usize> { match *self { } } }";
+        return r;
+    }
+
+    r += &format!("ctx.nested(|ctx| {{ let (_,{}) = <{} as codec::Codec>::decode_with(b,ctx)?; let r = match a {{ ",tag_pattern,tag_codec);
+    for (item,tag) in e.items.iter().zip(tags.iter()) {
+        let fields = enum_item_fields(item,big_endian);
+        r += &tag.to_string();
+        r += if fields.iter().any(|f| !f.2.skip) { " => { let mut ofs = " } else { " => { let ofs = " };
+        r += &tag_size.to_string();
+        r += "; ";
+        for (name,ty,attrs) in &fields {
+            if attrs.since.is_some() {
+                abort!(attrs.since_span,"`since` only applies to fields of `#[codec(versioned)]` structs");
+            }
+            r += &render_field_decode(&h,false,name,ty,attrs);
         }
-        r += ", ";
+        r += "(ofs,";
+        r += &render_enum_pattern(e,item,false);
+        r += ") }, ";
     }
-    r += "} } fn size(&self) -> usize { match self { ";
-    for i in 0..e.items.len() {
-        match &e.items[i] {
-            EnumItem::Bare(b) => {
-                r += &e.ident;
-                r += "::";
-                r += &b.ident;
-                r += " => 4";
-            },
-            EnumItem::Struct(s) => {
-                r += &e.ident;
-                r += "::";
-                r += &s.ident;
-                r += " { ";
-                for k in 0..s.fields.len() {
-                    r += &s.fields[k].ident;
-                    r += ", ";
-                }
-                r += "} => { let mut ofs = 4; ";
-                for k in 0..s.fields.len() {
-                    r += "ofs += ";
-                    r += &s.fields[k].ident;
-                    r += ".size(); ";
-                }
-                r += " ofs }";
-            },
-            EnumItem::Tuple(t) => {
-                r += &e.ident;
-                r += "::";
-                r += &t.ident;
-                r += "(";
-                let mut first = true;
-                for k in 0..t.fields.len() {
-                    if first {
-                        first = false;
-                    }
-                    else {
-                        r += ",";
-                    }
-                    r += "f";
-                    r += &k.to_string();
-                }
-                r += ") => { let mut ofs = 4; ";
-                for k in 0..t.fields.len() {
-                    r += "ofs += f";
-                    r += &k.to_string();
-                    r += ".size(); ";
-                }
-                r += " ofs }";
-            },
-            EnumItem::Discr(_) => { },
+    r += "_ => { return Err(codec::DecodeError::InvalidTag { offset: 0, tag: a as u32, }); }, }; Ok(r) }) } } ";
+    r += &h.encode;
+    let mut sizes = Vec::<String>::new();
+    let mut min_sizes = Vec::<String>::new();
+    for item in &e.items {
+        let fields = enum_item_fields(item,big_endian);
+        sizes.push(render_fixed_size_sum(fields.iter().map(|(_,ty,attrs)| (*ty,attrs))));
+        min_sizes.push(render_min_size_sum(fields.iter().map(|(_,ty,attrs)| (*ty,attrs))));
+    }
+    r += &format!("const FIXED_SIZE: Option<usize> = codec::fixed_size_variants({},&[{}]); ",tag_size,sizes.join(","));
+    r += &format!("const MIN_SIZE: usize = codec::min_size_variants({},&[{}]); ",tag_size,min_sizes.join(","));
+    r += &render_enum_schema(e,tag_size,&tags,big_endian);
+    r += "fn encode(&self,b: &mut Vec<u8>) -> usize { match self { ";
+    for (item,tag) in e.items.iter().zip(tags.iter()) {
+        let fields = enum_item_fields(item,big_endian);
+        r += &render_enum_pattern(e,item,true);
+        r += if fields.iter().any(|f| !f.2.skip) { " => { let mut ofs = codec::Encode::encode(&" } else { " => { let ofs = codec::Encode::encode(&" };
+        r += &format!("{}({}{})",tag_value,tag,tag_type);
+        r += ",b); ";
+        for (name,_,attrs) in &fields {
+            r += &render_field_encode(name,attrs);
         }
-        r += ", ";
+        r += "ofs }, ";
+    }
+    r += "} } fn size(&self) -> usize { if let Some(size) = <Self as codec::Encode>::FIXED_SIZE { return size; } match self { ";
+    for item in &e.items {
+        let fields = enum_item_fields(item,big_endian);
+        r += &render_enum_pattern(e,item,true);
+        r += if fields.iter().any(|f| !f.2.skip) { " => { let mut ofs = " } else { " => { let ofs = " };
+        r += &tag_size.to_string();
+        r += "; ";
+        for (name,_,attrs) in &fields {
+            r += &render_field_size(name,attrs);
+        }
+        r += "ofs }, ";
+    }
+    r += "} } fn encode_to(&self,s: &mut codec::Sink<'_>) -> std::io::Result<usize> { match self { ";
+    for (item,tag) in e.items.iter().zip(tags.iter()) {
+        let fields = enum_item_fields(item,big_endian);
+        r += &render_enum_pattern(e,item,true);
+        r += if fields.iter().any(|f| !f.2.skip) { " => { let mut ofs = codec::Encode::encode_to(&" } else { " => { let ofs = codec::Encode::encode_to(&" };
+        r += &format!("{}({}{})",tag_value,tag,tag_type);
+        r += ",s)?; ";
+        for (name,_,attrs) in &fields {
+            r += &render_field_encode_to(name,attrs);
+        }
+        r += "Ok(ofs) }, ";
     }
     r += "} } }";
     //eprintln!("{}",r);
     r
 }
 
-#[proc_macro_derive(Codec)]
+#[proc_macro_derive(Codec,attributes(codec))]
+#[proc_macro_error]
 pub fn derive_codec(stream: TokenStream) -> TokenStream {
     let mut lexer = Lexer::new(stream);
     if let Some(item) = lexer.parse_item() {
         //eprintln!("{}",item);
-        match item {
+        let r = match item {
             Item::Struct(s) => render_struct(&s),
             Item::Tuple(t) => render_tuple(&t),
             Item::Enum(e) => render_enum(&e),
-        }.parse().unwrap()
+        };
+        match r.parse() {
+            Ok(stream) => stream,
+            Err(error) => abort_call_site!("`#[derive(Codec)]` generated invalid code: {}",error),
+        }
     }
     else {
-        panic!("only `struct` or `enum` supported");
+        abort!(lexer.span(),"`#[derive(Codec)]` only supports structs and enums");
     }
 }
@@ -13,6 +13,7 @@ pub(crate) enum GenericArg {
         ident: String,
         path: Box<Path>,
     },
+    Const(String),
 }
 
 impl fmt::Display for GenericArg {
@@ -20,8 +21,9 @@ impl fmt::Display for GenericArg {
         match self {
             GenericArg::Lifetime(ident) => write!(f,"'{}",ident),
             GenericArg::Type(ty) => write!(f,"{}",ty),
-            GenericArg::Binding { ident,ty } => write!(f,"{}: {}",ident,ty),
+            GenericArg::Binding { ident,ty } => write!(f,"{} = {}",ident,ty),
             GenericArg::Qualifier { ident,path } => write!(f,"{} as {}",ident,path),
+            GenericArg::Const(expr) => write!(f,"{}",expr),
         }
     }
 }
@@ -91,7 +93,7 @@ impl Lexer {
                         args.push(GenericArg::Lifetime(ident));
                     }
                     else {
-                        panic!("identifier expected after `'`");
+                        abort!(self.span(),"identifier expected after `'`");
                     }
                 }
                 else if let Some(ident) = self.parse_some_ident() {
@@ -100,29 +102,45 @@ impl Lexer {
                             args.push(GenericArg::Binding { ident: ident.clone(), ty: Box::new(ty), });
                         }
                         else {
-                            panic!("type expected after `=`");
+                            abort!(self.span(),"type expected after `=`");
                         }
                     }
-                    if self.parse_ident("as") {
+                    else if self.parse_ident("as") {
                         if let Some(path) = self.parse_path() {
                             args.push(GenericArg::Qualifier { ident: ident.clone(), path: Box::new(path), });
                         }
                         else {
-                            panic!("path expected after `as`")
+                            abort!(self.span(),"path expected after `as`")
                         }
                     }
                     else {
+                        // rest of the path, like `Box<T>` in `Option<Box<T>>`
                         let mut segs = Vec::<PathSeg>::new();
                         segs.push(PathSeg::Ident(ident));
+                        while self.is_punct2(':',':') || self.is_punct('<') {
+                            self.parse_punct2(':',':');
+                            if let Some(seg) = self.parse_path_seg() {
+                                segs.push(seg);
+                            }
+                            else {
+                                abort!(self.span(),"path segment expected after `::`");
+                            }
+                        }
                         args.push(GenericArg::Type(Type::Path(Path { abs: false, segs: segs, })));
                     }
                 }
+                else if let Some(literal) = self.parse_some_literal() {
+                    args.push(GenericArg::Const(literal));
+                }
+                else if let Some(group) = self.parse_brace_group() {
+                    args.push(GenericArg::Const(format!("{{ {} }}",group.stream())));
+                }
                 else {
                     if let Some(ty) = self.parse_type() {
                         args.push(GenericArg::Type(ty));
                     }
                     else {
-                        panic!("type expected");
+                        abort!(self.span(),"type expected");
                     }
                 }
                 self.parse_punct(',');
@@ -139,7 +157,7 @@ impl Lexer {
     }
 
     pub(crate) fn parse_path(&mut self) -> Option<Path> {
-        let abs = if self.is_punct(':') {
+        let abs = if self.is_punct2(':',':') {
             self.parse_punct2(':',':');
             true
         }
@@ -149,13 +167,13 @@ impl Lexer {
         let mut segs = Vec::<PathSeg>::new();
         if let Some(seg) = self.parse_path_seg() {
             segs.push(seg);
-            while self.is_punct(':') || self.is_punct('<') {
+            while self.is_punct2(':',':') || self.is_punct('<') {
                 self.parse_punct2(':',':');
                 if let Some(seg) = self.parse_path_seg() {
                     segs.push(seg);
                 }
                 else {
-                    panic!("path segment expected after `::`");
+                    abort!(self.span(),"path segment expected after `::`");
                 }
             }
             Some(Path {
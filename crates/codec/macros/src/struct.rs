@@ -205,11 +205,11 @@ impl Lexer {
                     })
                 }
                 else {
-                    panic!("type expected after `:`");
+                    abort!(self.span(),"unsupported field type, expected a path, tuple, array, slice or reference");
                 }
             }
             else {
-                panic!("`:` expected in struct field");
+                abort!(self.span(),"`:` expected in struct field");
             }
         }
         else {
@@ -244,12 +244,15 @@ impl Lexer {
             if let Some(ident) = self.parse_some_ident() {
                 let generics = self.parse_generics();
                 if let Some(group) = self.parse_paren_group() {
-                    let mut lexer = Lexer::new(group.stream());
+                    let mut lexer = Lexer::new_in(&group);
                     let mut fields = Vec::<TupleField>::new();
                     while let Some(field) = lexer.parse_tuple_field() {
                         fields.push(field);
                         lexer.parse_punct(',');
                     }
+                    if lexer.token.is_some() {
+                        abort!(lexer.span(),"unsupported field type, expected a path, tuple, array, slice or reference");
+                    }
                     let wheres = self.parse_wheres();
                     Some(StructOrTuple::Tuple(Tuple {
                         attrs: attrs,
@@ -263,12 +266,15 @@ impl Lexer {
                 else {
                     let wheres = self.parse_wheres();
                     if let Some(group) = self.parse_brace_group() {
-                        let mut lexer = Lexer::new(group.stream());
+                        let mut lexer = Lexer::new_in(&group);
                         let mut fields = Vec::<StructField>::new();
                         while let Some(field) = lexer.parse_struct_field() {
                             fields.push(field);
                             lexer.parse_punct(',');
                         }
+                        if lexer.token.is_some() {
+                            abort!(lexer.span(),"struct field expected");
+                        }
                         Some(StructOrTuple::Struct(Struct {
                             attrs: attrs,
                             visibility: visibility,
@@ -278,13 +284,23 @@ impl Lexer {
                             fields: fields,
                         }))
                     }
+                    else if self.parse_punct(';') {
+                        Some(StructOrTuple::Struct(Struct {
+                            attrs: attrs,
+                            visibility: visibility,
+                            ident: ident,
+                            generics: generics,
+                            wheres: wheres,
+                            fields: Vec::<StructField>::new(),
+                        }))
+                    }
                     else {
-                        panic!("{}","`{` expected to describe struct");
+                        abort!(self.span(),"{}","`{` expected to describe struct");
                     }
                 }
             }
             else {
-                panic!("identifier expected after `struct`");
+                abort!(self.span(),"identifier expected after `struct`");
             }
         }
         else {
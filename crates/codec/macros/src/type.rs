@@ -9,6 +9,12 @@ pub(crate) enum Type {
         ty: Box<Type>,
         expr: Expr,
     },
+    Slice(Box<Type>),
+    Ref {
+        lifetime: Option<String>,
+        mutable: bool,
+        ty: Box<Type>,
+    },
 }
 
 impl fmt::Display for Type {
@@ -34,6 +40,20 @@ impl fmt::Display for Type {
             Type::Array { ty,expr } => {
                 write!(f,"[{}; {}]",ty,expr)
             },
+            Type::Slice(ty) => {
+                write!(f,"[{}]",ty)
+            },
+            Type::Ref { lifetime,mutable,ty } => {
+                let mut a = "&".to_string();
+                if let Some(lifetime) = lifetime {
+                    a += &format!("'{} ",lifetime);
+                }
+                if *mutable {
+                    a += "mut ";
+                }
+                a += &format!("{}",ty);
+                write!(f,"{}",a)
+            },
         }
     }
 }
@@ -42,24 +62,50 @@ impl Lexer {
 
     // TupleType = `(` [ Type { `,` Type } [ `,` ] `)` .
     // ArrayType = `[` Type `;` Expr `]` .
-    // Type = Path | TupleType | ArrayType .
+    // SliceType = `[` Type `]` .
+    // RefType = `&` [ `'` IDENTIFIER ] [ `mut` ] Type .
+    // Type = Path | TupleType | ArrayType | SliceType | RefType .
     pub(crate) fn parse_type(&mut self) -> Option<Type> {
-        if let Some(group) = self.parse_paren_group() {
-            let mut lexer = Lexer::new(group.stream());
+        if self.parse_punct('&') {
+            let lifetime = if self.parse_punct('\'') {
+                if let Some(ident) = self.parse_some_ident() {
+                    Some(ident)
+                }
+                else {
+                    abort!(self.span(),"identifier expected after `'`");
+                }
+            }
+            else {
+                None
+            };
+            let mutable = self.parse_ident("mut");
+            if let Some(ty) = self.parse_type() {
+                Some(Type::Ref {
+                    lifetime: lifetime,
+                    mutable: mutable,
+                    ty: Box::new(ty),
+                })
+            }
+            else {
+                abort!(self.span(),"type expected after `&`");
+            }
+        }
+        else if let Some(group) = self.parse_paren_group() {
+            let mut lexer = Lexer::new_in(&group);
             let mut types = Vec::<Type>::new();
             while let Some(_) = &lexer.token {
                 if let Some(ty) = lexer.parse_type() {
                     types.push(ty);
                 }
                 else {
-                    panic!("type expected in `(` `)`");
+                    abort!(lexer.span(),"type expected in `(` `)`");
                 }
                 lexer.parse_punct(',');
             }
             Some(Type::Tuple(types))
         }
         else if let Some(group) = self.parse_bracket_group() {
-            let mut lexer = Lexer::new(group.stream());
+            let mut lexer = Lexer::new_in(&group);
             if let Some(ty) = lexer.parse_type() {
                 if lexer.parse_punct(';') {
                     if let Some(expr) = lexer.parse_expr() {
@@ -69,17 +115,21 @@ impl Lexer {
                         })
                     }
                     else {
-                        panic!("expression expected after `;`");
+                        abort!(lexer.span(),"expression expected after `;`");
                     }
                 }
                 else {
-                    panic!("`;` expected");
+                    Some(Type::Slice(Box::new(ty)))
                 }
             }
             else {
-                panic!("type expected in `[` `]`");
+                abort!(lexer.span(),"type expected in `[` `]`");
             }
         }
+        else if self.is_ident("fn") || self.is_ident("unsafe") || self.is_ident("extern") || self.is_ident("dyn") || self.is_ident("impl") {
+            // function pointers and trait objects are not a path
+            None
+        }
         else {
             if let Some(path) = self.parse_path() {
                 Some(Type::Path(path))
@@ -19,7 +19,7 @@ impl fmt::Display for Where {
         match self {
             Where::Lifetime { ident,bounds }=> {
                 let mut a = String::new();
-                a += &format!("{}",ident);
+                a += &format!("'{}",ident);
                 if bounds.len() > 0 {
                     a += &format!(": ");
                     let mut first = true;
@@ -74,7 +74,10 @@ impl fmt::Display for Where {
 impl Lexer {
 
     fn is_where(&self) -> bool {
-        if self.is_punct(':') {
+        if self.is_punct('\'') {
+            true
+        }
+        else if self.is_punct(':') {
             true
         }
         else if self.is_punct('<') {
@@ -102,22 +105,22 @@ impl Lexer {
         if self.parse_ident("where") {
             let mut wheres = Vec::<Where>::new();
             while self.is_where() {
-                if self.is_punct('\'') {
+                if self.parse_punct('\'') {
                     if let Some(ident) = self.parse_some_ident() {
                         let mut bounds = Vec::<String>::new();
                         if self.parse_punct(':') {
                             while self.parse_punct('\'') {
-                                if self.is_some_ident() {
-                                    bounds.push(ident.to_string());
+                                if let Some(bound) = self.parse_some_ident() {
+                                    bounds.push(bound);
                                 }
                                 else {
-                                    panic!("identifier expected after `'`");
+                                    abort!(self.span(),"identifier expected after `'`");
                                 }
                                 self.parse_punct('+');
                             }
                         }
                         else {
-                            panic!("`:` expected after lifetime");
+                            abort!(self.span(),"`:` expected after lifetime");
                         }
                         wheres.push(Where::Lifetime {
                             ident: ident,
@@ -125,7 +128,7 @@ impl Lexer {
                         });
                     }
                     else {
-                        panic!("identifier expected after `'`");
+                        abort!(self.span(),"identifier expected after `'`");
                     }
                 }
                 else {
@@ -138,17 +141,17 @@ impl Lexer {
                                         for_lifetimes.push(ident);
                                     }
                                     else {
-                                        panic!("identifier expected after `'`");
+                                        abort!(self.span(),"identifier expected after `'`");
                                     }
                                     self.parse_punct(',');
                                 }
                                 else {
-                                    panic!("lifetime expected in `for <` `>`");
+                                    abort!(self.span(),"lifetime expected in `for <` `>`");
                                 }
                             }
                         }
                         else {
-                            panic!("`<` expected after `for`");
+                            abort!(self.span(),"`<` expected after `for`");
                         }
                     }
                     if let Some(ty) = self.parse_type() {
@@ -161,7 +164,7 @@ impl Lexer {
                             }
                         }
                         else {
-                            panic!("`:` expected after type");
+                            abort!(self.span(),"`:` expected after type");
                         }
                         wheres.push(Where::Type {
                             for_lifetimes: for_lifetimes,
@@ -170,7 +173,7 @@ impl Lexer {
                         });
                     }
                     else {
-                        panic!("type expected in where clause");
+                        abort!(self.span(),"type expected in where clause");
                     }
                 }
                 self.parse_punct(',');
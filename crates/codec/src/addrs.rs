@@ -11,8 +11,22 @@ use {
 };
 
 impl Codec for Ipv4Addr {
-    fn decode(buffer: &[u8]) -> Option<(usize,Self)> {
-        Some((4,Ipv4Addr::new(buffer[0],buffer[1],buffer[2],buffer[3])))
+    fn decode_with(buffer: &[u8],_ctx: &mut DecodeContext) -> Result<(usize,Self),DecodeError> {
+        ensure(buffer,4)?;
+        Ok((4,Ipv4Addr::new(buffer[0],buffer[1],buffer[2],buffer[3])))
+    }
+}
+
+impl Encode for Ipv4Addr {
+    const FIXED_SIZE: Option<usize> = Some(4);
+
+    fn schema() -> Schema {
+        Schema::Struct {
+            name: "Ipv4Addr".to_string(),
+            fields: vec![
+                Field { name: "0".to_string(), schema: Schema::Array(Box::new(Schema::U8),4), },
+            ],
+        }
     }
 
     fn encode(&self,buffer: &mut Vec<u8>) -> usize {
@@ -30,16 +44,30 @@ impl Codec for Ipv4Addr {
 }
 
 impl Codec for Ipv6Addr {
-    fn decode(buffer: &[u8]) -> Option<(usize,Self)> {
-        let a = { if let Some((_,r)) = u16::decode(&buffer[0..]) { r } else { return None; } };
-        let b = { if let Some((_,r)) = u16::decode(&buffer[2..]) { r } else { return None; } };
-        let c = { if let Some((_,r)) = u16::decode(&buffer[4..]) { r } else { return None; } };
-        let d = { if let Some((_,r)) = u16::decode(&buffer[6..]) { r } else { return None; } };
-        let e = { if let Some((_,r)) = u16::decode(&buffer[8..]) { r } else { return None; } };
-        let f = { if let Some((_,r)) = u16::decode(&buffer[10..]) { r } else { return None; } };
-        let g = { if let Some((_,r)) = u16::decode(&buffer[12..]) { r } else { return None; } };
-        let h = { if let Some((_,r)) = u16::decode(&buffer[14..]) { r } else { return None; } };
-        Some((16,Ipv6Addr::new(a,b,c,d,e,f,g,h)))
+    fn decode_with(buffer: &[u8],ctx: &mut DecodeContext) -> Result<(usize,Self),DecodeError> {
+        ensure(buffer,16)?;
+        let (_,a) = u16::decode_with(&buffer[0..],ctx)?;
+        let (_,b) = u16::decode_with(&buffer[2..],ctx)?;
+        let (_,c) = u16::decode_with(&buffer[4..],ctx)?;
+        let (_,d) = u16::decode_with(&buffer[6..],ctx)?;
+        let (_,e) = u16::decode_with(&buffer[8..],ctx)?;
+        let (_,f) = u16::decode_with(&buffer[10..],ctx)?;
+        let (_,g) = u16::decode_with(&buffer[12..],ctx)?;
+        let (_,h) = u16::decode_with(&buffer[14..],ctx)?;
+        Ok((16,Ipv6Addr::new(a,b,c,d,e,f,g,h)))
+    }
+}
+
+impl Encode for Ipv6Addr {
+    const FIXED_SIZE: Option<usize> = Some(16);
+
+    fn schema() -> Schema {
+        Schema::Struct {
+            name: "Ipv6Addr".to_string(),
+            fields: vec![
+                Field { name: "0".to_string(), schema: Schema::Array(Box::new(Schema::U16),8), },
+            ],
+        }
     }
 
     fn encode(&self,buffer: &mut Vec<u8>) -> usize {
@@ -61,15 +89,34 @@ impl Codec for Ipv6Addr {
 }
 
 impl Codec for IpAddr {
-    fn decode(buffer: &[u8]) -> Option<(usize,Self)> {
+    fn decode_with(buffer: &[u8],ctx: &mut DecodeContext) -> Result<(usize,Self),DecodeError> {
+        ensure(buffer,1)?;
         match buffer[0] {
-            0 => if let Some((_,result)) = Ipv4Addr::decode(&buffer[1..]) {
-                Some((5,IpAddr::V4(result)))
-            } else { None }
-            1 => if let Some((_,result)) = Ipv6Addr::decode(&buffer[1..]) {
-                Some((17,IpAddr::V6(result)))
-            } else { None }
-            _ => None,
+            0 => {
+                let (_,result) = Ipv4Addr::decode_with(&buffer[1..],ctx).map_err(|e| e.at(1))?;
+                Ok((5,IpAddr::V4(result)))
+            },
+            1 => {
+                let (_,result) = Ipv6Addr::decode_with(&buffer[1..],ctx).map_err(|e| e.at(1))?;
+                Ok((17,IpAddr::V6(result)))
+            },
+            tag => Err(DecodeError::InvalidTag { offset: 0, tag: tag as u32, }),
+        }
+    }
+}
+
+impl Encode for IpAddr {
+    const MIN_SIZE: usize = 5;
+
+    fn schema() -> Schema {
+        Schema::Enum {
+            name: "IpAddr".to_string(),
+            tag_size: 1,
+            big_endian: false,
+            variants: vec![
+                Variant { name: "V4".to_string(), tag: 0, fields: vec![Field { name: "0".to_string(), schema: Ipv4Addr::schema(), }], },
+                Variant { name: "V6".to_string(), tag: 1, fields: vec![Field { name: "0".to_string(), schema: Ipv6Addr::schema(), }], },
+            ],
         }
     }
 
@@ -97,12 +144,23 @@ impl Codec for IpAddr {
 }
 
 impl Codec for SocketAddrV4 {
-    fn decode(buffer: &[u8]) -> Option<(usize,Self)> {
-        if let Some((_,port)) = u16::decode(&buffer[4..]) {
-            Some((6,SocketAddrV4::new(Ipv4Addr::new(buffer[0],buffer[1],buffer[2],buffer[3]),port)))
-        }
-        else {
-            None
+    fn decode_with(buffer: &[u8],ctx: &mut DecodeContext) -> Result<(usize,Self),DecodeError> {
+        ensure(buffer,6)?;
+        let (_,port) = u16::decode_with(&buffer[4..],ctx)?;
+        Ok((6,SocketAddrV4::new(Ipv4Addr::new(buffer[0],buffer[1],buffer[2],buffer[3]),port)))
+    }
+}
+
+impl Encode for SocketAddrV4 {
+    const FIXED_SIZE: Option<usize> = Some(6);
+
+    fn schema() -> Schema {
+        Schema::Struct {
+            name: "SocketAddrV4".to_string(),
+            fields: vec![
+                Field { name: "ip".to_string(), schema: Ipv4Addr::schema(), },
+                Field { name: "port".to_string(), schema: Schema::U16, },
+            ],
         }
     }
 
@@ -122,17 +180,32 @@ impl Codec for SocketAddrV4 {
 }
 
 impl Codec for SocketAddrV6 {
-    fn decode(buffer: &[u8]) -> Option<(usize,Self)> {
-        let a = { if let Some((_,r)) = u16::decode(&buffer[0..]) { r } else { return None; } };
-        let b = { if let Some((_,r)) = u16::decode(&buffer[2..]) { r } else { return None; } };
-        let c = { if let Some((_,r)) = u16::decode(&buffer[4..]) { r } else { return None; } };
-        let d = { if let Some((_,r)) = u16::decode(&buffer[6..]) { r } else { return None; } };
-        let e = { if let Some((_,r)) = u16::decode(&buffer[8..]) { r } else { return None; } };
-        let f = { if let Some((_,r)) = u16::decode(&buffer[10..]) { r } else { return None; } };
-        let g = { if let Some((_,r)) = u16::decode(&buffer[12..]) { r } else { return None; } };
-        let h = { if let Some((_,r)) = u16::decode(&buffer[14..]) { r } else { return None; } };
-        let port = { if let Some((_,r)) = u16::decode(&buffer[16..]) { r } else { return None; } };
-        Some((18,SocketAddrV6::new(Ipv6Addr::new(a,b,c,d,e,f,g,h),port,0,0)))
+    fn decode_with(buffer: &[u8],ctx: &mut DecodeContext) -> Result<(usize,Self),DecodeError> {
+        ensure(buffer,18)?;
+        let (_,a) = u16::decode_with(&buffer[0..],ctx)?;
+        let (_,b) = u16::decode_with(&buffer[2..],ctx)?;
+        let (_,c) = u16::decode_with(&buffer[4..],ctx)?;
+        let (_,d) = u16::decode_with(&buffer[6..],ctx)?;
+        let (_,e) = u16::decode_with(&buffer[8..],ctx)?;
+        let (_,f) = u16::decode_with(&buffer[10..],ctx)?;
+        let (_,g) = u16::decode_with(&buffer[12..],ctx)?;
+        let (_,h) = u16::decode_with(&buffer[14..],ctx)?;
+        let (_,port) = u16::decode_with(&buffer[16..],ctx)?;
+        Ok((18,SocketAddrV6::new(Ipv6Addr::new(a,b,c,d,e,f,g,h),port,0,0)))
+    }
+}
+
+impl Encode for SocketAddrV6 {
+    const FIXED_SIZE: Option<usize> = Some(18);
+
+    fn schema() -> Schema {
+        Schema::Struct {
+            name: "SocketAddrV6".to_string(),
+            fields: vec![
+                Field { name: "ip".to_string(), schema: Ipv6Addr::schema(), },
+                Field { name: "port".to_string(), schema: Schema::U16, },
+            ],
+        }
     }
 
     fn encode(&self,buffer: &mut Vec<u8>) -> usize {
@@ -155,15 +228,34 @@ impl Codec for SocketAddrV6 {
 }
 
 impl Codec for SocketAddr {
-    fn decode(buffer: &[u8]) -> Option<(usize,Self)> {
+    fn decode_with(buffer: &[u8],ctx: &mut DecodeContext) -> Result<(usize,Self),DecodeError> {
+        ensure(buffer,1)?;
         match buffer[0] {
-            0 => if let Some((_,result)) = SocketAddrV4::decode(&buffer[1..]) {
-                Some((7,SocketAddr::V4(result)))
-            } else { None }
-            1 => if let Some((_,result)) = SocketAddrV6::decode(&buffer[1..]) {
-                Some((19,SocketAddr::V6(result)))
-            } else { None }
-            _ => None,
+            0 => {
+                let (_,result) = SocketAddrV4::decode_with(&buffer[1..],ctx).map_err(|e| e.at(1))?;
+                Ok((7,SocketAddr::V4(result)))
+            },
+            1 => {
+                let (_,result) = SocketAddrV6::decode_with(&buffer[1..],ctx).map_err(|e| e.at(1))?;
+                Ok((19,SocketAddr::V6(result)))
+            },
+            tag => Err(DecodeError::InvalidTag { offset: 0, tag: tag as u32, }),
+        }
+    }
+}
+
+impl Encode for SocketAddr {
+    const MIN_SIZE: usize = 7;
+
+    fn schema() -> Schema {
+        Schema::Enum {
+            name: "SocketAddr".to_string(),
+            tag_size: 1,
+            big_endian: false,
+            variants: vec![
+                Variant { name: "V4".to_string(), tag: 0, fields: vec![Field { name: "0".to_string(), schema: SocketAddrV4::schema(), }], },
+                Variant { name: "V6".to_string(), tag: 1, fields: vec![Field { name: "0".to_string(), schema: SocketAddrV6::schema(), }], },
+            ],
         }
     }
 
@@ -184,8 +276,8 @@ impl Codec for SocketAddr {
 
     fn size(&self) -> usize {
         match self {
-            SocketAddr::V4(_) => 5,
-            SocketAddr::V6(_) => 17,
+            SocketAddr::V4(_) => 7,
+            SocketAddr::V6(_) => 19,
         }
     }   
 }
@@ -193,7 +285,7 @@ impl Codec for SocketAddr {
 #[cfg(test)]
 mod tests {
 
-    use crate::Codec;
+    use crate::*;
     use std::net::{
         Ipv4Addr,
         Ipv6Addr,
@@ -208,11 +300,11 @@ mod tests {
         let source = Ipv4Addr::new(192,168,1,255);
         let mut buffer = Vec::<u8>::new();
         source.encode(&mut buffer);
-        if let Some((_,target)) = Ipv4Addr::decode(&buffer) {
+        if let Ok((_,target)) = Ipv4Addr::decode(&buffer) {
             assert_eq!(source,target)
         }
         else {
-            assert!(false)
+            panic!("decode failed");
         }
     }
 
@@ -221,11 +313,11 @@ mod tests {
         let source = Ipv6Addr::new(0xFDEC,0xC0BB,0xC329,0,0,0,0,1);
         let mut buffer = Vec::<u8>::new();
         source.encode(&mut buffer);
-        if let Some((_,target)) = Ipv6Addr::decode(&buffer) {
+        if let Ok((_,target)) = Ipv6Addr::decode(&buffer) {
             assert_eq!(source,target)
         }
         else {
-            assert!(false)
+            panic!("decode failed");
         }
     }
 
@@ -234,11 +326,11 @@ mod tests {
         let source = IpAddr::V4(Ipv4Addr::new(192,168,1,255));
         let mut buffer = Vec::<u8>::new();
         source.encode(&mut buffer);
-        if let Some((_,target)) = IpAddr::decode(&buffer) {
+        if let Ok((_,target)) = IpAddr::decode(&buffer) {
             assert_eq!(source,target)
         }
         else {
-            assert!(false)
+            panic!("decode failed");
         }
     }
 
@@ -247,11 +339,11 @@ mod tests {
         let source = SocketAddrV4::new(Ipv4Addr::new(192,168,1,255),8080);
         let mut buffer = Vec::<u8>::new();
         source.encode(&mut buffer);
-        if let Some((_,target)) = SocketAddrV4::decode(&buffer) {
+        if let Ok((_,target)) = SocketAddrV4::decode(&buffer) {
             assert_eq!(source,target)
         }
         else {
-            assert!(false)
+            panic!("decode failed");
         }
     }
 
@@ -260,11 +352,11 @@ mod tests {
         let source = SocketAddrV6::new(Ipv6Addr::new(0xFDEC,0xC0BB,0xC329,0,0,0,0,1),8080,0,0);
         let mut buffer = Vec::<u8>::new();
         source.encode(&mut buffer);
-        if let Some((_,target)) = SocketAddrV6::decode(&buffer) {
+        if let Ok((_,target)) = SocketAddrV6::decode(&buffer) {
             assert_eq!(source,target)
         }
         else {
-            assert!(false)
+            panic!("decode failed");
         }
     }
 
@@ -273,11 +365,17 @@ mod tests {
         let source = SocketAddr::V4(SocketAddrV4::new(Ipv4Addr::new(192,168,1,255),8080));
         let mut buffer = Vec::<u8>::new();
         source.encode(&mut buffer);
-        if let Some((_,target)) = SocketAddr::decode(&buffer) {
+        if let Ok((_,target)) = SocketAddr::decode(&buffer) {
             assert_eq!(source,target)
         }
         else {
-            assert!(false)
+            panic!("decode failed");
         }
     }
+
+    #[test]
+    fn test_socketaddr_invalid() {
+        assert_eq!(SocketAddr::decode(&[2]),Err(DecodeError::InvalidTag { offset: 0, tag: 2, }));
+        assert_eq!(SocketAddr::decode(&[0,192,168]),Err(DecodeError::UnexpectedEof { offset: 1, needed: 6, available: 2, }));
+    }
 }
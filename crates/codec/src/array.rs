@@ -0,0 +1,86 @@
+// Echidna - Codec
+
+use {
+    crate::*,
+    std::{
+        convert::TryInto,
+        io,
+    },
+};
+
+// Arrays are encoded as their `N` items, without a length prefix.
+
+impl<T: Codec,const N: usize> Codec for [T; N] {
+    fn decode_with(buffer: &[u8],ctx: &mut DecodeContext) -> Result<(usize,Self),DecodeError> {
+        let (ofs,r) = ctx.nested(|ctx| T::decode_items(buffer,N,ctx))?;
+        match r.try_into() {
+            Ok(r) => Ok((ofs,r)),
+            Err(_) => unreachable!(),
+        }
+    }
+}
+
+impl<T: Encode,const N: usize> Encode for [T; N] {
+    const FIXED_SIZE: Option<usize> = fixed_size_repeat(T::FIXED_SIZE,N);
+    const MIN_SIZE: usize = min_size_repeat(T::MIN_SIZE,N);
+
+    fn schema() -> Schema {
+        Schema::Array(Box::new(T::schema()),N)
+    }
+
+    fn encode(&self,buffer: &mut Vec<u8>) -> usize {
+        if let Some(size) = Self::FIXED_SIZE {
+            buffer.reserve(size);
+        }
+        T::encode_slice(self,buffer)
+    }
+
+    fn size(&self) -> usize {
+        if let Some(size) = Self::FIXED_SIZE {
+            return size;
+        }
+        let mut len = 0;
+        for item in self {
+            len += item.size();
+        }
+        len
+    }
+
+    fn encode_to(&self,sink: &mut Sink<'_>) -> io::Result<usize> {
+        T::encode_slice_to(self,sink)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::*;
+
+    #[test]
+    fn test_array() {
+        let source: [f32; 3] = [1.0,-2.0,3.5];
+        let mut buffer = Vec::<u8>::new();
+        source.encode(&mut buffer);
+        assert_eq!(buffer.len(),12);
+        if let Ok((_,target)) = <[f32; 3]>::decode(&buffer) {
+            assert_eq!(source,target)
+        }
+        else {
+            panic!("decode failed");
+        }
+    }
+
+    #[test]
+    fn test_array_nested() {
+        let source: [[String; 2]; 2] = [["a".to_string(),"b".to_string()],["c".to_string(),"d".to_string()]];
+        let mut buffer = Vec::<u8>::new();
+        source.encode(&mut buffer);
+        if let Ok((_,target)) = <[[String; 2]; 2]>::decode(&buffer) {
+            assert_eq!(source,target)
+        }
+        else {
+            panic!("decode failed");
+        }
+        assert_eq!(<[u16; 4]>::decode(&[1,0,2,0,3]),Err(DecodeError::UnexpectedEof { offset: 4, needed: 4, available: 1, }));
+    }
+}
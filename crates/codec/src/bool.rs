@@ -3,8 +3,21 @@
 use crate::*;
 
 impl Codec for bool {
-    fn decode(buffer: &[u8]) -> Option<(usize,Self)> {
-        Some((1,buffer[0] != 0))
+    fn decode_with(buffer: &[u8],_ctx: &mut DecodeContext) -> Result<(usize,Self),DecodeError> {
+        ensure(buffer,1)?;
+        match buffer[0] {
+            0 => Ok((1,false)),
+            1 => Ok((1,true)),
+            tag => Err(DecodeError::InvalidTag { offset: 0, tag: tag as u32, }),
+        }
+    }
+}
+
+impl Encode for bool {
+    const FIXED_SIZE: Option<usize> = Some(1);
+
+    fn schema() -> Schema {
+        Schema::Bool
     }
 
     fn encode(&self,buffer: &mut Vec<u8>) -> usize {
@@ -20,18 +33,24 @@ impl Codec for bool {
 #[cfg(test)]
 mod tests {
 
-    use crate::Codec;
+    use crate::*;
 
     #[test]
     fn test_bool() {
         let source = true;
         let mut buffer = Vec::<u8>::new();
         source.encode(&mut buffer);
-        if let Some((_,target)) = bool::decode(&buffer) {
+        if let Ok((_,target)) = bool::decode(&buffer) {
             assert_eq!(source,target)
         }
         else {
-            assert!(false)
+            panic!("decode failed");
         }
     }
+
+    #[test]
+    fn test_bool_invalid() {
+        assert_eq!(bool::decode(&[2]),Err(DecodeError::InvalidTag { offset: 0, tag: 2, }));
+        assert_eq!(bool::decode(&[]),Err(DecodeError::UnexpectedEof { offset: 0, needed: 1, available: 0, }));
+    }
 }
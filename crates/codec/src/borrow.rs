@@ -0,0 +1,197 @@
+// Echidna - Codec
+
+use {
+    crate::*,
+    std::{
+        borrow::Cow,
+        io,
+    },
+};
+
+/// Decode without copying, borrowing from the buffer for lifetime `'de`.
+///
+/// Every `Codec` type also decodes this way (into an owned value), so
+/// derived types with borrowed fields can mix owned and borrowed fields.
+/// `&str` and `&[u8]` are wire-compatible with `String` and `Vec<u8>`.
+pub trait BorrowDecode<'de>: Encode where Self: Sized {
+
+    /// Decode from `buffer` into new object that may borrow from `buffer`.
+    ///
+    /// Uses the default `DecodeLimits`.
+    fn borrow_decode(buffer: &'de [u8]) -> Result<(usize,Self),DecodeError> {
+        Self::borrow_decode_with(buffer,&mut DecodeContext::new(DecodeLimits::default()))
+    }
+
+    /// Decode from `buffer` into new object that may borrow from `buffer`,
+    /// within the limits of `ctx`.
+    fn borrow_decode_with(buffer: &'de [u8],ctx: &mut DecodeContext) -> Result<(usize,Self),DecodeError>;
+}
+
+impl<'de,T: Codec> BorrowDecode<'de> for T {
+    fn borrow_decode_with(buffer: &'de [u8],ctx: &mut DecodeContext) -> Result<(usize,Self),DecodeError> {
+        T::decode_with(buffer,ctx)
+    }
+}
+
+fn borrow_bytes<'de>(buffer: &'de [u8],ctx: &mut DecodeContext) -> Result<(usize,&'de [u8]),DecodeError> {
+    let (_,len) = u32::decode_with(buffer,ctx)?;
+    let end = 4usize.checked_add(len as usize).ok_or(DecodeError::LengthOverflow { offset: 0, length: len as u64, })?;
+    ensure(buffer,end)?;
+    Ok((end,&buffer[4..end]))
+}
+
+fn borrow_str<'de>(buffer: &'de [u8],ctx: &mut DecodeContext) -> Result<(usize,&'de str),DecodeError> {
+    let (l,bytes) = borrow_bytes(buffer,ctx)?;
+    match std::str::from_utf8(bytes) {
+        Ok(a) => Ok((l,a)),
+        Err(e) => Err(DecodeError::InvalidUtf8 { offset: 4 + e.valid_up_to(), }),
+    }
+}
+
+pub(crate) fn encode_bytes(bytes: &[u8],buffer: &mut Vec<u8>) -> usize {
+    (bytes.len() as u32).encode(buffer);
+    buffer.extend_from_slice(bytes);
+    4 + bytes.len()
+}
+
+pub(crate) fn encode_bytes_to(bytes: &[u8],sink: &mut Sink<'_>) -> io::Result<usize> {
+    (bytes.len() as u32).encode(sink.buffer());
+    sink.write_bytes(bytes)?;
+    Ok(4 + bytes.len())
+}
+
+impl<'a,'de: 'a> BorrowDecode<'de> for &'a [u8] {
+    fn borrow_decode_with(buffer: &'de [u8],ctx: &mut DecodeContext) -> Result<(usize,Self),DecodeError> {
+        borrow_bytes(buffer,ctx)
+    }
+}
+
+// A reference encodes the same as what it refers to.
+impl<T: Encode + ?Sized> Encode for &T {
+    fn schema() -> Schema {
+        T::schema()
+    }
+
+    fn encode(&self,buffer: &mut Vec<u8>) -> usize {
+        (**self).encode(buffer)
+    }
+
+    fn size(&self) -> usize {
+        (**self).size()
+    }
+
+    fn encode_to(&self,sink: &mut Sink<'_>) -> io::Result<usize> {
+        (**self).encode_to(sink)
+    }
+}
+
+impl<'a,'de: 'a> BorrowDecode<'de> for &'a str {
+    fn borrow_decode_with(buffer: &'de [u8],ctx: &mut DecodeContext) -> Result<(usize,Self),DecodeError> {
+        borrow_str(buffer,ctx)
+    }
+}
+
+impl<'a,'de: 'a> BorrowDecode<'de> for Cow<'a,[u8]> {
+    fn borrow_decode_with(buffer: &'de [u8],ctx: &mut DecodeContext) -> Result<(usize,Self),DecodeError> {
+        let (l,bytes) = borrow_bytes(buffer,ctx)?;
+        Ok((l,Cow::Borrowed(bytes)))
+    }
+}
+
+impl<'a> Encode for Cow<'a,[u8]> {
+    const MIN_SIZE: usize = 4;
+
+    fn schema() -> Schema {
+        Schema::List(Box::new(Schema::U8))
+    }
+
+    fn encode(&self,buffer: &mut Vec<u8>) -> usize {
+        encode_bytes(self,buffer)
+    }
+
+    fn size(&self) -> usize {
+        4 + self.len()
+    }
+
+    fn encode_to(&self,sink: &mut Sink<'_>) -> io::Result<usize> {
+        encode_bytes_to(self,sink)
+    }
+}
+
+impl<'a,'de: 'a> BorrowDecode<'de> for Cow<'a,str> {
+    fn borrow_decode_with(buffer: &'de [u8],ctx: &mut DecodeContext) -> Result<(usize,Self),DecodeError> {
+        let (l,a) = borrow_str(buffer,ctx)?;
+        Ok((l,Cow::Borrowed(a)))
+    }
+}
+
+impl<'a> Encode for Cow<'a,str> {
+    const MIN_SIZE: usize = 4;
+
+    fn schema() -> Schema {
+        Schema::String
+    }
+
+    fn encode(&self,buffer: &mut Vec<u8>) -> usize {
+        encode_bytes(self.as_bytes(),buffer)
+    }
+
+    fn size(&self) -> usize {
+        4 + self.len()
+    }
+
+    fn encode_to(&self,sink: &mut Sink<'_>) -> io::Result<usize> {
+        encode_bytes_to(self.as_bytes(),sink)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use {
+        crate::*,
+        std::borrow::Cow,
+    };
+
+    #[test]
+    fn test_str() {
+        let source = "Hello, World!".to_string();
+        let mut buffer = Vec::<u8>::new();
+        source.encode(&mut buffer);
+        if let Ok((_,target)) = <&str>::borrow_decode(&buffer) {
+            assert_eq!(source,target);
+            assert_eq!(target.as_ptr(),buffer[4..].as_ptr());
+        }
+        else {
+            panic!("decode failed");
+        }
+    }
+
+    #[test]
+    fn test_bytes() {
+        let source: &[u8] = &[1,2,3,4,5];
+        let mut buffer = Vec::<u8>::new();
+        source.encode(&mut buffer);
+        if let Ok((_,target)) = Vec::<u8>::decode(&buffer) {
+            assert_eq!(source,&target[..]);
+        }
+        else {
+            panic!("decode failed");
+        }
+        if let Ok((len,target)) = <&[u8]>::borrow_decode(&buffer) {
+            assert_eq!(len,buffer.len());
+            assert_eq!(source,target);
+            assert_eq!(target.as_ptr(),buffer[4..].as_ptr());
+        }
+        else {
+            panic!("decode failed");
+        }
+        if let Ok((_,target)) = Cow::<[u8]>::borrow_decode(&buffer) {
+            assert_eq!(source,&target[..]);
+            assert!(matches!(target,Cow::Borrowed(_)));
+        }
+        else {
+            panic!("decode failed");
+        }
+    }
+}
@@ -0,0 +1,103 @@
+// Echidna - Codec
+
+use {
+    crate::*,
+    std::{
+        collections::BTreeMap,
+        io,
+    },
+};
+
+// Same as `HashMap`: number of entries as u32, followed by each key and
+// value. Entries are encoded in key order.
+
+impl<K: Codec + Ord,V: Codec> Codec for BTreeMap<K,V> {
+    fn decode_with(buffer: &[u8],ctx: &mut DecodeContext) -> Result<(usize,Self),DecodeError> {
+        let (_,len) = u32::decode_with(buffer,ctx)?;
+        ctx.nested(|ctx| {
+            ctx.elements(len as usize,std::mem::size_of::<(K,V)>())?;
+            let mut r = BTreeMap::<K,V>::new();
+            let mut ofs = 4usize;
+            let entry = min_size_sum(&[K::MIN_SIZE,V::MIN_SIZE]);
+            for i in 0..len {
+                let key_ofs = ofs;
+                let rest = min_size_repeat(entry,(len - i - 1) as usize);
+                let (l,key) = K::decode_with(&buffer[ofs..],ctx).map_err(|e| e.at(ofs).more(rest.saturating_add(V::MIN_SIZE)))?;
+                ofs += l;
+                let (l,value) = V::decode_with(&buffer[ofs..],ctx).map_err(|e| e.at(ofs).more(rest))?;
+                ofs += l;
+                if r.insert(key,value).is_some() {
+                    return Err(DecodeError::DuplicateKey { offset: key_ofs, });
+                }
+            }
+            Ok((ofs,r))
+        })
+    }
+}
+
+impl<K: Encode,V: Encode> Encode for BTreeMap<K,V> {
+    const MIN_SIZE: usize = 4;
+
+    fn schema() -> Schema {
+        Schema::Map(Box::new(K::schema()),Box::new(V::schema()))
+    }
+
+    fn encode(&self,buffer: &mut Vec<u8>) -> usize {
+        if let Some(size) = <(K,V)>::FIXED_SIZE {
+            buffer.reserve(4 + self.len() * size);
+        }
+        (self.len() as u32).encode(buffer);
+        let mut len = 4;
+        for (key,value) in self {
+            len += key.encode(buffer);
+            len += value.encode(buffer);
+        }
+        len
+    }
+
+    fn size(&self) -> usize {
+        if let Some(size) = <(K,V)>::FIXED_SIZE {
+            return 4 + self.len() * size;
+        }
+        let mut len = 4;
+        for (key,value) in self {
+            len += key.size();
+            len += value.size();
+        }
+        len
+    }
+
+    fn encode_to(&self,sink: &mut Sink<'_>) -> io::Result<usize> {
+        let mut len = (self.len() as u32).encode_to(sink)?;
+        for (key,value) in self {
+            len += key.encode_to(sink)?;
+            len += value.encode_to(sink)?;
+        }
+        Ok(len)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use {
+        crate::*,
+        std::collections::BTreeMap,
+    };
+
+    #[test]
+    fn test_btreemap() {
+        let mut source: BTreeMap<String,u16> = BTreeMap::new();
+        source.insert("b".to_string(),2);
+        source.insert("a".to_string(),1);
+        let mut buffer = Vec::<u8>::new();
+        source.encode(&mut buffer);
+        assert_eq!(buffer[8],b'a');
+        if let Ok((_,target)) = BTreeMap::<String,u16>::decode(&buffer) {
+            assert_eq!(source,target)
+        }
+        else {
+            panic!("decode failed");
+        }
+    }
+}
@@ -0,0 +1,148 @@
+// Echidna - Codec
+
+// Slices of numbers are encoded as their little-endian bytes back to back,
+// which on little-endian hosts is exactly how they are stored in memory. So
+// the whole slice can be copied at once, instead of item by item.
+
+macro_rules! bulk_encode {
+    ($ty:ty) => {
+        fn encode_slice(items: &[$ty],buffer: &mut Vec<u8>) -> usize {
+            let len = items.len() * std::mem::size_of::<$ty>();
+            #[cfg(target_endian = "little")]
+            {
+                // SAFETY: numbers have no padding and every byte is initialized
+                let bytes = unsafe { std::slice::from_raw_parts(items.as_ptr() as *const u8,len) };
+                buffer.extend_from_slice(bytes);
+            }
+            #[cfg(target_endian = "big")]
+            {
+                buffer.reserve(len);
+                for item in items {
+                    buffer.extend_from_slice(&item.to_le_bytes());
+                }
+            }
+            len
+        }
+
+        fn encode_slice_to(items: &[$ty],sink: &mut Sink<'_>) -> std::io::Result<usize> {
+            let len = items.len() * std::mem::size_of::<$ty>();
+            #[cfg(target_endian = "little")]
+            {
+                // SAFETY: numbers have no padding and every byte is initialized
+                let bytes = unsafe { std::slice::from_raw_parts(items.as_ptr() as *const u8,len) };
+                sink.write_bytes(bytes)?;
+            }
+            #[cfg(target_endian = "big")]
+            {
+                for item in items {
+                    sink.buffer().extend_from_slice(&item.to_le_bytes());
+                }
+                sink.commit()?;
+            }
+            Ok(len)
+        }
+    };
+}
+
+macro_rules! bulk_decode {
+    ($ty:ty) => {
+        fn decode_items(buffer: &[u8],count: usize,_ctx: &mut DecodeContext) -> Result<(usize,Vec<$ty>),DecodeError> {
+            const SIZE: usize = std::mem::size_of::<$ty>();
+            let len = count.checked_mul(SIZE).ok_or(DecodeError::LengthOverflow { offset: 0, length: count as u64, })?;
+            if buffer.len() < len {
+                // at the first incomplete item, but needing all the missing
+                // bytes, so a stream can read them at once
+                let ofs = buffer.len() / SIZE * SIZE;
+                return Err(DecodeError::UnexpectedEof { offset: ofs, needed: len - ofs, available: buffer.len() - ofs, });
+            }
+            #[cfg(target_endian = "little")]
+            {
+                let mut r = Vec::<$ty>::with_capacity(count);
+                // SAFETY: the buffer holds `len` bytes, the vector has room
+                // for `count` items of `SIZE` bytes, and every bit pattern is
+                // a valid number
+                unsafe {
+                    std::ptr::copy_nonoverlapping(buffer.as_ptr(),r.as_mut_ptr() as *mut u8,len);
+                    r.set_len(count);
+                }
+                Ok((len,r))
+            }
+            #[cfg(target_endian = "big")]
+            {
+                let r = buffer[..len].chunks_exact(SIZE).map(|c| <$ty>::from_le_bytes(std::convert::TryInto::try_into(c).unwrap())).collect();
+                Ok((len,r))
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+
+    use {
+        crate::*,
+        std::collections::VecDeque,
+    };
+
+    // Same wire format as `u16`, without the bulk path.
+    #[derive(Debug,PartialEq)]
+    struct Item(u16);
+
+    impl Codec for Item {
+        fn decode_with(buffer: &[u8],ctx: &mut DecodeContext) -> Result<(usize,Self),DecodeError> {
+            let (l,a) = u16::decode_with(buffer,ctx)?;
+            Ok((l,Item(a)))
+        }
+    }
+
+    impl Encode for Item {
+        fn encode(&self,buffer: &mut Vec<u8>) -> usize {
+            self.0.encode(buffer)
+        }
+
+        fn size(&self) -> usize {
+            2
+        }
+    }
+
+    #[test]
+    fn test_bulk_bytes() {
+        let source: Vec<u16> = vec![0x0102,0x0304,0xFFFE];
+        let mut buffer = Vec::<u8>::new();
+        assert_eq!(source.encode(&mut buffer),10);
+        assert_eq!(buffer,vec![3,0,0,0,0x02,0x01,0x04,0x03,0xFE,0xFF]);
+        let items: Vec<Item> = source.iter().map(|a| Item(*a)).collect();
+        let mut slow = Vec::<u8>::new();
+        items.encode(&mut slow);
+        assert_eq!(buffer,slow);
+        assert_eq!(Vec::<u16>::decode(&buffer),Ok((10,source)));
+        let mut buffer = Vec::<u8>::new();
+        [-1.5f64,0.25].encode(&mut buffer);
+        assert_eq!(buffer,[(-1.5f64).to_le_bytes(),0.25f64.to_le_bytes()].concat());
+    }
+
+    #[test]
+    fn test_bulk_roundtrip() {
+        testing::assert_roundtrip(&(0..1000).map(|i| i as u8).collect::<Vec<u8>>());
+        testing::assert_roundtrip(&(0..1000).map(|i| i as f32 * 0.5).collect::<Vec<f32>>());
+        testing::assert_roundtrip(&vec![i128::MIN,-1,i128::MAX]);
+        testing::assert_roundtrip(&[[1u32,2],[3,4]]);
+        testing::assert_roundtrip(&Vec::<i64>::new());
+        let mut source: VecDeque<i32> = (0..10).collect();
+        source.rotate_left(3);
+        source.push_front(-1);
+        testing::assert_roundtrip(&source);
+    }
+
+    #[test]
+    fn test_bulk_eof() {
+        let buffer = vec![3,0,0,0,1,0,2,0,3];
+        assert_eq!(Vec::<u16>::decode(&buffer),Err(DecodeError::UnexpectedEof { offset: 8, needed: 2, available: 1, }));
+        assert_eq!(Vec::<Item>::decode(&buffer),Err(DecodeError::UnexpectedEof { offset: 8, needed: 2, available: 1, }));
+        assert_eq!(<[u32; 2]>::decode(&[1,0,0,0,2,0]),Err(DecodeError::UnexpectedEof { offset: 4, needed: 4, available: 2, }));
+
+        // everything that is missing, not just the next item
+        assert_eq!(Vec::<u16>::decode(&[100,0,0,0,1,0,2]),Err(DecodeError::UnexpectedEof { offset: 6, needed: 198, available: 1, }));
+        assert_eq!(Vec::<u8>::decode(&[0,0,1,0,7]),Err(DecodeError::UnexpectedEof { offset: 5, needed: 0x10000 - 1, available: 0, }));
+    }
+}
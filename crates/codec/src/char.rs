@@ -0,0 +1,53 @@
+// Echidna - Codec
+
+use crate::*;
+
+impl Codec for char {
+    fn decode_with(buffer: &[u8],ctx: &mut DecodeContext) -> Result<(usize,Self),DecodeError> {
+        let (l,a) = u32::decode_with(buffer,ctx)?;
+        match char::from_u32(a) {
+            Some(c) => Ok((l,c)),
+            None => Err(DecodeError::InvalidChar { offset: 0, value: a, }),
+        }
+    }
+}
+
+impl Encode for char {
+    const FIXED_SIZE: Option<usize> = Some(4);
+
+    fn schema() -> Schema {
+        Schema::Char
+    }
+
+    fn encode(&self,buffer: &mut Vec<u8>) -> usize {
+        (*self as u32).encode(buffer)
+    }
+
+    fn size(&self) -> usize {
+        4
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::*;
+
+    #[test]
+    fn test_char() {
+        let source = 'ß';
+        let mut buffer = Vec::<u8>::new();
+        source.encode(&mut buffer);
+        if let Ok((_,target)) = char::decode(&buffer) {
+            assert_eq!(source,target)
+        }
+        else {
+            panic!("decode failed");
+        }
+    }
+
+    #[test]
+    fn test_char_invalid() {
+        assert_eq!(char::decode(&[0x00,0xD8,0x00,0x00]),Err(DecodeError::InvalidChar { offset: 0, value: 0xD800, }));
+    }
+}
@@ -0,0 +1,131 @@
+// Echidna - Codec
+
+use crate::*;
+
+/// Value followed by a CRC32C of its encoding, so corrupted bytes are found
+/// instead of decoded.
+///
+/// The checksum is 4 bytes, little-endian, over the bytes of the value right
+/// before it. Decoding fails with `DecodeError::ChecksumMismatch` if it does
+/// not match, or with the error of the value if the corruption already
+/// breaks decoding the value. Works as a field, or around a whole message.
+#[derive(Clone,Copy,Debug,Default,PartialEq,Eq,PartialOrd,Ord,Hash)]
+pub struct Checked<T>(pub T);
+
+// CRC32C (Castagnoli) polynomial, reversed.
+const CRC32C_POLY: u32 = 0x82F6_3B78;
+
+const fn crc32c_table() -> [u32; 256] {
+    let mut table = [0u32; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 != 0 { (crc >> 1) ^ CRC32C_POLY } else { crc >> 1 };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+}
+
+static CRC32C_TABLE: [u32; 256] = crc32c_table();
+
+/// CRC32C (Castagnoli) of `bytes`, as in iSCSI and ext4.
+pub fn crc32c(bytes: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for byte in bytes {
+        crc = CRC32C_TABLE[((crc ^ *byte as u32) & 0xFF) as usize] ^ (crc >> 8);
+    }
+    !crc
+}
+
+/// Check the CRC32C that follows the first `len` bytes of `buffer`.
+///
+/// Returns the length including the checksum.
+pub(crate) fn verify_crc32c(buffer: &[u8],len: usize) -> Result<usize,DecodeError> {
+    let (_,stored) = u32::decode(&buffer[len..]).map_err(|e| e.at(len))?;
+    if stored != crc32c(&buffer[..len]) {
+        return Err(DecodeError::ChecksumMismatch { offset: len, });
+    }
+    Ok(len + 4)
+}
+
+impl<T: Codec> Codec for Checked<T> {
+    fn decode_with(buffer: &[u8],ctx: &mut DecodeContext) -> Result<(usize,Self),DecodeError> {
+        let (len,value) = T::decode_with(buffer,ctx)?;
+        Ok((verify_crc32c(buffer,len)?,Checked(value)))
+    }
+}
+
+impl<T: Encode> Encode for Checked<T> {
+    const FIXED_SIZE: Option<usize> = match T::FIXED_SIZE {
+        Some(size) => Some(size + 4),
+        None => None,
+    };
+    const MIN_SIZE: usize = T::MIN_SIZE.saturating_add(4);
+
+    fn schema() -> Schema {
+        Schema::Checked(Box::new(T::schema()))
+    }
+
+    fn encode(&self,buffer: &mut Vec<u8>) -> usize {
+        let start = buffer.len();
+        let len = self.0.encode(buffer);
+        let crc = crc32c(&buffer[start..]);
+        len + crc.encode(buffer)
+    }
+
+    fn size(&self) -> usize {
+        self.0.size() + 4
+    }
+}
+
+impl<T> From<T> for Checked<T> {
+    fn from(value: T) -> Self {
+        Checked(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::*;
+
+    #[test]
+    fn test_crc32c() {
+        assert_eq!(crc32c(b""),0);
+        assert_eq!(crc32c(b"123456789"),0xE306_9283);
+        assert_eq!(crc32c(&[0u8; 32]),0x8A91_36AA);
+    }
+
+    #[test]
+    fn test_checked() {
+        let mut buffer = Vec::<u8>::new();
+        Checked(0x1234u16).encode(&mut buffer);
+        assert_eq!(buffer.len(),6);
+        assert_eq!(&buffer[2..],&crc32c(&[0x34,0x12]).to_le_bytes());
+        assert_eq!(Checked::<u16>::FIXED_SIZE,Some(6));
+        assert_eq!(Checked::<u16>::decode(&buffer),Ok((6,Checked(0x1234))));
+
+        // a flipped bit in the value or the checksum
+        buffer[1] ^= 0x10;
+        assert_eq!(Checked::<u16>::decode(&buffer),Err(DecodeError::ChecksumMismatch { offset: 2, }));
+        buffer[1] ^= 0x10;
+        buffer[5] ^= 0x01;
+        assert_eq!(Checked::<u16>::decode(&buffer),Err(DecodeError::ChecksumMismatch { offset: 2, }));
+        assert_eq!(Checked::<u16>::decode(&buffer[..4]),Err(DecodeError::UnexpectedEof { offset: 2, needed: 4, available: 2, }));
+    }
+
+    #[test]
+    fn test_checked_items() {
+        let source = vec![Checked("a".to_string()),Checked("bc".to_string())];
+        let mut buffer = Vec::<u8>::new();
+        source.encode(&mut buffer);
+        assert_eq!(Vec::<Checked<String>>::decode(&buffer),Ok((buffer.len(),source)));
+        buffer[17] = b'x';
+        assert_eq!(Vec::<Checked<String>>::decode(&buffer),Err(DecodeError::ChecksumMismatch { offset: 19, }));
+    }
+}
@@ -0,0 +1,292 @@
+// Echidna - Codec
+
+use {
+    crate::*,
+    std::borrow::Cow,
+};
+
+/// Value encoded and then compressed, for big payloads with repetitions,
+/// like raw images, point clouds or logs.
+///
+/// On the wire, the length of the encoding (u32) and the length of the
+/// compressed bytes (u32) are followed by the compressed bytes. The
+/// compressor is a small LZ4-style one, built in. Values that don't get
+/// smaller are stored as they are, with both lengths the same. Works as a
+/// field, or around a whole message.
+///
+/// `size` compresses the value to find out, which is as expensive as
+/// `encode`. Encoding doesn't ask for it: versioned structs and
+/// `send_message` of the data crate fill in their lengths afterwards, and
+/// `encode_into` only asks when the value doesn't fit.
+///
+/// Decoding counts the decompressed bytes against `DecodeLimits::max_alloc`
+/// before decompressing. Errors in the value are at offsets in the
+/// decompressed bytes, counted from the start of the compressed bytes.
+#[derive(Clone,Copy,Debug,Default,PartialEq,Eq,PartialOrd,Ord,Hash)]
+pub struct Compressed<T>(pub T);
+
+// Shortest match worth a sequence, and the furthest one back.
+const MIN_MATCH: usize = 4;
+const MAX_OFFSET: usize = 0xFFFF;
+
+const HASH_BITS: u32 = 12;
+
+fn hash(bytes: &[u8]) -> usize {
+    let value = u32::from_le_bytes([bytes[0],bytes[1],bytes[2],bytes[3]]);
+    (value.wrapping_mul(0x9E37_79B1) >> (32 - HASH_BITS)) as usize
+}
+
+// Lengths from 15 on continue after the token, in bytes of up to 255.
+fn push_length(mut rest: usize,packed: &mut Vec<u8>) {
+    while rest >= 255 {
+        packed.push(255);
+        rest -= 255;
+    }
+    packed.push(rest as u8);
+}
+
+// Token with the literal and match lengths, the literals, then the offset
+// and length of the match. The last sequence has literals only.
+fn push_sequence(literals: &[u8],matched: Option<(usize,usize)>,packed: &mut Vec<u8>) {
+    let literal_len = literals.len();
+    let match_len = matched.map_or(0,|(_,len)| len - MIN_MATCH);
+    packed.push(((literal_len.min(15) as u8) << 4) | (match_len.min(15) as u8));
+    if literal_len >= 15 {
+        push_length(literal_len - 15,packed);
+    }
+    packed.extend_from_slice(literals);
+    if let Some((offset,_)) = matched {
+        packed.extend_from_slice(&(offset as u16).to_le_bytes());
+        if match_len >= 15 {
+            push_length(match_len - 15,packed);
+        }
+    }
+}
+
+fn compress(raw: &[u8]) -> Vec<u8> {
+    let mut packed = Vec::<u8>::new();
+    let mut table = vec![usize::MAX; 1 << HASH_BITS];
+    let mut anchor = 0usize;
+    let mut i = 0usize;
+    while i + MIN_MATCH <= raw.len() {
+        let h = hash(&raw[i..]);
+        let candidate = table[h];
+        table[h] = i;
+        if (candidate != usize::MAX) && (i - candidate <= MAX_OFFSET) && (raw[candidate..candidate + MIN_MATCH] == raw[i..i + MIN_MATCH]) {
+            let mut len = MIN_MATCH;
+            while (i + len < raw.len()) && (raw[candidate + len] == raw[i + len]) {
+                len += 1;
+            }
+            push_sequence(&raw[anchor..i],Some((i - candidate,len)),&mut packed);
+            i += len;
+            anchor = i;
+        }
+        else {
+            i += 1;
+        }
+    }
+    push_sequence(&raw[anchor..],None,&mut packed);
+    packed
+}
+
+fn read_length(packed: &[u8],i: &mut usize) -> Option<usize> {
+    let mut len = 0usize;
+    loop {
+        let byte = *packed.get(*i)?;
+        *i += 1;
+        len = len.checked_add(byte as usize)?;
+        if byte != 255 {
+            return Some(len);
+        }
+    }
+}
+
+// `None` if `packed` is not a valid compression of `raw_len` bytes.
+fn decompress(packed: &[u8],raw_len: usize) -> Option<Vec<u8>> {
+    let mut raw = Vec::<u8>::with_capacity(raw_len);
+    let mut i = 0usize;
+    while i < packed.len() {
+        let token = packed[i];
+        i += 1;
+        let mut literal_len = (token >> 4) as usize;
+        if literal_len == 15 {
+            literal_len += read_length(packed,&mut i)?;
+        }
+        let literals = packed.get(i..i.checked_add(literal_len)?)?;
+        if raw.len() + literal_len > raw_len {
+            return None;
+        }
+        raw.extend_from_slice(literals);
+        i += literal_len;
+        if i == packed.len() {
+            break;
+        }
+        let offset = u16::from_le_bytes([*packed.get(i)?,*packed.get(i + 1)?]) as usize;
+        i += 2;
+        let mut match_len = (token & 15) as usize + MIN_MATCH;
+        if token & 15 == 15 {
+            match_len += read_length(packed,&mut i)?;
+        }
+        if (offset == 0) || (offset > raw.len()) || (raw.len() + match_len > raw_len) {
+            return None;
+        }
+
+        // byte by byte, the match can overlap what it copies
+        let start = raw.len() - offset;
+        for k in 0..match_len {
+            raw.push(raw[start + k]);
+        }
+    }
+    if raw.len() == raw_len { Some(raw) } else { None }
+}
+
+/// Append the lengths and the compression of `raw` to `buffer`.
+///
+/// Returns the number of bytes appended.
+pub(crate) fn encode_compressed(raw: &[u8],buffer: &mut Vec<u8>) -> usize {
+    let packed = compress(raw);
+    let stored = if packed.len() < raw.len() { &packed[..] } else { raw };
+    (raw.len() as u32).encode(buffer);
+    (stored.len() as u32).encode(buffer);
+    buffer.extend_from_slice(stored);
+    8 + stored.len()
+}
+
+/// Decompress the bytes `encode_compressed` wrote at the front of `buffer`.
+///
+/// Returns the number of bytes taken from `buffer` and the decompressed
+/// bytes, which borrow from `buffer` if they were stored as they are.
+pub(crate) fn decode_compressed<'a>(buffer: &'a [u8],ctx: &mut DecodeContext) -> Result<(usize,Cow<'a,[u8]>),DecodeError> {
+    let (_,raw_len) = u32::decode_with(buffer,ctx)?;
+    let (_,packed_len) = u32::decode_with(&buffer[4..],ctx).map_err(|e| e.at(4))?;
+    let end = 8usize.checked_add(packed_len as usize).ok_or(DecodeError::LengthOverflow { offset: 4, length: packed_len as u64, })?;
+    ensure(buffer,end)?;
+    let packed = &buffer[8..end];
+    if packed_len == raw_len {
+        return Ok((end,Cow::Borrowed(packed)));
+    }
+    ctx.alloc(raw_len as usize)?;
+    match decompress(packed,raw_len as usize) {
+        Some(raw) => Ok((end,Cow::Owned(raw))),
+        None => Err(DecodeError::InvalidCompression { offset: 8, }),
+    }
+}
+
+/// Error of the value inside the decompressed bytes `decode_compressed`
+/// returned.
+///
+/// Those bytes are complete, so a value that runs out of them is malformed,
+/// waiting for more bytes won't help.
+pub(crate) fn inner_error(error: DecodeError) -> DecodeError {
+    match error {
+        DecodeError::UnexpectedEof { .. } => DecodeError::InvalidCompression { offset: 8, },
+        error => error.at(8),
+    }
+}
+
+impl<T: Codec> Codec for Compressed<T> {
+    fn decode_with(buffer: &[u8],ctx: &mut DecodeContext) -> Result<(usize,Self),DecodeError> {
+        let (end,raw) = decode_compressed(buffer,ctx)?;
+        let (len,value) = T::decode_with(&raw,ctx).map_err(inner_error)?;
+        if len != raw.len() {
+            return Err(DecodeError::InvalidCompression { offset: 8, });
+        }
+        Ok((end,Compressed(value)))
+    }
+}
+
+impl<T: Encode> Encode for Compressed<T> {
+    const MIN_SIZE: usize = 8;
+
+    fn schema() -> Schema {
+        Schema::Compressed(Box::new(T::schema()))
+    }
+
+    fn encode(&self,buffer: &mut Vec<u8>) -> usize {
+        let mut raw = Vec::<u8>::with_capacity(self.0.size());
+        self.0.encode(&mut raw);
+        encode_compressed(&raw,buffer)
+    }
+
+    // the size is only known after compressing, see the type docs
+    fn size(&self) -> usize {
+        self.encode(&mut Vec::new())
+    }
+}
+
+impl<T> From<T> for Compressed<T> {
+    fn from(value: T) -> Self {
+        Compressed(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::*;
+
+    #[test]
+    fn test_compressed() {
+        let image: Vec<u8> = (0..10000).map(|i| ((i / 100) % 7) as u8).collect();
+        let mut buffer = Vec::<u8>::new();
+        Compressed(image.clone()).encode(&mut buffer);
+        assert!(buffer.len() < 1000);
+        assert_eq!(Compressed(image.clone()).size(),buffer.len());
+        assert_eq!(Compressed::<Vec<u8>>::decode(&buffer),Ok((buffer.len(),Compressed(image))));
+
+        // long literals, long matches and overlapping matches
+        let mut text = Vec::<u8>::new();
+        for i in 0..300u32 {
+            text.extend_from_slice(&i.wrapping_mul(0x9E37_79B1).to_le_bytes());
+        }
+        text.extend_from_slice(&[b'a'; 1000]);
+        text.extend_from_slice(&text.clone()[..700]);
+        let mut buffer = Vec::<u8>::new();
+        Compressed(text.clone()).encode(&mut buffer);
+        assert!(buffer.len() < text.len());
+        assert_eq!(Compressed::<Vec<u8>>::decode(&buffer),Ok((buffer.len(),Compressed(text))));
+    }
+
+    #[test]
+    fn test_stored() {
+        let mut buffer = Vec::<u8>::new();
+        Compressed(0x1234_5678u32).encode(&mut buffer);
+        assert_eq!(buffer,vec![4,0,0,0,4,0,0,0,0x78,0x56,0x34,0x12]);
+        assert_eq!(Compressed::<u32>::decode(&buffer),Ok((12,Compressed(0x1234_5678))));
+        let mut buffer = Vec::<u8>::new();
+        Compressed(()).encode(&mut buffer);
+        assert_eq!(Compressed::<()>::decode(&buffer),Ok((8,Compressed(()))));
+    }
+
+    #[test]
+    fn test_invalid() {
+        let mut buffer = Vec::<u8>::new();
+        Compressed(vec![7u16; 100]).encode(&mut buffer);
+        let len = buffer.len();
+
+        // wrong decompressed length, cut off compressed bytes, offset too far back
+        buffer[0] += 1;
+        assert_eq!(Compressed::<Vec<u16>>::decode(&buffer),Err(DecodeError::InvalidCompression { offset: 8, }));
+        buffer[0] -= 1;
+        assert_eq!(Compressed::<Vec<u16>>::decode(&buffer[..len - 1]),Err(DecodeError::UnexpectedEof { offset: 0, needed: len, available: len - 1, }));
+        let hostile = vec![200,0,0,0,4,0,0,0,0x00,0xFF,0x00,0x01];
+        assert_eq!(Compressed::<Vec<u8>>::decode(&hostile),Err(DecodeError::InvalidCompression { offset: 8, }));
+
+        // all bytes there, but too few for the value inside
+        let short = vec![2,0,0,0,2,0,0,0,1,2];
+        assert_eq!(Compressed::<u32>::decode(&short),Err(DecodeError::InvalidCompression { offset: 8, }));
+        assert_eq!(Value::decode_with(&Compressed::<u32>::schema(),&short),Err(DecodeError::InvalidCompression { offset: 8, }));
+
+        // a decompression bomb stops at the allocation limit
+        let bomb = vec![0xFF,0xFF,0xFF,0x7F,1,0,0,0,0];
+        assert_eq!(Compressed::<Vec<u8>>::decode(&bomb),Err(DecodeError::LimitExceeded { offset: 0, limit: Limit::Allocation, value: 0x7FFF_FFFF, }));
+    }
+
+    #[test]
+    fn test_stream_short() {
+        let data = vec![2,0,0,0,2,0,0,0,1,2,3,4,5,6];
+        let mut decoder = Decoder::new(&data[..]);
+        let error = decoder.decode::<Compressed<u32>>().unwrap_err();
+        assert_eq!(error.kind(),std::io::ErrorKind::InvalidData);
+    }
+}
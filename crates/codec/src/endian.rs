@@ -0,0 +1,109 @@
+// Echidna - Codec
+
+use crate::*;
+
+/// Integer or float encoded big-endian (network byte order) instead of
+/// little-endian, for packets of sensors and gateways that speak big-endian
+/// protocols.
+///
+/// Fields of derived types can also be marked `#[codec(big_endian)]`, or all
+/// integer and float fields of a type at once with `#[codec(big_endian)]` on
+/// the type.
+#[derive(Clone,Copy,Debug,Default,PartialEq,Eq,PartialOrd,Ord,Hash)]
+pub struct BigEndian<T>(pub T);
+
+macro_rules! impl_big_endian {
+    ($($ty:ty => $schema:ident),+) => {
+        $(
+            impl Codec for BigEndian<$ty> {
+                fn decode_with(buffer: &[u8],_ctx: &mut DecodeContext) -> Result<(usize,Self),DecodeError> {
+                    const SIZE: usize = std::mem::size_of::<$ty>();
+                    ensure(buffer,SIZE)?;
+                    let mut bytes = [0u8; SIZE];
+                    bytes.copy_from_slice(&buffer[..SIZE]);
+                    Ok((SIZE,BigEndian(<$ty>::from_be_bytes(bytes))))
+                }
+            }
+
+            impl Encode for BigEndian<$ty> {
+                const FIXED_SIZE: Option<usize> = Some(std::mem::size_of::<$ty>());
+
+                fn schema() -> Schema {
+                    Schema::BigEndian(Box::new(Schema::$schema))
+                }
+
+                fn encode(&self,buffer: &mut Vec<u8>) -> usize {
+                    buffer.extend_from_slice(&self.0.to_be_bytes());
+                    std::mem::size_of::<$ty>()
+                }
+
+                fn size(&self) -> usize {
+                    std::mem::size_of::<$ty>()
+                }
+            }
+        )+
+    };
+}
+
+impl_big_endian!(
+    u8 => U8,
+    u16 => U16,
+    u32 => U32,
+    u64 => U64,
+    u128 => U128,
+    i8 => I8,
+    i16 => I16,
+    i32 => I32,
+    i64 => I64,
+    i128 => I128,
+    f32 => F32,
+    f64 => F64
+);
+
+impl<T> From<T> for BigEndian<T> {
+    fn from(value: T) -> Self {
+        BigEndian(value)
+    }
+}
+
+/// Size of an integer or float schema, `None` for other schemas.
+pub(crate) fn number_size(schema: &Schema) -> Option<usize> {
+    match schema {
+        Schema::U8 | Schema::I8 => Some(1),
+        Schema::U16 | Schema::I16 => Some(2),
+        Schema::U32 | Schema::I32 | Schema::F32 => Some(4),
+        Schema::U64 | Schema::I64 | Schema::F64 => Some(8),
+        Schema::U128 | Schema::I128 => Some(16),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::*;
+
+    #[test]
+    fn test_big_endian() {
+        let mut buffer = Vec::<u8>::new();
+        BigEndian(0x1234u16).encode(&mut buffer);
+        BigEndian(-2i32).encode(&mut buffer);
+        BigEndian(1.0f32).encode(&mut buffer);
+        BigEndian(7u8).encode(&mut buffer);
+        assert_eq!(buffer,vec![0x12,0x34,0xFF,0xFF,0xFF,0xFE,0x3F,0x80,0x00,0x00,7]);
+        assert_eq!(BigEndian::<u16>::decode(&buffer),Ok((2,BigEndian(0x1234))));
+        assert_eq!(BigEndian::<i32>::decode(&buffer[2..]),Ok((4,BigEndian(-2))));
+        assert_eq!(BigEndian::<f32>::decode(&buffer[6..]),Ok((4,BigEndian(1.0))));
+        assert_eq!(BigEndian::<u64>::FIXED_SIZE,Some(8));
+        assert_eq!(BigEndian::<u128>::decode(&buffer),Err(DecodeError::UnexpectedEof { offset: 0, needed: 16, available: 11, }));
+    }
+
+    #[test]
+    fn test_value() {
+        let mut buffer = Vec::<u8>::new();
+        (BigEndian(0x0102u16),BigEndian(-1.5f64)).encode(&mut buffer);
+        let schema = <(BigEndian<u16>,BigEndian<f64>)>::schema();
+        assert_eq!(Value::decode_with(&schema,&buffer),Ok((10,Value::List(vec![Value::UInt(0x0102),Value::Float(-1.5)]))));
+        assert_ne!(BigEndian::<u16>::type_hash(),u16::type_hash());
+    }
+}
@@ -0,0 +1,220 @@
+// Echidna - Codec
+
+use std::fmt;
+
+/// Reason why decoding failed.
+///
+/// Every variant carries the byte offset at which decoding failed, counted
+/// from the start of the buffer that was passed to the outermost `decode`.
+#[derive(Clone,Copy,Debug,PartialEq,Eq)]
+pub enum DecodeError {
+
+    /// The buffer ended before the value was complete.
+    UnexpectedEof { offset: usize, needed: usize, available: usize, },
+
+    /// An enum tag did not match any known variant.
+    InvalidTag { offset: usize, tag: u32, },
+
+    /// String data was not valid UTF-8.
+    InvalidUtf8 { offset: usize, },
+
+    /// A `char` is not a Unicode scalar value.
+    InvalidChar { offset: usize, value: u32, },
+
+    /// A `Varint` is too long, not in its shortest form, or does not fit
+    /// the integer type.
+    InvalidVarint { offset: usize, },
+
+    /// Nanoseconds of a time are out of range, or the time does not fit
+    /// `SystemTime`.
+    InvalidTime { offset: usize, },
+
+    /// A map or set holds the same key twice.
+    DuplicateKey { offset: usize, },
+
+    /// A length prefix does not fit in the address space, or is too short
+    /// for what it holds.
+    LengthOverflow { offset: usize, length: u64, },
+
+    /// Decoding would exceed one of the `DecodeLimits`.
+    LimitExceeded { offset: usize, limit: Limit, value: usize, },
+
+    /// `Value::decode_with` met a type without a layout, see
+    /// `Schema::Opaque`.
+    Opaque { offset: usize, },
+
+    /// The checksum of a `Checked` value does not match its bytes.
+    ChecksumMismatch { offset: usize, },
+
+    /// The bytes of a `Compressed` value do not decompress into the value.
+    InvalidCompression { offset: usize, },
+}
+
+/// Which of the `DecodeLimits` was exceeded.
+#[derive(Clone,Copy,Debug,PartialEq,Eq)]
+pub enum Limit {
+    Allocation,
+    Elements,
+    Depth,
+}
+
+impl DecodeError {
+
+    /// Byte offset at which decoding failed.
+    pub fn offset(&self) -> usize {
+        match self {
+            DecodeError::UnexpectedEof { offset, .. } => *offset,
+            DecodeError::InvalidTag { offset, .. } => *offset,
+            DecodeError::InvalidUtf8 { offset } => *offset,
+            DecodeError::InvalidChar { offset, .. } => *offset,
+            DecodeError::InvalidVarint { offset } => *offset,
+            DecodeError::InvalidTime { offset } => *offset,
+            DecodeError::DuplicateKey { offset } => *offset,
+            DecodeError::LengthOverflow { offset, .. } => *offset,
+            DecodeError::LimitExceeded { offset, .. } => *offset,
+            DecodeError::Opaque { offset } => *offset,
+            DecodeError::ChecksumMismatch { offset } => *offset,
+            DecodeError::InvalidCompression { offset } => *offset,
+        }
+    }
+
+    /// Move the error `base` bytes further into the buffer.
+    ///
+    /// Used by containers to translate an error from a nested `decode`,
+    /// which only sees a slice, to the buffer of the container.
+    pub fn at(mut self,base: usize) -> Self {
+        match &mut self {
+            DecodeError::UnexpectedEof { offset, .. } => *offset += base,
+            DecodeError::InvalidTag { offset, .. } => *offset += base,
+            DecodeError::InvalidUtf8 { offset } => *offset += base,
+            DecodeError::InvalidChar { offset, .. } => *offset += base,
+            DecodeError::InvalidVarint { offset } => *offset += base,
+            DecodeError::InvalidTime { offset } => *offset += base,
+            DecodeError::DuplicateKey { offset } => *offset += base,
+            DecodeError::LengthOverflow { offset, .. } => *offset += base,
+            DecodeError::LimitExceeded { offset, .. } => *offset += base,
+            DecodeError::Opaque { offset } => *offset += base,
+            DecodeError::ChecksumMismatch { offset } => *offset += base,
+            DecodeError::InvalidCompression { offset } => *offset += base,
+        }
+        self
+    }
+
+    /// Add `bytes` to what an `UnexpectedEof` needs.
+    ///
+    /// Used by containers when more items follow the one that ran out of
+    /// bytes, so a stream reads the bytes of all of them at once.
+    pub fn more(mut self,bytes: usize) -> Self {
+        if let DecodeError::UnexpectedEof { needed, .. } = &mut self {
+            *needed = needed.saturating_add(bytes);
+        }
+        self
+    }
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self,f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeError::UnexpectedEof { offset,needed,available } => write!(f,"unexpected end of buffer at offset {}: {} bytes needed, {} available",offset,needed,available),
+            DecodeError::InvalidTag { offset,tag } => write!(f,"invalid tag {} at offset {}",tag,offset),
+            DecodeError::InvalidUtf8 { offset } => write!(f,"invalid UTF-8 at offset {}",offset),
+            DecodeError::InvalidChar { offset,value } => write!(f,"invalid char {:#X} at offset {}",value,offset),
+            DecodeError::InvalidVarint { offset } => write!(f,"invalid varint at offset {}",offset),
+            DecodeError::InvalidTime { offset } => write!(f,"invalid time at offset {}",offset),
+            DecodeError::DuplicateKey { offset } => write!(f,"duplicate key at offset {}",offset),
+            DecodeError::LengthOverflow { offset,length } => write!(f,"length {} at offset {} overflows",length,offset),
+            DecodeError::LimitExceeded { offset,limit,value } => write!(f,"{:?} limit exceeded at offset {} ({})",limit,offset,value),
+            DecodeError::Opaque { offset } => write!(f,"type without schema at offset {}",offset),
+            DecodeError::ChecksumMismatch { offset } => write!(f,"checksum mismatch at offset {}",offset),
+            DecodeError::InvalidCompression { offset } => write!(f,"invalid compressed data at offset {}",offset),
+        }
+    }
+}
+
+impl std::error::Error for DecodeError { }
+
+/// Reason why encoding into a fixed buffer failed.
+#[derive(Clone,Copy,Debug,PartialEq,Eq)]
+pub enum EncodeError {
+
+    /// The encoded value does not fit in the buffer.
+    BufferTooSmall { needed: usize, available: usize, },
+}
+
+impl fmt::Display for EncodeError {
+    fn fmt(&self,f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EncodeError::BufferTooSmall { needed,available } => write!(f,"buffer too small: {} bytes needed, {} available",needed,available),
+        }
+    }
+}
+
+impl std::error::Error for EncodeError { }
+
+/// Reason why `to_text` or `from_text` failed.
+#[derive(Clone,Debug,PartialEq)]
+pub enum TextError {
+
+    /// The text is not valid, or does not fit the type, at `line` and
+    /// `column`, both counted from 1.
+    Invalid { line: usize, column: usize, message: String, },
+
+    /// The type, or one of its fields, does not describe its layout, see
+    /// `Schema::Opaque`.
+    Opaque,
+
+    /// The value from the text does not decode as the type, like a set that
+    /// holds the same key twice.
+    Decode(DecodeError),
+}
+
+impl fmt::Display for TextError {
+    fn fmt(&self,f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TextError::Invalid { line,column,message } => write!(f,"{} at line {}, column {}",message,line,column),
+            TextError::Opaque => write!(f,"type without schema"),
+            TextError::Decode(e) => write!(f,"{}",e),
+        }
+    }
+}
+
+impl std::error::Error for TextError { }
+
+/// Check that `buffer` holds at least `needed` bytes.
+#[doc(hidden)]
+pub fn ensure(buffer: &[u8],needed: usize) -> Result<(),DecodeError> {
+    if buffer.len() < needed {
+        Err(DecodeError::UnexpectedEof { offset: 0, needed, available: buffer.len(), })
+    }
+    else {
+        Ok(())
+    }
+}
+
+/// Error of a field of a `#[codec(versioned)]` struct at `offset`, decoded
+/// from the `length` bytes after the version and the length.
+///
+/// Those bytes are all there, so a field that runs past them means the
+/// length is wrong, not that more bytes are coming.
+#[doc(hidden)]
+pub fn versioned_field_error(error: DecodeError,offset: usize,length: u32) -> DecodeError {
+    match error {
+        DecodeError::UnexpectedEof { .. } => DecodeError::LengthOverflow { offset: 2, length: length as u64, },
+        error => error.at(offset),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::*;
+
+    #[test]
+    fn test_truncated() {
+        let source: Vec<u32> = vec![1,2,3];
+        let mut buffer = Vec::<u8>::new();
+        source.encode(&mut buffer);
+        buffer.truncate(10);
+        assert_eq!(Vec::<u32>::decode(&buffer),Err(DecodeError::UnexpectedEof { offset: 8, needed: 8, available: 2, }));
+    }
+}
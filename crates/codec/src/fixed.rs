@@ -0,0 +1,163 @@
+// Echidna - Codec
+
+// Helpers to calculate `Encode::FIXED_SIZE` and `Encode::MIN_SIZE` at
+// compile time, also used by the derive macro.
+
+/// Fixed size of a value that consists of parts with the given fixed sizes,
+/// like a struct or a tuple.
+///
+/// Only fixed if all parts are.
+pub const fn fixed_size_sum(sizes: &[Option<usize>]) -> Option<usize> {
+    let mut sum = 0usize;
+    let mut i = 0;
+    while i < sizes.len() {
+        match sizes[i] {
+            Some(size) => match sum.checked_add(size) {
+                Some(s) => { sum = s; },
+                None => { return None; },
+            },
+            None => { return None; },
+        }
+        i += 1;
+    }
+    Some(sum)
+}
+
+/// Fixed size of `count` items of fixed size `size`, like an array.
+pub const fn fixed_size_repeat(size: Option<usize>,count: usize) -> Option<usize> {
+    match size {
+        Some(size) => size.checked_mul(count),
+        None => None,
+    }
+}
+
+/// Fixed size of an enum with a `tag` byte tag, where the fields of the
+/// variants have the given fixed sizes.
+///
+/// Only fixed if all variants have the same fixed size.
+pub const fn fixed_size_variants(tag: usize,sizes: &[Option<usize>]) -> Option<usize> {
+    if sizes.is_empty() {
+        return None;
+    }
+    let first = match sizes[0] {
+        Some(size) => size,
+        None => { return None; },
+    };
+    let mut i = 1;
+    while i < sizes.len() {
+        match sizes[i] {
+            Some(size) if size == first => { },
+            _ => { return None; },
+        }
+        i += 1;
+    }
+    tag.checked_add(first)
+}
+
+/// Smallest size of a value that consists of parts with the given smallest
+/// sizes, like a struct or a tuple.
+pub const fn min_size_sum(sizes: &[usize]) -> usize {
+    let mut sum = 0usize;
+    let mut i = 0;
+    while i < sizes.len() {
+        sum = sum.saturating_add(sizes[i]);
+        i += 1;
+    }
+    sum
+}
+
+/// Smallest size of `count` items of smallest size `size`.
+pub const fn min_size_repeat(size: usize,count: usize) -> usize {
+    size.saturating_mul(count)
+}
+
+/// Smallest size of an enum with a `tag` byte tag, where the fields of the
+/// variants have the given smallest sizes.
+pub const fn min_size_variants(tag: usize,sizes: &[usize]) -> usize {
+    if sizes.is_empty() {
+        return tag;
+    }
+    let mut min = sizes[0];
+    let mut i = 1;
+    while i < sizes.len() {
+        if sizes[i] < min {
+            min = sizes[i];
+        }
+        i += 1;
+    }
+    tag.saturating_add(min)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use {
+        crate::*,
+        std::{
+            collections::HashMap,
+            net::SocketAddrV4,
+            rc::Rc,
+            time::Duration,
+        },
+    };
+
+    #[test]
+    fn test_fixed_size() {
+        assert_eq!(u8::FIXED_SIZE,Some(1));
+        assert_eq!(<(u16,f64,char)>::FIXED_SIZE,Some(14));
+        assert_eq!(<[SocketAddrV4; 3]>::FIXED_SIZE,Some(18));
+        assert_eq!(<[[f32; 3]; 3]>::FIXED_SIZE,Some(36));
+        assert_eq!(<(Duration,[u8; 4])>::FIXED_SIZE,Some(16));
+        assert_eq!(<Rc<Duration>>::FIXED_SIZE,None);
+        assert_eq!(<()>::FIXED_SIZE,Some(0));
+        assert_eq!(String::FIXED_SIZE,None);
+        assert_eq!(<Vec<u8>>::FIXED_SIZE,None);
+        assert_eq!(<Option<u8>>::FIXED_SIZE,None);
+        assert_eq!(<(u8,String)>::FIXED_SIZE,None);
+        assert_eq!(<[String; 0]>::FIXED_SIZE,None);
+    }
+
+    #[test]
+    fn test_min_size() {
+        assert_eq!(u8::MIN_SIZE,1);
+        assert_eq!(String::MIN_SIZE,4);
+        assert_eq!(<Option<u64>>::MIN_SIZE,1);
+        assert_eq!(<(u16,Vec<u8>)>::MIN_SIZE,6);
+        assert_eq!(<[Option<u8>; 3]>::MIN_SIZE,3);
+        assert_eq!(<HashMap<String,u32>>::MIN_SIZE,4);
+        assert_eq!(<Rc<String>>::MIN_SIZE,0);
+        assert_eq!(min_size_sum(&[1,usize::MAX]),usize::MAX);
+        assert_eq!(min_size_variants(4,&[8,0,2]),4);
+        assert_eq!(min_size_variants(1,&[]),1);
+
+        // a container that runs out of bytes needs the rest of its items too
+        let buffer = [3,0,0,0,1,0,0,0,b'a',2,0];
+        assert_eq!(Vec::<String>::decode(&buffer),Err(DecodeError::UnexpectedEof { offset: 9, needed: 8, available: 2, }));
+    }
+
+    #[test]
+    fn test_fixed_size_containers() {
+        let source: Vec<(u16,f32)> = (0..100).map(|i| (i,i as f32)).collect();
+        assert_eq!(source.size(),604);
+        let mut buffer = Vec::<u8>::new();
+        assert_eq!(source.encode(&mut buffer),604);
+        assert_eq!(buffer.len(),604);
+        let mut source = HashMap::<u32,u64>::new();
+        source.insert(1,2);
+        source.insert(3,4);
+        assert_eq!(source.size(),28);
+        assert_eq!([[1u16; 4]; 2].size(),16);
+    }
+
+    #[test]
+    fn test_fixed_size_helpers() {
+        assert_eq!(fixed_size_sum(&[]),Some(0));
+        assert_eq!(fixed_size_sum(&[Some(1),Some(usize::MAX)]),None);
+        assert_eq!(fixed_size_repeat(Some(4),3),Some(12));
+        assert_eq!(fixed_size_repeat(None,3),None);
+        assert_eq!(fixed_size_variants(4,&[Some(8),Some(8)]),Some(12));
+        assert_eq!(fixed_size_variants(1,&[Some(0)]),Some(1));
+        assert_eq!(fixed_size_variants(4,&[Some(8),Some(4)]),None);
+        assert_eq!(fixed_size_variants(4,&[]),None);
+    }
+}
@@ -3,13 +3,19 @@
 use crate::*;
 
 impl Codec for f32 {
-    fn decode(buffer: &[u8]) -> Option<(usize,Self)> {
-        if let Some(a) = u32::decode(buffer) {
-            Some((4,f32::from_bits(a.1)))
-        }
-        else {
-            None
-        }
+    fn decode_with(buffer: &[u8],ctx: &mut DecodeContext) -> Result<(usize,Self),DecodeError> {
+        let (l,a) = u32::decode_with(buffer,ctx)?;
+        Ok((l,f32::from_bits(a)))
+    }
+
+    bulk_decode!(f32);
+}
+
+impl Encode for f32 {
+    const FIXED_SIZE: Option<usize> = Some(4);
+
+    fn schema() -> Schema {
+        Schema::F32
     }
 
     fn encode(&self,buffer: &mut Vec<u8>) -> usize {
@@ -20,16 +26,24 @@ impl Codec for f32 {
     fn size(&self) -> usize {
         4
     }
+
+    bulk_encode!(f32);
 }
 
 impl Codec for f64 {
-    fn decode(buffer: &[u8]) -> Option<(usize,Self)> {
-        if let Some(a) = u64::decode(buffer) {
-            Some((8,f64::from_bits(a.1)))
-        }
-        else {
-            None
-        }
+    fn decode_with(buffer: &[u8],ctx: &mut DecodeContext) -> Result<(usize,Self),DecodeError> {
+        let (l,a) = u64::decode_with(buffer,ctx)?;
+        Ok((l,f64::from_bits(a)))
+    }
+
+    bulk_decode!(f64);
+}
+
+impl Encode for f64 {
+    const FIXED_SIZE: Option<usize> = Some(8);
+
+    fn schema() -> Schema {
+        Schema::F64
     }
 
     fn encode(&self,buffer: &mut Vec<u8>) -> usize {
@@ -40,23 +54,25 @@ impl Codec for f64 {
     fn size(&self) -> usize {
         8
     }
+
+    bulk_encode!(f64);
 }
 
 #[cfg(test)]
 mod tests {
 
-    use crate::Codec;
+    use crate::*;
 
     #[test]
     fn test_f32() {
         let source: f32 = 1.23456;
         let mut buffer = Vec::<u8>::new();
         source.encode(&mut buffer);
-        if let Some((_,target)) = f32::decode(&buffer) {
+        if let Ok((_,target)) = f32::decode(&buffer) {
             assert_eq!(source,target)
         }
         else {
-            assert!(false)
+            panic!("decode failed");
         }
     }
 
@@ -65,11 +81,11 @@ mod tests {
         let source: f64 = 1.23456;
         let mut buffer = Vec::<u8>::new();
         source.encode(&mut buffer);
-        if let Some((_,target)) = f64::decode(&buffer) {
+        if let Ok((_,target)) = f64::decode(&buffer) {
             assert_eq!(source,target)
         }
         else {
-            assert!(false)
+            panic!("decode failed");
         }
     }
 }
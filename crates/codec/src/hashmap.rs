@@ -5,39 +5,45 @@ use {
     std::{
         collections::HashMap,
         hash::Hash,
+        io,
     },
 };
 
 impl<K: Codec + Eq + Hash,V: Codec> Codec for HashMap<K,V> {
-    fn decode(buffer: &[u8]) -> Option<(usize,Self)> {
-        if let Some((_,len)) = u32::decode(buffer) {
+    fn decode_with(buffer: &[u8],ctx: &mut DecodeContext) -> Result<(usize,Self),DecodeError> {
+        let (_,len) = u32::decode_with(buffer,ctx)?;
+        ctx.nested(|ctx| {
+            ctx.elements(len as usize,std::mem::size_of::<(K,V)>())?;
             let mut r = HashMap::<K,V>::new();
             let mut ofs = 4usize;
-            for _ in 0..len {
-                let key = if let Some((l,key)) = K::decode(&buffer[ofs..]) {
-                    ofs += l;
-                    key
+            let entry = min_size_sum(&[K::MIN_SIZE,V::MIN_SIZE]);
+            for i in 0..len {
+                let key_ofs = ofs;
+                let rest = min_size_repeat(entry,(len - i - 1) as usize);
+                let (l,key) = K::decode_with(&buffer[ofs..],ctx).map_err(|e| e.at(ofs).more(rest.saturating_add(V::MIN_SIZE)))?;
+                ofs += l;
+                let (l,value) = V::decode_with(&buffer[ofs..],ctx).map_err(|e| e.at(ofs).more(rest))?;
+                ofs += l;
+                if r.insert(key,value).is_some() {
+                    return Err(DecodeError::DuplicateKey { offset: key_ofs, });
                 }
-                else {
-                    return None;
-                };
-                let value = if let Some((l,value)) = V::decode(&buffer[ofs..]) {
-                    ofs += l;
-                    value
-                }
-                else {
-                    return None;
-                };
-                r.insert(key,value);
             }
-            Some((ofs,r))
-        }
-        else {
-            None
-        }
+            Ok((ofs,r))
+        })
+    }
+}
+
+impl<K: Encode,V: Encode> Encode for HashMap<K,V> {
+    const MIN_SIZE: usize = 4;
+
+    fn schema() -> Schema {
+        Schema::Map(Box::new(K::schema()),Box::new(V::schema()))
     }
 
     fn encode(&self,buffer: &mut Vec<u8>) -> usize {
+        if let Some(size) = <(K,V)>::FIXED_SIZE {
+            buffer.reserve(4 + self.len() * size);
+        }
         (self.len() as u32).encode(buffer);
         let mut len = 4;
         for (key,value) in self {
@@ -48,6 +54,9 @@ impl<K: Codec + Eq + Hash,V: Codec> Codec for HashMap<K,V> {
     }
 
     fn size(&self) -> usize {
+        if let Some(size) = <(K,V)>::FIXED_SIZE {
+            return 4 + self.len() * size;
+        }
         let mut len = 4;
         for (key,value) in self {
             len += key.size();
@@ -55,12 +64,21 @@ impl<K: Codec + Eq + Hash,V: Codec> Codec for HashMap<K,V> {
         }
         len
     }
+
+    fn encode_to(&self,sink: &mut Sink<'_>) -> io::Result<usize> {
+        let mut len = (self.len() as u32).encode_to(sink)?;
+        for (key,value) in self {
+            len += key.encode_to(sink)?;
+            len += value.encode_to(sink)?;
+        }
+        Ok(len)
+    }
 }
 
 #[cfg(test)]
 mod tests {
 
-    use crate::Codec;
+    use crate::*;
     use std::collections::HashMap;
 
     #[test]
@@ -71,11 +89,17 @@ mod tests {
         source.insert(2,-1);
         let mut buffer = Vec::<u8>::new();
         source.encode(&mut buffer);
-        if let Some((_,target)) = HashMap::<u16,i32>::decode(&buffer) {
+        if let Ok((_,target)) = HashMap::<u16,i32>::decode(&buffer) {
             assert_eq!(source,target)
         }
         else {
-            assert!(false)
+            panic!("decode failed");
         }
     }
+
+    #[test]
+    fn test_hashmap_duplicate() {
+        let buffer = vec![2,0,0,0,7,0,1,0,0,0,7,0,2,0,0,0];
+        assert_eq!(HashMap::<u16,i32>::decode(&buffer),Err(DecodeError::DuplicateKey { offset: 10, }));
+    }
 }
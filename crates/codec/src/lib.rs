@@ -4,14 +4,33 @@
 //! 
 //! This is a replacement for `serde` that just encodes/decodes to/from a flat
 //! binary dump.
-
-pub trait Codec where Self: Sized {
-
-    /// Decode from `buffer` into new object.
-    /// 
-    /// If successful, returns the number of bytes decoded and the newly
-    /// created object. If not successful, returns `None`.
-    fn decode(buffer: &[u8]) -> Option<(usize,Self)>;
+//!
+//! `Encode` writes a value, `Codec` decodes it into a new owned value, and
+//! `BorrowDecode` decodes it into a value that borrows from the buffer.
+//! `Encoder` and `Decoder` do the same over `io::Write` and `io::Read`.
+//! `to_text` and `from_text` do the same in a JSON-like text format.
+
+use std::io;
+
+pub trait Encode {
+
+    /// Size of the encoded version of every value of this type, if it is the
+    /// same for all values.
+    ///
+    /// Integers, floats and structs or tuples of them have a fixed size.
+    /// Containers use it to calculate their size without visiting the items,
+    /// and to reserve space in the buffer before encoding.
+    const FIXED_SIZE: Option<usize> = None;
+
+    /// Size of the encoded version of the smallest value of this type.
+    ///
+    /// A container that runs out of bytes halfway adds this for each of the
+    /// items that are still to come, so `Decoder` can wait for all of them
+    /// before it tries again.
+    const MIN_SIZE: usize = match Self::FIXED_SIZE {
+        Some(size) => size,
+        None => 0,
+    };
 
     /// Encode `self` onto the end of `buffer`.
     /// 
@@ -22,36 +41,173 @@ pub trait Codec where Self: Sized {
     /// 
     /// Returns the number of bytes this would encode into.
     fn size(&self) -> usize;
+
+    /// Encode `self` into `sink`, which passes it on to a writer in pieces.
+    ///
+    /// Returns the number of bytes encoded. Containers override this to
+    /// encode their items one by one, so big values are never buffered whole.
+    fn encode_to(&self,sink: &mut Sink<'_>) -> io::Result<usize> {
+        let len = self.encode(sink.buffer());
+        sink.commit()?;
+        Ok(len)
+    }
+
+    /// Encode `self` into the start of a fixed `buffer`, like shared memory
+    /// or a preallocated UDP frame.
+    ///
+    /// Returns the number of bytes encoded, or `EncodeError::BufferTooSmall`
+    /// if the value does not fit. The value is written in pieces of about
+    /// 64KB, and only pieces that fit whole, so values smaller than that
+    /// leave `buffer` untouched if they don't fit. Bigger ones may have
+    /// overwritten the start of `buffer` by then. `size` is only called to
+    /// report how much was needed.
+    fn encode_into(&self,buffer: &mut [u8]) -> Result<usize,EncodeError> {
+        encode_into_slice(self,buffer)
+    }
+
+    /// Describe how values of this type are laid out on the wire.
+    ///
+    /// Built-in types and `#[derive(Codec)]` describe their layout, other
+    /// types are `Schema::Opaque`.
+    fn schema() -> Schema {
+        Schema::Opaque(std::any::type_name::<Self>().to_string())
+    }
+
+    /// Stable 64-bit hash of the wire layout of this type, see
+    /// `Schema::fingerprint`.
+    ///
+    /// Two sides that exchange values of a type can compare hashes to find
+    /// out if they agree on its layout.
+    fn type_hash() -> u64 {
+        Self::schema().fingerprint()
+    }
+
+    /// Encode the items of a slice, one after the other.
+    ///
+    /// Used by `Vec`, slices, `VecDeque` and arrays. Numeric types override
+    /// this to copy the whole slice at once.
+    #[doc(hidden)]
+    fn encode_slice(items: &[Self],buffer: &mut Vec<u8>) -> usize where Self: Sized {
+        let mut len = 0;
+        for item in items {
+            len += item.encode(buffer);
+        }
+        len
+    }
+
+    /// Encode the items of a slice into `sink`, one after the other.
+    #[doc(hidden)]
+    fn encode_slice_to(items: &[Self],sink: &mut Sink<'_>) -> io::Result<usize> where Self: Sized {
+        let mut len = 0;
+        for item in items {
+            len += item.encode_to(sink)?;
+        }
+        Ok(len)
+    }
+}
+
+pub trait Codec: Encode where Self: Sized {
+
+    /// Decode from `buffer` into new object.
+    /// 
+    /// If successful, returns the number of bytes decoded and the newly
+    /// created object. If not successful, returns a `DecodeError` that
+    /// describes what went wrong and where. Uses the default `DecodeLimits`.
+    fn decode(buffer: &[u8]) -> Result<(usize,Self),DecodeError> {
+        Self::decode_with(buffer,&mut DecodeContext::new(DecodeLimits::default()))
+    }
+
+    /// Decode from `buffer` into new object, within the limits of `ctx`.
+    ///
+    /// Containers pass `ctx` on to the `decode_with` of their items.
+    fn decode_with(buffer: &[u8],ctx: &mut DecodeContext) -> Result<(usize,Self),DecodeError>;
+
+    /// Decode `count` items, one after the other.
+    ///
+    /// Used by `Vec`, `VecDeque` and arrays, after checking `count` against
+    /// the limits. Numeric types override this to copy all items at once.
+    #[doc(hidden)]
+    fn decode_items(buffer: &[u8],count: usize,ctx: &mut DecodeContext) -> Result<(usize,Vec<Self>),DecodeError> {
+        let mut r = Vec::<Self>::new();
+        let mut ofs = 0usize;
+        for i in 0..count {
+            let (l,item) = Self::decode_with(&buffer[ofs..],ctx).map_err(|e| e.at(ofs).more(min_size_repeat(Self::MIN_SIZE,count - i - 1)))?;
+            ofs += l;
+            r.push(item);
+        }
+        Ok((ofs,r))
+    }
 }
 
 pub use macros::*;
 
-mod bool;
-pub use crate::bool::*;
+extern crate self as codec;
 
-mod ui8;
-pub use ui8::*;
+mod error;
+pub use error::*;
 
-mod ui16;
-pub use ui16::*;
+mod limits;
+pub use limits::*;
 
-mod ui32;
-pub use ui32::*;
+mod borrow;
+pub use borrow::*;
 
-mod ui64;
-pub use ui64::*;
+mod stream;
+pub use stream::*;
 
-mod float;
-pub use float::*;
+mod varint;
+pub use varint::*;
 
-mod string;
-pub use string::*;
+mod time;
+pub use time::*;
 
-mod vec;
-pub use vec::*;
+mod fixed;
+pub use fixed::*;
 
-mod hashmap;
-pub use hashmap::*;
+mod endian;
+pub use endian::*;
+
+mod checked;
+pub use checked::*;
+
+mod compressed;
+pub use compressed::*;
+
+mod pool;
+pub use pool::*;
+
+mod schema;
+pub use schema::*;
+
+mod value;
+pub use value::*;
+
+mod text;
+pub use text::*;
+
+#[cfg(any(test,feature = "testing"))]
+pub mod testing;
+
+#[macro_use]
+mod bulk;
 
+mod bool;
+mod ui8;
+mod ui16;
+mod ui32;
+mod ui64;
+mod ui128;
+mod usize;
+mod float;
+mod char;
+mod string;
+mod option;
+mod pointer;
+mod tuple;
+mod array;
+mod vec;
+mod vecdeque;
+mod hashmap;
+mod btreemap;
+mod sets;
 mod addrs;
-pub use addrs::*;
\ No newline at end of file
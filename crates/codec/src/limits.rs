@@ -0,0 +1,153 @@
+// Echidna - Codec
+
+use crate::*;
+
+/// Upper bounds on what a single decode may do.
+///
+/// Length prefixes come straight from the wire, so containers check them
+/// against these limits before allocating anything.
+#[derive(Clone,Copy,Debug,PartialEq,Eq)]
+pub struct DecodeLimits {
+
+    /// Maximum number of bytes all containers together may allocate.
+    pub max_alloc: usize,
+
+    /// Maximum number of elements in a single container.
+    pub max_elements: usize,
+
+    /// Maximum nesting depth of containers and derived types.
+    pub max_depth: usize,
+}
+
+impl DecodeLimits {
+
+    /// No limits at all, for trusted input.
+    pub fn unlimited() -> DecodeLimits {
+        DecodeLimits {
+            max_alloc: usize::MAX,
+            max_elements: usize::MAX,
+            max_depth: usize::MAX,
+        }
+    }
+}
+
+impl Default for DecodeLimits {
+
+    /// 256MB total allocation, 16M elements per container, 64 levels deep.
+    fn default() -> DecodeLimits {
+        DecodeLimits {
+            max_alloc: 256 * 1024 * 1024,
+            max_elements: 16 * 1024 * 1024,
+            max_depth: 64,
+        }
+    }
+}
+
+/// State of a single decode, threaded through all nested `decode_with` calls.
+pub struct DecodeContext {
+    limits: DecodeLimits,
+    allocated: usize,
+    depth: usize,
+}
+
+impl DecodeContext {
+
+    pub fn new(limits: DecodeLimits) -> DecodeContext {
+        DecodeContext {
+            limits,
+            allocated: 0,
+            depth: 0,
+        }
+    }
+
+    pub fn limits(&self) -> &DecodeLimits {
+        &self.limits
+    }
+
+    /// Number of bytes allocated by containers so far.
+    pub fn allocated(&self) -> usize {
+        self.allocated
+    }
+
+    /// Enter a nested container or derived type.
+    pub fn enter(&mut self) -> Result<(),DecodeError> {
+        if self.depth >= self.limits.max_depth {
+            return Err(DecodeError::LimitExceeded { offset: 0, limit: Limit::Depth, value: self.depth + 1, });
+        }
+        self.depth += 1;
+        Ok(())
+    }
+
+    /// Leave a nested container or derived type.
+    pub fn leave(&mut self) {
+        debug_assert!(self.depth > 0,"leave without enter");
+        self.depth = self.depth.saturating_sub(1);
+    }
+
+    /// Decode a nested container or derived type with `f`, between `enter`
+    /// and `leave`. Leaves also when `f` fails, so the context can be used
+    /// again after an error.
+    pub fn nested<T>(&mut self,f: impl FnOnce(&mut DecodeContext) -> Result<T,DecodeError>) -> Result<T,DecodeError> {
+        self.enter()?;
+        let result = f(self);
+        self.leave();
+        result
+    }
+
+    /// Account for `bytes` of allocation.
+    ///
+    /// Call this before allocating.
+    pub fn alloc(&mut self,bytes: usize) -> Result<(),DecodeError> {
+        let allocated = self.allocated.saturating_add(bytes);
+        if allocated > self.limits.max_alloc {
+            return Err(DecodeError::LimitExceeded { offset: 0, limit: Limit::Allocation, value: bytes, });
+        }
+        self.allocated = allocated;
+        Ok(())
+    }
+
+    /// Account for a container of `count` items of `item_size` bytes each.
+    ///
+    /// Call this before allocating the container.
+    pub fn elements(&mut self,count: usize,item_size: usize) -> Result<(),DecodeError> {
+        if count > self.limits.max_elements {
+            return Err(DecodeError::LimitExceeded { offset: 0, limit: Limit::Elements, value: count, });
+        }
+        self.alloc(count.saturating_mul(item_size))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::*;
+
+    #[test]
+    fn test_limits() {
+        let source: Vec<Vec<u16>> = vec![vec![1,2,3],vec![4,5]];
+        let mut buffer = Vec::<u8>::new();
+        source.encode(&mut buffer);
+        let mut ctx = DecodeContext::new(DecodeLimits { max_alloc: 1024, max_elements: 2, max_depth: 2, });
+        assert_eq!(Vec::<Vec<u16>>::decode_with(&buffer,&mut ctx),Err(DecodeError::LimitExceeded { offset: 4, limit: Limit::Elements, value: 3, }));
+        let mut ctx = DecodeContext::new(DecodeLimits { max_alloc: 1024, max_elements: 4, max_depth: 1, });
+        assert_eq!(Vec::<Vec<u16>>::decode_with(&buffer,&mut ctx),Err(DecodeError::LimitExceeded { offset: 4, limit: Limit::Depth, value: 2, }));
+        let mut ctx = DecodeContext::new(DecodeLimits { max_alloc: 64, max_elements: 4, max_depth: 2, });
+        assert!(Vec::<Vec<u16>>::decode_with(&buffer,&mut ctx).is_ok());
+        assert_eq!(ctx.allocated(),2 * std::mem::size_of::<Vec<u16>>() + 5 * 2);
+    }
+
+    #[test]
+    fn test_depth_after_error() {
+        let mut ctx = DecodeContext::new(DecodeLimits::default());
+        assert!(Vec::<Vec<u16>>::decode_with(&[1,0,0,0,2,0,0,0,1,0],&mut ctx).is_err());
+        assert!(std::collections::HashMap::<u8,Vec<u8>>::decode_with(&[1,0,0,0,7,9],&mut ctx).is_err());
+        assert!(<(u8,Option<u16>)>::decode_with(&[1,1],&mut ctx).is_err());
+        assert_eq!(ctx.depth,0);
+    }
+
+    #[test]
+    fn test_hostile_length() {
+        let buffer = vec![0xFF,0xFF,0xFF,0xFF,b'a'];
+        assert_eq!(String::decode(&buffer),Err(DecodeError::LimitExceeded { offset: 0, limit: Limit::Allocation, value: 0xFFFFFFFF, }));
+    }
+}
@@ -0,0 +1,82 @@
+// Echidna - Codec
+
+use {
+    crate::*,
+    std::io,
+};
+
+// One byte, 0 for `None` or 1 for `Some`, followed by the value for `Some`.
+
+impl<T: Codec> Codec for Option<T> {
+    fn decode_with(buffer: &[u8],ctx: &mut DecodeContext) -> Result<(usize,Self),DecodeError> {
+        ensure(buffer,1)?;
+        match buffer[0] {
+            0 => Ok((1,None)),
+            1 => {
+                let (l,value) = T::decode_with(&buffer[1..],ctx).map_err(|e| e.at(1))?;
+                Ok((1 + l,Some(value)))
+            },
+            tag => Err(DecodeError::InvalidTag { offset: 0, tag: tag as u32, }),
+        }
+    }
+}
+
+impl<T: Encode> Encode for Option<T> {
+    const MIN_SIZE: usize = 1;
+
+    fn schema() -> Schema {
+        Schema::Option(Box::new(T::schema()))
+    }
+
+    fn encode(&self,buffer: &mut Vec<u8>) -> usize {
+        match self {
+            None => {
+                buffer.push(0);
+                1
+            },
+            Some(value) => {
+                buffer.push(1);
+                1 + value.encode(buffer)
+            },
+        }
+    }
+
+    fn size(&self) -> usize {
+        match self {
+            None => 1,
+            Some(value) => 1 + value.size(),
+        }
+    }
+
+    fn encode_to(&self,sink: &mut Sink<'_>) -> io::Result<usize> {
+        match self {
+            None => 0u8.encode_to(sink),
+            Some(value) => Ok(1u8.encode_to(sink)? + value.encode_to(sink)?),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::*;
+
+    #[test]
+    fn test_option() {
+        let source: Vec<Option<String>> = vec![Some("Hello".to_string()),None];
+        let mut buffer = Vec::<u8>::new();
+        source.encode(&mut buffer);
+        if let Ok((_,target)) = Vec::<Option<String>>::decode(&buffer) {
+            assert_eq!(source,target)
+        }
+        else {
+            panic!("decode failed");
+        }
+    }
+
+    #[test]
+    fn test_option_invalid() {
+        assert_eq!(Option::<u8>::decode(&[2,0]),Err(DecodeError::InvalidTag { offset: 0, tag: 2, }));
+        assert_eq!(Option::<u16>::decode(&[1,0]),Err(DecodeError::UnexpectedEof { offset: 1, needed: 2, available: 1, }));
+    }
+}
@@ -0,0 +1,133 @@
+// Echidna - Codec
+
+use {
+    crate::*,
+    std::{
+        io,
+        rc::Rc,
+        sync::Arc,
+    },
+};
+
+// `Box`, `Rc` and `Arc` are encoded as the value they point to. Decoding
+// `Rc` and `Arc` creates a new allocation for every occurrence. They don't
+// pass on `FIXED_SIZE`, because recursive types like
+// `enum List { Nil, Cons(u32,Box<List>), }` would need their own
+// `FIXED_SIZE` to calculate it.
+
+impl<T: Codec> Codec for Box<T> {
+    fn decode_with(buffer: &[u8],ctx: &mut DecodeContext) -> Result<(usize,Self),DecodeError> {
+        ctx.alloc(std::mem::size_of::<T>())?;
+        let (l,value) = T::decode_with(buffer,ctx)?;
+        Ok((l,Box::new(value)))
+    }
+}
+
+impl<T: Encode + ?Sized> Encode for Box<T> {
+    fn schema() -> Schema {
+        T::schema()
+    }
+
+    fn encode(&self,buffer: &mut Vec<u8>) -> usize {
+        (**self).encode(buffer)
+    }
+
+    fn size(&self) -> usize {
+        (**self).size()
+    }
+
+    fn encode_to(&self,sink: &mut Sink<'_>) -> io::Result<usize> {
+        (**self).encode_to(sink)
+    }
+}
+
+impl<T: Codec> Codec for Rc<T> {
+    fn decode_with(buffer: &[u8],ctx: &mut DecodeContext) -> Result<(usize,Self),DecodeError> {
+        ctx.alloc(std::mem::size_of::<T>())?;
+        let (l,value) = T::decode_with(buffer,ctx)?;
+        Ok((l,Rc::new(value)))
+    }
+}
+
+impl<T: Encode + ?Sized> Encode for Rc<T> {
+    fn schema() -> Schema {
+        T::schema()
+    }
+
+    fn encode(&self,buffer: &mut Vec<u8>) -> usize {
+        (**self).encode(buffer)
+    }
+
+    fn size(&self) -> usize {
+        (**self).size()
+    }
+
+    fn encode_to(&self,sink: &mut Sink<'_>) -> io::Result<usize> {
+        (**self).encode_to(sink)
+    }
+}
+
+impl<T: Codec> Codec for Arc<T> {
+    fn decode_with(buffer: &[u8],ctx: &mut DecodeContext) -> Result<(usize,Self),DecodeError> {
+        ctx.alloc(std::mem::size_of::<T>())?;
+        let (l,value) = T::decode_with(buffer,ctx)?;
+        Ok((l,Arc::new(value)))
+    }
+}
+
+impl<T: Encode + ?Sized> Encode for Arc<T> {
+    fn schema() -> Schema {
+        T::schema()
+    }
+
+    fn encode(&self,buffer: &mut Vec<u8>) -> usize {
+        (**self).encode(buffer)
+    }
+
+    fn size(&self) -> usize {
+        (**self).size()
+    }
+
+    fn encode_to(&self,sink: &mut Sink<'_>) -> io::Result<usize> {
+        (**self).encode_to(sink)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use {
+        crate::*,
+        std::{
+            rc::Rc,
+            sync::Arc,
+        },
+    };
+
+    #[test]
+    fn test_box() {
+        let source: Box<u32> = Box::new(42);
+        let mut buffer = Vec::<u8>::new();
+        source.encode(&mut buffer);
+        assert_eq!(buffer,vec![42,0,0,0]);
+        if let Ok((_,target)) = Box::<u32>::decode(&buffer) {
+            assert_eq!(source,target)
+        }
+        else {
+            panic!("decode failed");
+        }
+    }
+
+    #[test]
+    fn test_rc_arc() {
+        let source: (Rc<String>,Arc<Vec<u16>>) = (Rc::new("Hello".to_string()),Arc::new(vec![1,2,3]));
+        let mut buffer = Vec::<u8>::new();
+        source.encode(&mut buffer);
+        if let Ok((_,target)) = <(Rc<String>,Arc<Vec<u16>>)>::decode(&buffer) {
+            assert_eq!(source,target)
+        }
+        else {
+            panic!("decode failed");
+        }
+    }
+}
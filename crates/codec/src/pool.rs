@@ -0,0 +1,135 @@
+// Echidna - Codec
+
+use std::{
+    ops::{
+        Deref,
+        DerefMut,
+    },
+    sync::{
+        Arc,
+        Mutex,
+    },
+};
+
+/// Recycles encode buffers, so sending many values does not allocate a new
+/// `Vec` for each of them.
+///
+/// Clones share the same buffers. Buffers go back to the pool when their
+/// `PooledBuffer` is dropped, up to `max_buffers` of them; the rest is freed.
+#[derive(Clone)]
+pub struct BufferPool {
+    buffers: Arc<Mutex<Vec<Vec<u8>>>>,
+    max_buffers: usize,
+}
+
+impl BufferPool {
+
+    pub fn new(max_buffers: usize) -> BufferPool {
+        BufferPool {
+            buffers: Arc::new(Mutex::new(Vec::new())),
+            max_buffers,
+        }
+    }
+
+    /// Take an empty buffer from the pool, or a new one if the pool is empty.
+    pub fn get(&self) -> PooledBuffer {
+        let buffer = self.buffers.lock().unwrap().pop().unwrap_or_default();
+        PooledBuffer {
+            buffer,
+            pool: self.clone(),
+        }
+    }
+
+    /// Number of buffers waiting in the pool.
+    pub fn available(&self) -> usize {
+        self.buffers.lock().unwrap().len()
+    }
+
+    fn put(&self,mut buffer: Vec<u8>) {
+        buffer.clear();
+        let mut buffers = self.buffers.lock().unwrap();
+        if buffers.len() < self.max_buffers {
+            buffers.push(buffer);
+        }
+    }
+}
+
+/// Buffer taken from a `BufferPool`, goes back to the pool when dropped.
+///
+/// Derefs to `Vec<u8>`, so values encode into it as usual.
+pub struct PooledBuffer {
+    buffer: Vec<u8>,
+    pool: BufferPool,
+}
+
+impl PooledBuffer {
+
+    /// Keep the buffer instead of returning it to the pool.
+    pub fn into_inner(mut self) -> Vec<u8> {
+        std::mem::take(&mut self.buffer)
+    }
+}
+
+impl Deref for PooledBuffer {
+    type Target = Vec<u8>;
+    fn deref(&self) -> &Vec<u8> {
+        &self.buffer
+    }
+}
+
+impl DerefMut for PooledBuffer {
+    fn deref_mut(&mut self) -> &mut Vec<u8> {
+        &mut self.buffer
+    }
+}
+
+impl Drop for PooledBuffer {
+    fn drop(&mut self) {
+        // buffers taken by `into_inner` never held anything worth keeping
+        if self.buffer.capacity() > 0 {
+            self.pool.put(std::mem::take(&mut self.buffer));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::*;
+
+    #[test]
+    fn test_reuse() {
+        let pool = BufferPool::new(2);
+        let mut buffer = pool.get();
+        42u32.encode(&mut buffer);
+        let ptr = buffer.as_ptr();
+        drop(buffer);
+        assert_eq!(pool.available(),1);
+        let buffer = pool.get();
+        assert!(buffer.is_empty());
+        assert_eq!(buffer.as_ptr(),ptr);
+        assert_eq!(pool.available(),0);
+    }
+
+    #[test]
+    fn test_max_buffers() {
+        let pool = BufferPool::new(2);
+        let buffers: Vec<PooledBuffer> = (0..4).map(|i| {
+            let mut buffer = pool.get();
+            (i as u64).encode(&mut buffer);
+            buffer
+        }).collect();
+        drop(buffers);
+        assert_eq!(pool.available(),2);
+    }
+
+    #[test]
+    fn test_into_inner() {
+        let pool = BufferPool::new(2);
+        let mut buffer = pool.get();
+        "hello".to_string().encode(&mut buffer);
+        let buffer = buffer.into_inner();
+        assert_eq!(buffer.len(),9);
+        assert_eq!(pool.available(),0);
+    }
+}
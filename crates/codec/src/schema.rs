@@ -0,0 +1,261 @@
+// Echidna - Codec
+
+use std::cell::RefCell;
+
+/// Description of how a type is laid out on the wire.
+///
+/// Every type that implements `Encode` describes itself with
+/// `Encode::schema`, derived types as a `Struct` or an `Enum` of the
+/// schemas of their fields. `Value::decode_with` uses it to decode the type
+/// without knowing the Rust type.
+#[derive(Clone,Debug,PartialEq)]
+pub enum Schema {
+
+    /// No bytes at all, like `()`.
+    Unit,
+
+    Bool,
+    U8,
+    U16,
+    U32,
+    U64,
+    U128,
+    I8,
+    I16,
+    I32,
+    I64,
+    I128,
+    F32,
+    F64,
+    Char,
+
+    /// Length prefixed UTF-8 string.
+    String,
+
+    /// LEB128 `Varint`, zigzag-mapped if `signed`.
+    Varint { signed: bool, },
+
+    /// `Option`, one byte 0 or 1, followed by the value for 1.
+    Option(Box<Schema>),
+
+    /// Length prefixed items, like `Vec`, `VecDeque` and sets.
+    List(Box<Schema>),
+
+    /// Fixed number of items without length prefix, like arrays.
+    Array(Box<Schema>,usize),
+
+    /// Length prefixed key/value pairs.
+    Map(Box<Schema>,Box<Schema>),
+
+    /// Fields one after the other, like tuples.
+    Tuple(Vec<Schema>),
+
+    /// Named fields one after the other. Tuple structs have fields named
+    /// `0`, `1` and so on.
+    Struct { name: String, fields: Vec<Field>, },
+
+    /// Tag of `tag_size` bytes, little-endian unless `big_endian`, followed
+    /// by the fields of the variant.
+    Enum { name: String, tag_size: usize, big_endian: bool, variants: Vec<Variant>, },
+
+    /// `#[codec(versioned)]` struct of the given version, whose first fields,
+    /// as many as the number after the version, are in every version. The
+    /// version (u16) and length (u32) of the encoder's fields, followed by
+    /// the fields. Fields of newer versions than the encoder's are missing,
+    /// fields after the ones in the schema are skipped.
+    Versioned(u16,usize,Box<Schema>),
+
+    /// `BigEndian` integer or float, the bytes of the number in reverse.
+    BigEndian(Box<Schema>),
+
+    /// `Checked` value, followed by its CRC32C (u32).
+    Checked(Box<Schema>),
+
+    /// `Compressed` value, the lengths (u32) of the value and of the
+    /// compressed bytes, followed by the compressed bytes.
+    Compressed(Box<Schema>),
+
+    /// The innermost enclosing `Struct` or `Enum` called `name`, for
+    /// recursive types.
+    Recursive(String),
+
+    /// Type that does not describe its layout, like a field encoded `with`
+    /// a module.
+    Opaque(String),
+}
+
+/// Field of a `Schema::Struct` or a `Variant`.
+#[derive(Clone,Debug,PartialEq)]
+pub struct Field {
+    pub name: String,
+    pub schema: Schema,
+}
+
+/// Variant of a `Schema::Enum`.
+#[derive(Clone,Debug,PartialEq)]
+pub struct Variant {
+    pub name: String,
+    pub tag: u32,
+    pub fields: Vec<Field>,
+}
+
+impl Schema {
+
+    /// Stable 64-bit hash of the wire layout.
+    ///
+    /// Covers the kinds of all fields, their order, the enum tags and the
+    /// nested types, but not the names of types, fields or variants, so two
+    /// types with the same layout have the same hash, and renaming one keeps
+    /// it. Versioned structs are compatible across versions, so only the
+    /// fields every version has count. Opaque
+    /// types all hash the same, because their names can come from
+    /// `std::any::type_name`, which is not stable. The hash is FNV-1a, the
+    /// same on every platform and Rust version.
+    pub fn fingerprint(&self) -> u64 {
+        let mut hasher = Fnv(FNV_OFFSET);
+        self.feed(&mut hasher,&mut Vec::new());
+        hasher.0
+    }
+
+    fn feed<'s>(&'s self,h: &mut Fnv,enclosing: &mut Vec<&'s str>) {
+        match self {
+            Schema::Unit => h.byte(0),
+            Schema::Bool => h.byte(1),
+            Schema::U8 => h.byte(2),
+            Schema::U16 => h.byte(3),
+            Schema::U32 => h.byte(4),
+            Schema::U64 => h.byte(5),
+            Schema::U128 => h.byte(6),
+            Schema::I8 => h.byte(7),
+            Schema::I16 => h.byte(8),
+            Schema::I32 => h.byte(9),
+            Schema::I64 => h.byte(10),
+            Schema::I128 => h.byte(11),
+            Schema::F32 => h.byte(12),
+            Schema::F64 => h.byte(13),
+            Schema::Char => h.byte(14),
+            Schema::String => h.byte(15),
+            Schema::Varint { signed } => {
+                h.byte(16);
+                h.byte(*signed as u8);
+            },
+            Schema::Option(item) => {
+                h.byte(17);
+                item.feed(h,enclosing);
+            },
+            Schema::List(item) => {
+                h.byte(18);
+                item.feed(h,enclosing);
+            },
+            Schema::Array(item,count) => {
+                h.byte(19);
+                item.feed(h,enclosing);
+                h.number(*count as u64);
+            },
+            Schema::Map(key,value) => {
+                h.byte(20);
+                key.feed(h,enclosing);
+                value.feed(h,enclosing);
+            },
+            Schema::Tuple(items) => {
+                h.byte(21);
+                h.number(items.len() as u64);
+                for item in items {
+                    item.feed(h,enclosing);
+                }
+            },
+            Schema::Struct { name,fields } => {
+                h.byte(22);
+                enclosing.push(name);
+                h.number(fields.len() as u64);
+                for field in fields {
+                    field.schema.feed(h,enclosing);
+                }
+                enclosing.pop();
+            },
+            Schema::Enum { name,tag_size,big_endian,variants } => {
+                h.byte(if *big_endian { 30 } else { 23 });
+                enclosing.push(name);
+                h.number(*tag_size as u64);
+                h.number(variants.len() as u64);
+                for variant in variants {
+                    h.number(variant.tag as u64);
+                    h.number(variant.fields.len() as u64);
+                    for field in &variant.fields {
+                        field.schema.feed(h,enclosing);
+                    }
+                }
+                enclosing.pop();
+            },
+            Schema::Versioned(_,base,inner) => {
+                h.byte(24);
+                if let Schema::Struct { name,fields } = inner.as_ref() {
+                    enclosing.push(name);
+                    h.number(*base as u64);
+                    for field in fields.iter().take(*base) {
+                        field.schema.feed(h,enclosing);
+                    }
+                    enclosing.pop();
+                }
+            },
+
+            // how many structs or enums up, the names don't count
+            Schema::Recursive(name) => {
+                h.byte(25);
+                h.number(enclosing.iter().rev().position(|n| n == name).map(|i| i as u64).unwrap_or(u64::MAX));
+            },
+            Schema::Opaque(_) => h.byte(26),
+            Schema::BigEndian(inner) => {
+                h.byte(29);
+                inner.feed(h,enclosing);
+            },
+            Schema::Checked(inner) => {
+                h.byte(27);
+                inner.feed(h,enclosing);
+            },
+            Schema::Compressed(inner) => {
+                h.byte(28);
+                inner.feed(h,enclosing);
+            },
+        }
+    }
+}
+
+const FNV_OFFSET: u64 = 0xCBF2_9CE4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01B3;
+
+struct Fnv(u64);
+
+impl Fnv {
+
+    fn byte(&mut self,byte: u8) {
+        self.0 = (self.0 ^ byte as u64).wrapping_mul(FNV_PRIME);
+    }
+
+    fn number(&mut self,number: u64) {
+        for byte in number.to_le_bytes() {
+            self.byte(byte);
+        }
+    }
+}
+
+thread_local! {
+    // Types whose schema is being built, innermost last.
+    static BUILDING: RefCell<Vec<&'static str>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Schema of the struct or enum `T` called `name`, built by `build`.
+///
+/// If `build` needs the schema of `T` itself, as in a recursive type, it
+/// gets `Schema::Recursive(name)` instead of building it again forever.
+#[doc(hidden)]
+pub fn named_schema<T: ?Sized>(name: &str,build: impl FnOnce() -> Schema) -> Schema {
+    let type_name = std::any::type_name::<T>();
+    if BUILDING.with(|building| building.borrow().contains(&type_name)) {
+        return Schema::Recursive(name.to_string());
+    }
+    BUILDING.with(|building| building.borrow_mut().push(type_name));
+    let schema = build();
+    BUILDING.with(|building| building.borrow_mut().pop());
+    schema
+}
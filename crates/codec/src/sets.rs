@@ -0,0 +1,178 @@
+// Echidna - Codec
+
+use {
+    crate::*,
+    std::{
+        collections::{
+            HashSet,
+            BTreeSet,
+        },
+        hash::Hash,
+        io,
+    },
+};
+
+// Same as `Vec`: number of items as u32, followed by the items. `BTreeSet`
+// items are encoded in order.
+
+impl<T: Codec + Eq + Hash> Codec for HashSet<T> {
+    fn decode_with(buffer: &[u8],ctx: &mut DecodeContext) -> Result<(usize,Self),DecodeError> {
+        let (_,len) = u32::decode_with(buffer,ctx)?;
+        ctx.nested(|ctx| {
+            ctx.elements(len as usize,std::mem::size_of::<T>())?;
+            let mut r = HashSet::<T>::new();
+            let mut ofs = 4usize;
+            for i in 0..len {
+                let (l,item) = T::decode_with(&buffer[ofs..],ctx).map_err(|e| e.at(ofs).more(min_size_repeat(T::MIN_SIZE,(len - i - 1) as usize)))?;
+                if !r.insert(item) {
+                    return Err(DecodeError::DuplicateKey { offset: ofs, });
+                }
+                ofs += l;
+            }
+            Ok((ofs,r))
+        })
+    }
+}
+
+impl<T: Encode> Encode for HashSet<T> {
+    const MIN_SIZE: usize = 4;
+
+    fn schema() -> Schema {
+        Schema::List(Box::new(T::schema()))
+    }
+
+    fn encode(&self,buffer: &mut Vec<u8>) -> usize {
+        if let Some(size) = T::FIXED_SIZE {
+            buffer.reserve(4 + self.len() * size);
+        }
+        (self.len() as u32).encode(buffer);
+        let mut len = 4;
+        for item in self {
+            len += item.encode(buffer);
+        }
+        len
+    }
+
+    fn size(&self) -> usize {
+        if let Some(size) = T::FIXED_SIZE {
+            return 4 + self.len() * size;
+        }
+        let mut len = 4;
+        for item in self {
+            len += item.size();
+        }
+        len
+    }
+
+    fn encode_to(&self,sink: &mut Sink<'_>) -> io::Result<usize> {
+        let mut len = (self.len() as u32).encode_to(sink)?;
+        for item in self {
+            len += item.encode_to(sink)?;
+        }
+        Ok(len)
+    }
+}
+
+impl<T: Codec + Ord> Codec for BTreeSet<T> {
+    fn decode_with(buffer: &[u8],ctx: &mut DecodeContext) -> Result<(usize,Self),DecodeError> {
+        let (_,len) = u32::decode_with(buffer,ctx)?;
+        ctx.nested(|ctx| {
+            ctx.elements(len as usize,std::mem::size_of::<T>())?;
+            let mut r = BTreeSet::<T>::new();
+            let mut ofs = 4usize;
+            for i in 0..len {
+                let (l,item) = T::decode_with(&buffer[ofs..],ctx).map_err(|e| e.at(ofs).more(min_size_repeat(T::MIN_SIZE,(len - i - 1) as usize)))?;
+                if !r.insert(item) {
+                    return Err(DecodeError::DuplicateKey { offset: ofs, });
+                }
+                ofs += l;
+            }
+            Ok((ofs,r))
+        })
+    }
+}
+
+impl<T: Encode> Encode for BTreeSet<T> {
+    const MIN_SIZE: usize = 4;
+
+    fn schema() -> Schema {
+        Schema::List(Box::new(T::schema()))
+    }
+
+    fn encode(&self,buffer: &mut Vec<u8>) -> usize {
+        if let Some(size) = T::FIXED_SIZE {
+            buffer.reserve(4 + self.len() * size);
+        }
+        (self.len() as u32).encode(buffer);
+        let mut len = 4;
+        for item in self {
+            len += item.encode(buffer);
+        }
+        len
+    }
+
+    fn size(&self) -> usize {
+        if let Some(size) = T::FIXED_SIZE {
+            return 4 + self.len() * size;
+        }
+        let mut len = 4;
+        for item in self {
+            len += item.size();
+        }
+        len
+    }
+
+    fn encode_to(&self,sink: &mut Sink<'_>) -> io::Result<usize> {
+        let mut len = (self.len() as u32).encode_to(sink)?;
+        for item in self {
+            len += item.encode_to(sink)?;
+        }
+        Ok(len)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use {
+        crate::*,
+        std::collections::{
+            HashSet,
+            BTreeSet,
+        },
+    };
+
+    #[test]
+    fn test_hashset() {
+        let source: HashSet<u64> = vec![1,2,3].into_iter().collect();
+        let mut buffer = Vec::<u8>::new();
+        source.encode(&mut buffer);
+        if let Ok((_,target)) = HashSet::<u64>::decode(&buffer) {
+            assert_eq!(source,target)
+        }
+        else {
+            panic!("decode failed");
+        }
+    }
+
+    #[test]
+    fn test_btreeset() {
+        let source: BTreeSet<i16> = vec![3,-1,2].into_iter().collect();
+        let mut buffer = Vec::<u8>::new();
+        source.encode(&mut buffer);
+        assert_eq!(buffer,vec![3,0,0,0,0xFF,0xFF,2,0,3,0]);
+        if let Ok((_,target)) = BTreeSet::<i16>::decode(&buffer) {
+            assert_eq!(source,target)
+        }
+        else {
+            panic!("decode failed");
+        }
+    }
+
+    #[test]
+    fn test_set_duplicate() {
+        let buffer = vec![3,0,0,0,1,0,2,0,1,0];
+        assert_eq!(HashSet::<i16>::decode(&buffer),Err(DecodeError::DuplicateKey { offset: 8, }));
+        assert_eq!(BTreeSet::<i16>::decode(&buffer),Err(DecodeError::DuplicateKey { offset: 8, }));
+    }
+}
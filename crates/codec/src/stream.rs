@@ -0,0 +1,959 @@
+// Echidna - Codec
+
+use {
+    crate::*,
+    std::{
+        io,
+        cell::RefCell,
+    },
+};
+
+#[cfg(feature = "tokio")]
+use {
+    std::{
+        future::Future,
+        task::Poll,
+    },
+    tokio::io::{
+        AsyncRead,
+        AsyncReadExt,
+        AsyncWrite,
+        AsyncWriteExt,
+    },
+};
+
+/// Encoded bytes are passed on to the writer in pieces of about this size.
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// Destination of `Encode::encode_to`.
+///
+/// Collects encoded bytes and passes them on to the writer as soon as a
+/// piece of about 64KB is complete.
+pub struct Sink<'a> {
+    writer: &'a mut dyn io::Write,
+    buffer: &'a mut Vec<u8>,
+}
+
+impl<'a> Sink<'a> {
+
+    /// Buffer to encode into, call `commit` afterwards.
+    pub fn buffer(&mut self) -> &mut Vec<u8> {
+        self.buffer
+    }
+
+    /// Pass the buffer on to the writer if a piece is complete.
+    pub fn commit(&mut self) -> io::Result<()> {
+        if self.buffer.len() >= CHUNK_SIZE {
+            self.flush()
+        }
+        else {
+            Ok(())
+        }
+    }
+
+    /// Write raw bytes, big slices go to the writer directly.
+    pub fn write_bytes(&mut self,bytes: &[u8]) -> io::Result<()> {
+        if self.buffer.len() + bytes.len() < CHUNK_SIZE {
+            self.buffer.extend_from_slice(bytes);
+            Ok(())
+        }
+        else {
+            self.flush()?;
+            self.writer.write_all(bytes)
+        }
+    }
+
+    /// Pass everything in the buffer on to the writer.
+    pub fn flush(&mut self) -> io::Result<()> {
+        if !self.buffer.is_empty() {
+            self.writer.write_all(self.buffer)?;
+            self.buffer.clear();
+        }
+        Ok(())
+    }
+}
+
+/// Encodes values into an `io::Write`.
+pub struct Encoder<W> {
+    writer: W,
+    buffer: Vec<u8>,
+}
+
+impl<W: io::Write> Encoder<W> {
+
+    pub fn new(writer: W) -> Encoder<W> {
+        Encoder {
+            writer,
+            buffer: Vec::new(),
+        }
+    }
+
+    /// Encode `value` into the writer.
+    ///
+    /// Returns the number of bytes written.
+    pub fn encode<T: Encode>(&mut self,value: &T) -> io::Result<usize> {
+        let mut sink = Sink {
+            writer: &mut self.writer,
+            buffer: &mut self.buffer,
+        };
+        let len = value.encode_to(&mut sink)?;
+        sink.flush()?;
+        Ok(len)
+    }
+
+    /// Flush the writer.
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+
+    pub fn get_ref(&self) -> &W {
+        &self.writer
+    }
+
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.writer
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+thread_local! {
+    // Piece buffer of `encode_into`, kept so small values encode without
+    // allocating.
+    static SCRATCH: RefCell<Vec<u8>> = const { RefCell::new(Vec::new()) };
+}
+
+// Fixed buffer that takes a piece of bytes only if all of it fits, so a
+// value that doesn't fit overwrites as little as possible.
+struct Fixed<'a> {
+    buffer: &'a mut [u8],
+    len: usize,
+}
+
+impl io::Write for Fixed<'_> {
+    fn write(&mut self,bytes: &[u8]) -> io::Result<usize> {
+        if bytes.len() > self.buffer.len() - self.len {
+            return Err(io::ErrorKind::WriteZero.into());
+        }
+        self.buffer[self.len..self.len + bytes.len()].copy_from_slice(bytes);
+        self.len += bytes.len();
+        Ok(bytes.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+// Encode `value` into the start of `buffer` through a `Sink`, so containers
+// copy their items in pieces instead of building the whole value first.
+// `size` is only asked when the value doesn't fit, it can be as expensive
+// as encoding, like for `Compressed`.
+pub(crate) fn encode_into_slice<T: Encode + ?Sized>(value: &T,buffer: &mut [u8]) -> Result<usize,EncodeError> {
+    let available = buffer.len();
+    if let Some(needed) = T::FIXED_SIZE {
+        if needed > available {
+            return Err(EncodeError::BufferTooSmall { needed, available, });
+        }
+    }
+    let mut writer = Fixed { buffer, len: 0, };
+    let mut encode = |scratch: &mut Vec<u8>| {
+        scratch.clear();
+        let mut sink = Sink {
+            writer: &mut writer,
+            buffer: scratch,
+        };
+        value.encode_to(&mut sink)?;
+        sink.flush()?;
+        Ok::<(),io::Error>(())
+    };
+    let result = SCRATCH.with(|scratch| {
+        // a `with` module may encode into a fixed buffer of its own
+        match scratch.try_borrow_mut() {
+            Ok(mut scratch) => {
+                let result = encode(&mut scratch);
+                if scratch.capacity() > 2 * CHUNK_SIZE {
+                    *scratch = Vec::new();
+                }
+                result
+            },
+            Err(_) => encode(&mut Vec::new()),
+        }
+    });
+    // count what was actually written, the length `encode_to` returns can
+    // be wrong for hand-written types
+    match result {
+        Ok(()) => Ok(writer.len),
+        Err(_) => Err(EncodeError::BufferTooSmall { needed: value.size().max(available + 1), available, }),
+    }
+}
+
+// Outcome of decoding from the front of a partially filled buffer.
+enum Progress<T> {
+    Done(T),
+    Need(usize),
+}
+
+// Decode a `T` from the front of `buffer` and remove its bytes, or find out
+// how many bytes are needed before decoding can get further. A type that
+// runs out of bytes it already has can't get further by reading more, so
+// that is malformed instead.
+fn decode_front<T: Codec>(buffer: &mut Vec<u8>,limits: &DecodeLimits) -> io::Result<Progress<T>> {
+    match T::decode_with(buffer,&mut DecodeContext::new(*limits)) {
+        Ok((len,value)) => {
+            buffer.drain(..len);
+            Ok(Progress::Done(value))
+        },
+        Err(DecodeError::UnexpectedEof { offset,needed,.. }) if offset.saturating_add(needed) > buffer.len() => Ok(Progress::Need(offset.saturating_add(needed))),
+        Err(e) => Err(io::Error::new(io::ErrorKind::InvalidData,e)),
+    }
+}
+
+// Make room to read into when the first `end` bytes of `buffer` are filled.
+// The buffer grows by 64KB or doubles, whichever is more, so a value is
+// decoded again only a few times while it comes in, and a hostile length
+// prefix costs no more memory than the bytes that actually arrive.
+fn grow(buffer: &mut Vec<u8>,end: usize) {
+    if end == buffer.len() {
+        buffer.resize(end + CHUNK_SIZE.max(end),0);
+    }
+}
+
+// Outcome of `decode` when the reader ends.
+fn end_of_stream<T>(buffer: &[u8]) -> io::Result<Option<T>> {
+    if buffer.is_empty() {
+        Ok(None)
+    }
+    else {
+        Err(io::Error::new(io::ErrorKind::UnexpectedEof,"stream ended inside a value"))
+    }
+}
+
+/// Decodes values from an `io::Read`.
+///
+/// Reads only as much as needed to complete the next value; whatever is
+/// read beyond that is kept for the next call to `decode`.
+pub struct Decoder<R> {
+    reader: R,
+    buffer: Vec<u8>,
+    needed: usize,
+    limits: DecodeLimits,
+}
+
+impl<R: io::Read> Decoder<R> {
+
+    pub fn new(reader: R) -> Decoder<R> {
+        Decoder::with_limits(reader,DecodeLimits::default())
+    }
+
+    /// Decoder that checks every value against `limits`.
+    pub fn with_limits(reader: R,limits: DecodeLimits) -> Decoder<R> {
+        Decoder {
+            reader,
+            buffer: Vec::new(),
+            needed: 0,
+            limits,
+        }
+    }
+
+    /// Decode the next value.
+    ///
+    /// Returns `None` if the reader ends cleanly before the next value. A
+    /// malformed value is reported as `io::ErrorKind::InvalidData`, with the
+    /// `DecodeError` inside. After an error of the reader, like
+    /// `io::ErrorKind::WouldBlock`, call `decode` again for the same type to
+    /// carry on where it stopped.
+    pub fn decode<T: Codec>(&mut self) -> io::Result<Option<T>> {
+        loop {
+            if self.buffer.len() >= self.needed {
+                match decode_front(&mut self.buffer,&self.limits)? {
+                    Progress::Done(value) => {
+                        self.needed = 0;
+                        return Ok(Some(value));
+                    },
+                    Progress::Need(needed) => self.needed = needed,
+                }
+            }
+            if !self.fill(self.needed)? {
+                self.needed = 0;
+                return end_of_stream(&self.buffer);
+            }
+        }
+    }
+
+    // Read until the buffer holds at least `needed` bytes, returns false if
+    // the reader ended first. A blocking read can't tell if more bytes are
+    // on their way, so unlike `AsyncDecoder` this doesn't read ahead: the
+    // other side may be waiting for an answer to the value.
+    fn fill(&mut self,needed: usize) -> io::Result<bool> {
+        let mut end = self.buffer.len();
+        while end < needed {
+            grow(&mut self.buffer,end);
+            match self.reader.read(&mut self.buffer[end..]) {
+                Ok(0) => {
+                    self.buffer.truncate(end);
+                    return Ok(false);
+                },
+                Ok(n) => end += n,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => { },
+                Err(e) => {
+                    self.buffer.truncate(end);
+                    return Err(e);
+                },
+            }
+        }
+        self.buffer.truncate(end);
+        Ok(true)
+    }
+
+    pub fn get_ref(&self) -> &R {
+        &self.reader
+    }
+
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.reader
+    }
+
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+}
+
+// Writer of `AsyncEncoder` for values that fit in one piece. A value that
+// has to pass a piece on is encoded again on a blocking thread.
+#[cfg(feature = "tokio")]
+struct Refuse;
+
+#[cfg(feature = "tokio")]
+impl io::Write for Refuse {
+    fn write(&mut self,_: &[u8]) -> io::Result<usize> {
+        Err(io::ErrorKind::WouldBlock.into())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+// Writer of the blocking thread of `AsyncEncoder`, sends pieces of at most
+// 64KB to the task that writes them. The channel holds only a few, so the
+// thread waits when the writer is slow, and stops when the task is gone.
+#[cfg(feature = "tokio")]
+struct Pieces(tokio::sync::mpsc::Sender<Vec<u8>>);
+
+#[cfg(feature = "tokio")]
+impl io::Write for Pieces {
+    fn write(&mut self,bytes: &[u8]) -> io::Result<usize> {
+        let len = bytes.len().min(CHUNK_SIZE);
+        match self.0.blocking_send(bytes[..len].to_vec()) {
+            Ok(()) => Ok(len),
+            Err(_) => Err(io::ErrorKind::BrokenPipe.into()),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Encodes values into a tokio `AsyncWrite`.
+///
+/// Streams like `Encoder`: a value is written in pieces of about 64KB while
+/// it is encoded, so it never ends up in memory whole. `Encode::encode_to`
+/// is synchronous, so a value bigger than one piece is encoded on a thread
+/// of `tokio::task::spawn_blocking`, which waits while the writer takes the
+/// pieces. Smaller values are encoded and written right away.
+#[cfg(feature = "tokio")]
+pub struct AsyncEncoder<W> {
+    writer: W,
+    buffer: Vec<u8>,
+}
+
+#[cfg(feature = "tokio")]
+impl<W: AsyncWrite + Unpin> AsyncEncoder<W> {
+
+    pub fn new(writer: W) -> AsyncEncoder<W> {
+        AsyncEncoder {
+            writer,
+            buffer: Vec::new(),
+        }
+    }
+
+    /// Encode `value` into the writer.
+    ///
+    /// Takes the value, because a big one goes to another thread. Pass an
+    /// `Arc` to keep it, it encodes the same as the value inside. Returns
+    /// the number of bytes written.
+    pub async fn encode<T: Encode + Send + 'static>(&mut self,value: T) -> io::Result<usize> {
+        self.buffer.clear();
+        let result = value.encode_to(&mut Sink {
+            writer: &mut Refuse,
+            buffer: &mut self.buffer,
+        });
+
+        // don't hold on to the memory of a value without `encode_to`
+        if self.buffer.capacity() > 2 * CHUNK_SIZE {
+            self.buffer = Vec::new();
+        }
+        if let Ok(len) = result {
+            self.writer.write_all(&self.buffer).await?;
+            return Ok(len);
+        }
+
+        let (sender,mut receiver) = tokio::sync::mpsc::channel::<Vec<u8>>(2);
+        let encoder = tokio::task::spawn_blocking(move || Encoder::new(Pieces(sender)).encode(&value));
+        while let Some(piece) = receiver.recv().await {
+            self.writer.write_all(&piece).await?;
+        }
+        match encoder.await {
+            Ok(result) => result,
+            Err(e) if e.is_panic() => std::panic::resume_unwind(e.into_panic()),
+            Err(e) => Err(io::Error::other(e)),
+        }
+    }
+
+    /// Flush the writer.
+    pub async fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush().await
+    }
+
+    pub fn get_ref(&self) -> &W {
+        &self.writer
+    }
+
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.writer
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+/// Decodes values from a tokio `AsyncRead`.
+///
+/// Same as `Decoder`, but waits for more bytes asynchronously.
+#[cfg(feature = "tokio")]
+pub struct AsyncDecoder<R> {
+    reader: R,
+    buffer: Vec<u8>,
+    needed: usize,
+    attempted: usize,
+    limits: DecodeLimits,
+}
+
+#[cfg(feature = "tokio")]
+impl<R: AsyncRead + Unpin> AsyncDecoder<R> {
+
+    pub fn new(reader: R) -> AsyncDecoder<R> {
+        AsyncDecoder::with_limits(reader,DecodeLimits::default())
+    }
+
+    /// Decoder that checks every value against `limits`.
+    pub fn with_limits(reader: R,limits: DecodeLimits) -> AsyncDecoder<R> {
+        AsyncDecoder {
+            reader,
+            buffer: Vec::new(),
+            needed: 0,
+            attempted: 0,
+            limits,
+        }
+    }
+
+    /// Decode the next value.
+    ///
+    /// Returns `None` if the reader ends cleanly before the next value.
+    /// Cancelling `decode`, for instance in `tokio::select!`, loses nothing,
+    /// the next call for the same type carries on where it stopped.
+    pub async fn decode<T: Codec>(&mut self) -> io::Result<Option<T>> {
+        loop {
+            if self.buffer.len() >= self.needed {
+                match decode_front(&mut self.buffer,&self.limits)? {
+                    Progress::Done(value) => {
+                        self.needed = 0;
+                        self.attempted = 0;
+                        return Ok(Some(value));
+                    },
+                    Progress::Need(needed) => {
+                        self.needed = needed;
+                        self.attempted = self.buffer.len();
+                    },
+                }
+            }
+            if !self.fill(self.needed).await? {
+                self.needed = 0;
+                self.attempted = 0;
+                return end_of_stream(&self.buffer);
+            }
+        }
+    }
+
+    // Same as `Decoder::fill`, but reads into the spare capacity of the
+    // buffer, so it only ever holds bytes that were read, also when the
+    // read is cancelled.
+    //
+    // After that it keeps reading the bytes that are there already, until
+    // the buffer holds twice as many as at the last attempt to decode. So a
+    // big value that comes in faster than it decodes is decoded a few times,
+    // not after every read. Bytes that are not there yet are not waited for.
+    async fn fill(&mut self,needed: usize) -> io::Result<bool> {
+        while self.buffer.len() < needed {
+            self.reserve();
+            match self.reader.read_buf(&mut self.buffer).await {
+                Ok(0) => return Ok(false),
+                Ok(_) => { },
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => { },
+                Err(e) => return Err(e),
+            }
+        }
+        while self.buffer.len() < self.attempted.saturating_mul(2) {
+            self.reserve();
+            let mut read = std::pin::pin!(self.reader.read_buf(&mut self.buffer));
+            let ready = std::future::poll_fn(|cx| match read.as_mut().poll(cx) {
+                Poll::Ready(result) => Poll::Ready(Some(result)),
+                Poll::Pending => Poll::Ready(None),
+            }).await;
+            match ready {
+                Some(Ok(0)) | None => break,
+                Some(Ok(_)) => { },
+                Some(Err(e)) if e.kind() == io::ErrorKind::Interrupted => { },
+                Some(Err(e)) => return Err(e),
+            }
+        }
+        Ok(true)
+    }
+
+    fn reserve(&mut self) {
+        let end = self.buffer.len();
+        if end == self.buffer.capacity() {
+            self.buffer.reserve(CHUNK_SIZE.max(end));
+        }
+    }
+
+    pub fn get_ref(&self) -> &R {
+        &self.reader
+    }
+
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.reader
+    }
+
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use {
+        crate::*,
+        std::io,
+    };
+
+    // Reader that hands out at most `step` bytes per read.
+    struct Trickle {
+        data: Vec<u8>,
+        pos: usize,
+        step: usize,
+        reads: usize,
+    }
+
+    impl io::Read for Trickle {
+        fn read(&mut self,buf: &mut [u8]) -> io::Result<usize> {
+            self.reads += 1;
+            let n = self.step.min(buf.len()).min(self.data.len() - self.pos);
+            buf[..n].copy_from_slice(&self.data[self.pos..self.pos + n]);
+            self.pos += n;
+            Ok(n)
+        }
+    }
+
+    // Same as the `io::Read`, the bytes are always there.
+    #[cfg(feature = "tokio")]
+    impl tokio::io::AsyncRead for Trickle {
+        fn poll_read(self: std::pin::Pin<&mut Self>,_: &mut std::task::Context<'_>,buf: &mut tokio::io::ReadBuf<'_>) -> std::task::Poll<io::Result<()>> {
+            let this = self.get_mut();
+            let n = io::Read::read(this,buf.initialize_unfilled())?;
+            buf.advance(n);
+            std::task::Poll::Ready(Ok(()))
+        }
+    }
+
+    // Writer that counts the writes.
+    #[derive(Default)]
+    struct Counter {
+        data: Vec<u8>,
+        writes: usize,
+        #[cfg(feature = "tokio")]
+        biggest: usize,
+    }
+
+    impl io::Write for Counter {
+        fn write(&mut self,buf: &[u8]) -> io::Result<usize> {
+            self.data.extend_from_slice(buf);
+            self.writes += 1;
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    // Same as the `io::Write`, also remembers the biggest write.
+    #[cfg(feature = "tokio")]
+    impl tokio::io::AsyncWrite for Counter {
+        fn poll_write(self: std::pin::Pin<&mut Self>,_: &mut std::task::Context<'_>,buf: &[u8]) -> std::task::Poll<io::Result<usize>> {
+            let this = self.get_mut();
+            this.biggest = this.biggest.max(buf.len());
+            std::task::Poll::Ready(io::Write::write(this,buf))
+        }
+
+        fn poll_flush(self: std::pin::Pin<&mut Self>,_: &mut std::task::Context<'_>) -> std::task::Poll<io::Result<()>> {
+            std::task::Poll::Ready(Ok(()))
+        }
+
+        fn poll_shutdown(self: std::pin::Pin<&mut Self>,_: &mut std::task::Context<'_>) -> std::task::Poll<io::Result<()>> {
+            std::task::Poll::Ready(Ok(()))
+        }
+    }
+
+    #[test]
+    fn test_stream() {
+        let first: Vec<String> = vec!["Hello".to_string(),"World".to_string()];
+        let second: u32 = 0x12345678;
+        let mut buffer = Vec::<u8>::new();
+        first.encode(&mut buffer);
+        let first_len = buffer.len();
+        second.encode(&mut buffer);
+        let mut encoder = Encoder::new(Vec::<u8>::new());
+        assert_eq!(encoder.encode(&first).unwrap(),first_len);
+        assert_eq!(encoder.encode(&second).unwrap(),4);
+        let data = encoder.into_inner();
+        assert_eq!(data,buffer);
+
+        let mut decoder = Decoder::new(Trickle { data, pos: 0, step: 3, reads: 0, });
+        assert_eq!(decoder.decode::<Vec<String>>().unwrap(),Some(first));
+        assert_eq!(decoder.decode::<u32>().unwrap(),Some(second));
+        assert_eq!(decoder.decode::<u32>().unwrap(),None);
+    }
+
+    #[test]
+    fn test_stream_large() {
+        let source: Vec<u8> = (0..300000).map(|i| i as u8).collect();
+        let mut encoder = Encoder::new(Counter::default());
+        encoder.encode(&vec![source.clone(),source.clone()]).unwrap();
+        let counter = encoder.into_inner();
+        assert!(counter.writes > 2);
+        let mut decoder = Decoder::new(Trickle { data: counter.data, pos: 0, step: 10000, reads: 0, });
+        assert_eq!(decoder.decode::<Vec<Vec<u8>>>().unwrap(),Some(vec![source.clone(),source]));
+
+        // a vector of numbers asks for all its bytes at once, they are read
+        // into a buffer that doubles
+        let source: Vec<u32> = (0..1000000).collect();
+        let mut data = Vec::<u8>::new();
+        source.encode(&mut data);
+        let mut decoder = Decoder::new(Trickle { data, pos: 0, step: usize::MAX, reads: 0, });
+        assert_eq!(decoder.decode::<Vec<u32>>().unwrap(),Some(source));
+        assert!(decoder.get_ref().reads < 10);
+
+        // many small items don't make the buffer grow in small steps
+        let source: Vec<String> = (0..100000).map(|i| i.to_string()).collect();
+        let mut data = Vec::<u8>::new();
+        source.encode(&mut data);
+        let mut decoder = Decoder::new(Trickle { data, pos: 0, step: usize::MAX, reads: 0, });
+        assert_eq!(decoder.decode::<Vec<String>>().unwrap(),Some(source));
+        assert!(decoder.get_ref().reads < 8);
+    }
+
+    #[test]
+    fn test_stream_invalid() {
+        let mut decoder = Decoder::new(Trickle { data: vec![2,0,0,0,1,0,2], pos: 0, step: 1, reads: 0, });
+        let error = decoder.decode::<Vec<u16>>().unwrap_err();
+        assert_eq!(error.kind(),io::ErrorKind::UnexpectedEof);
+        let mut decoder = Decoder::new(Trickle { data: vec![1,0,0,0,2], pos: 0, step: 1, reads: 0, });
+        let error = decoder.decode::<Vec<bool>>().unwrap_err();
+        assert_eq!(error.kind(),io::ErrorKind::InvalidData);
+        let mut decoder = Decoder::with_limits(Trickle { data: vec![0xFF,0xFF,0xFF,0xFF], pos: 0, step: 1, reads: 0, },DecodeLimits { max_alloc: 1024, max_elements: 16, max_depth: 4, });
+        let error = decoder.decode::<Vec<u8>>().unwrap_err();
+        assert_eq!(error.kind(),io::ErrorKind::InvalidData);
+
+        // a length prefix alone doesn't allocate what it promises
+        let mut decoder = Decoder::new(Trickle { data: vec![0,0,0x80,0,1,2,3], pos: 0, step: usize::MAX, reads: 0, });
+        let error = decoder.decode::<Vec<u8>>().unwrap_err();
+        assert_eq!(error.kind(),io::ErrorKind::UnexpectedEof);
+        assert!(decoder.buffer.capacity() <= 2 * super::CHUNK_SIZE);
+    }
+
+    // Always says it needs one byte more than it got.
+    #[derive(Debug)]
+    struct Stuck;
+
+    impl Encode for Stuck {
+        fn encode(&self,_: &mut Vec<u8>) -> usize {
+            0
+        }
+
+        fn size(&self) -> usize {
+            0
+        }
+    }
+
+    impl Codec for Stuck {
+        fn decode_with(buffer: &[u8],_: &mut DecodeContext) -> Result<(usize,Self),DecodeError> {
+            Err(DecodeError::UnexpectedEof { offset: 0, needed: 1, available: buffer.len().saturating_sub(1), })
+        }
+    }
+
+    #[test]
+    fn test_stream_stuck() {
+        let mut decoder = Decoder::new(Trickle { data: vec![1,2,3,4], pos: 0, step: 1, reads: 0, });
+        let error = decoder.decode::<Stuck>().unwrap_err();
+        assert_eq!(error.kind(),io::ErrorKind::InvalidData);
+        assert_eq!(decoder.get_ref().reads,1);
+    }
+
+    // Reader that can't go on after every read, like a non-blocking socket.
+    struct Blocking(Trickle,bool);
+
+    impl io::Read for Blocking {
+        fn read(&mut self,buf: &mut [u8]) -> io::Result<usize> {
+            self.1 = !self.1;
+            if self.1 {
+                self.0.read(buf)
+            }
+            else {
+                Err(io::Error::from(io::ErrorKind::WouldBlock))
+            }
+        }
+    }
+
+    thread_local! {
+        static ATTEMPTS: std::cell::Cell<usize> = const { std::cell::Cell::new(0) };
+    }
+
+    // Counts how often it is decoded.
+    #[derive(Debug,PartialEq)]
+    struct Counted<T>(T);
+
+    impl<T: Encode> Encode for Counted<T> {
+        const MIN_SIZE: usize = T::MIN_SIZE;
+
+        fn encode(&self,buffer: &mut Vec<u8>) -> usize {
+            self.0.encode(buffer)
+        }
+
+        fn size(&self) -> usize {
+            self.0.size()
+        }
+    }
+
+    impl<T: Codec> Codec for Counted<T> {
+        fn decode_with(buffer: &[u8],ctx: &mut DecodeContext) -> Result<(usize,Self),DecodeError> {
+            ATTEMPTS.with(|attempts| attempts.set(attempts.get() + 1));
+            let (len,value) = T::decode_with(buffer,ctx)?;
+            Ok((len,Counted(value)))
+        }
+    }
+
+    fn attempts() -> usize {
+        ATTEMPTS.with(|attempts| attempts.replace(0))
+    }
+
+    #[test]
+    fn test_stream_would_block() {
+        let source = Counted::<Vec<u8>>((0..10000).map(|i| i as u8).collect());
+        let mut data = Vec::<u8>::new();
+        source.encode(&mut data);
+        let mut decoder = Decoder::new(Blocking(Trickle { data, pos: 0, step: 10, reads: 0, },false));
+        let target = loop {
+            match decoder.decode::<Counted<Vec<u8>>>() {
+                Ok(target) => break target,
+                Err(e) => assert_eq!(e.kind(),io::ErrorKind::WouldBlock),
+            }
+        };
+        assert_eq!(target,Some(source));
+
+        // before the length, before the bytes and with all bytes, not after
+        // every read
+        assert_eq!(attempts(),3);
+    }
+
+    #[test]
+    fn test_stream_mtu() {
+        // the items still to come need at least their length, so the
+        // decoder waits for most of the bytes instead of trying again
+        // after every read
+        let source = Counted::<Vec<String>>((0..200000).map(|i| i.to_string()).collect());
+        let mut data = Vec::<u8>::new();
+        source.encode(&mut data);
+        let mut decoder = Decoder::new(Trickle { data, pos: 0, step: 1460, reads: 0, });
+        attempts();
+        assert_eq!(decoder.decode::<Counted<Vec<String>>>().unwrap(),Some(source));
+        assert!(decoder.get_ref().reads > 1000);
+        assert!(attempts() < 20);
+    }
+
+    #[cfg(feature = "tokio")]
+    #[test]
+    fn test_async_stream() {
+        let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+        runtime.block_on(async {
+            let source: Vec<String> = vec!["Hello".to_string(),"World".to_string()];
+            let (client,server) = tokio::io::duplex(7);
+            let sender = async move {
+                let mut encoder = AsyncEncoder::new(client);
+                encoder.encode(source.clone()).await.unwrap();
+                encoder.encode(source).await.unwrap();
+            };
+            let receiver = async move {
+                let mut decoder = AsyncDecoder::new(server);
+                let mut count = 0;
+                while let Some(target) = decoder.decode::<Vec<String>>().await.unwrap() {
+                    assert_eq!(target,vec!["Hello".to_string(),"World".to_string()]);
+                    count += 1;
+                }
+                count
+            };
+            let sender = tokio::spawn(sender);
+            assert_eq!(receiver.await,2);
+            sender.await.unwrap();
+        });
+    }
+
+    #[cfg(feature = "tokio")]
+    #[test]
+    fn test_async_stream_large() {
+        let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+        runtime.block_on(async {
+            let bytes: Vec<u8> = (0..300000).map(|i| i as u8).collect();
+            let names: Vec<String> = (0..20000).map(|i| i.to_string()).collect();
+            let source = std::sync::Arc::new((bytes,names));
+            let mut expected = Vec::<u8>::new();
+            source.encode(&mut expected);
+            let mut encoder = AsyncEncoder::new(Counter::default());
+            assert_eq!(encoder.encode(source.clone()).await.unwrap(),expected.len());
+            assert_eq!(encoder.encode(7u32).await.unwrap(),4);
+
+            // written in pieces, none of them bigger than about 64KB
+            assert!(encoder.buffer.capacity() <= 2 * super::CHUNK_SIZE);
+            let counter = encoder.into_inner();
+            assert!(counter.writes > 4);
+            assert!(counter.biggest <= super::CHUNK_SIZE);
+            let data = counter.data;
+            assert_eq!(&data[..expected.len()],&expected[..]);
+            let source = std::sync::Arc::try_unwrap(source).unwrap();
+            let mut decoder = AsyncDecoder::new(&data[..]);
+            assert_eq!(decoder.decode::<(Vec<u8>,Vec<String>)>().await.unwrap(),Some(source));
+            assert_eq!(decoder.decode::<u32>().await.unwrap(),Some(7));
+        });
+    }
+
+    #[cfg(feature = "tokio")]
+    #[test]
+    fn test_async_stream_mtu() {
+        // bytes that are there already are read up to twice as many as
+        // at the last attempt
+        let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+        runtime.block_on(async {
+            let source = Counted::<Vec<String>>((0..200000).map(|i| i.to_string()).collect());
+            let mut data = Vec::<u8>::new();
+            source.encode(&mut data);
+            let mut decoder = AsyncDecoder::new(Trickle { data, pos: 0, step: 1460, reads: 0, });
+            attempts();
+            assert_eq!(decoder.decode::<Counted<Vec<String>>>().await.unwrap(),Some(source));
+            assert!(attempts() < 8);
+        });
+    }
+
+    #[cfg(feature = "tokio")]
+    #[test]
+    fn test_async_stream_cancel() {
+        use {
+            std::future::Future,
+            tokio::io::AsyncWriteExt,
+        };
+        let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+        runtime.block_on(async {
+            let source: Vec<u8> = (0..1000).map(|i| i as u8).collect();
+            let mut data = Vec::<u8>::new();
+            source.encode(&mut data);
+            let (mut client,server) = tokio::io::duplex(4096);
+            let mut decoder = AsyncDecoder::new(server);
+            client.write_all(&data[..500]).await.unwrap();
+
+            // poll once, it reads what there is and waits for the rest
+            let mut decode = Box::pin(decoder.decode::<Vec<u8>>());
+            std::future::poll_fn(|cx| {
+                assert!(decode.as_mut().poll(cx).is_pending());
+                std::task::Poll::Ready(())
+            }).await;
+            drop(decode);
+            assert_eq!(decoder.buffer.len(),500);
+
+            client.write_all(&data[500..]).await.unwrap();
+            assert_eq!(decoder.decode::<Vec<u8>>().await.unwrap(),Some(source));
+        });
+    }
+
+    #[test]
+    fn test_encode_into() {
+        let source: Vec<String> = vec!["Hello".to_string(),"World".to_string()];
+        let mut expected = Vec::<u8>::new();
+        source.encode(&mut expected);
+        let mut frame = [0xFFu8; 64];
+        assert_eq!(source.encode_into(&mut frame),Ok(expected.len()));
+        assert_eq!(&frame[..expected.len()],&expected[..]);
+        assert!(frame[expected.len()..].iter().all(|b| *b == 0xFF));
+        let mut small = [0u8; 8];
+        assert_eq!(source.encode_into(&mut small),Err(EncodeError::BufferTooSmall { needed: expected.len(), available: 8, }));
+        assert_eq!(small,[0u8; 8]);
+    }
+
+    // Encodes the bytes in `0`, but says its size is `1`.
+    struct Liar(Vec<u8>,usize);
+
+    impl Encode for Liar {
+        fn encode(&self,buffer: &mut Vec<u8>) -> usize {
+            buffer.extend_from_slice(&self.0);
+            self.0.len()
+        }
+
+        fn size(&self) -> usize {
+            self.1
+        }
+    }
+
+    #[test]
+    fn test_encode_into_wrong_size() {
+        // `size` is not asked when the value fits
+        let mut frame = [0xFFu8; 4];
+        assert_eq!(Liar(vec![1,2],100).encode_into(&mut frame),Ok(2));
+        assert_eq!(frame,[1,2,0xFF,0xFF]);
+        assert_eq!(Liar(vec![1,2,3],1).encode_into(&mut frame),Ok(3));
+        assert_eq!(frame,[1,2,3,0xFF]);
+
+        // and when it doesn't, the error needs more than there is anyway
+        assert_eq!(Liar(vec![4,5,6,7,8,9],1).encode_into(&mut frame),Err(EncodeError::BufferTooSmall { needed: 5, available: 4, }));
+        assert_eq!(frame,[1,2,3,0xFF]);
+
+        // a big value is written in pieces, the first ones that fit stay
+        let mut frame = vec![0u8; 100000];
+        let error = vec![vec![7u8; 1000]; 200].encode_into(&mut frame).unwrap_err();
+        assert_eq!(error,EncodeError::BufferTooSmall { needed: 4 + 200 * 1004, available: 100000, });
+        assert_eq!(&frame[..6],&[200,0,0,0,0xE8,0x03]);
+    }
+
+    #[test]
+    fn test_encode_into_big() {
+        let source: Vec<u32> = (0..100000).collect();
+        let mut expected = Vec::<u8>::new();
+        source.encode(&mut expected);
+        let mut frame = vec![0u8; expected.len()];
+        assert_eq!(source.encode_into(&mut frame),Ok(expected.len()));
+        assert_eq!(frame,expected);
+    }
+}
@@ -1,47 +1,86 @@
 // Echidna - Codec
 
-use crate::*;
+use {
+    crate::*,
+    std::io,
+};
 
 impl Codec for String {
-    fn decode(buffer: &[u8]) -> Option<(usize,Self)> {
-        if let Some((_,len)) = u32::decode(buffer) {
-            let a = String::from_utf8_lossy(&buffer[4..4 + len as usize]);
-            Some((4 + len as usize,a.to_string()))
-        }
-        else {
-            None
+    fn decode_with(buffer: &[u8],ctx: &mut DecodeContext) -> Result<(usize,Self),DecodeError> {
+        let (_,len) = u32::decode_with(buffer,ctx)?;
+        let end = 4usize.checked_add(len as usize).ok_or(DecodeError::LengthOverflow { offset: 0, length: len as u64, })?;
+        ctx.alloc(len as usize)?;
+        ensure(buffer,end)?;
+        match std::str::from_utf8(&buffer[4..end]) {
+            Ok(a) => Ok((end,a.to_string())),
+            Err(e) => Err(DecodeError::InvalidUtf8 { offset: 4 + e.valid_up_to(), }),
         }
     }
+}
+
+impl Encode for String {
+    const MIN_SIZE: usize = 4;
+
+    fn schema() -> Schema {
+        Schema::String
+    }
 
     fn encode(&self,buffer: &mut Vec<u8>) -> usize {
-        let slice = self.as_bytes();
-        let len = slice.len() as u32;
-        len.encode(buffer);
-        buffer.extend_from_slice(slice);
-        len as usize
+        encode_bytes(self.as_bytes(),buffer)
     }
 
     fn size(&self) -> usize {
-        let slice = self.as_bytes();
-        slice.len()
+        4 + self.len()
+    }
+
+    fn encode_to(&self,sink: &mut Sink<'_>) -> io::Result<usize> {
+        encode_bytes_to(self.as_bytes(),sink)
+    }
+}
+
+// A `str` encodes the same as a `String`.
+impl Encode for str {
+    const MIN_SIZE: usize = 4;
+
+    fn schema() -> Schema {
+        Schema::String
+    }
+
+    fn encode(&self,buffer: &mut Vec<u8>) -> usize {
+        encode_bytes(self.as_bytes(),buffer)
+    }
+
+    fn size(&self) -> usize {
+        4 + self.len()
+    }
+
+    fn encode_to(&self,sink: &mut Sink<'_>) -> io::Result<usize> {
+        encode_bytes_to(self.as_bytes(),sink)
     }
 }
 
 #[cfg(test)]
 mod tests {
 
-    use crate::Codec;
+    use crate::*;
 
     #[test]
     fn test_string() {
         let source = "Hello, World!".to_string();
         let mut buffer = Vec::<u8>::new();
         source.encode(&mut buffer);
-        if let Some((_,target)) = String::decode(&buffer) {
+        if let Ok((_,target)) = String::decode(&buffer) {
             assert_eq!(source,target)
         }
         else {
-            assert!(false)
+            panic!("decode failed");
         }
     }
+
+    #[test]
+    fn test_string_invalid() {
+        let buffer = vec![3,0,0,0,b'a',0xFF,b'b'];
+        assert_eq!(String::decode(&buffer),Err(DecodeError::InvalidUtf8 { offset: 5, }));
+        assert_eq!(String::decode(&buffer[0..6]),Err(DecodeError::UnexpectedEof { offset: 0, needed: 7, available: 6, }));
+    }
 }
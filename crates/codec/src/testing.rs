@@ -0,0 +1,831 @@
+// Echidna - Codec
+
+//! Conformance checks for `Encode` and `Codec` implementations.
+//!
+//! Every implementation, hand-written or derived, has to keep three
+//! promises: `size` equals the number of bytes `encode` writes, `encode`
+//! returns that same number, and `decode` consumes exactly those bytes and
+//! gives back an equal value. `assert_roundtrip` checks these for one value,
+//! `assert_random_roundtrips` for many values made by `Random`.
+//!
+//! `fuzz_decode` and its variants go the other way, from arbitrary bytes to
+//! a value and back, for the fuzz targets. `fuzz_value` does the same for
+//! `Value::decode_with`, and `fuzz_from_text` for `from_text`.
+
+use {
+    crate::*,
+    std::{
+        alloc::{
+            GlobalAlloc,
+            Layout,
+            System,
+        },
+        collections::{
+            BTreeMap,
+            BTreeSet,
+            HashMap,
+            HashSet,
+            VecDeque,
+        },
+        fmt::Debug,
+        hash::Hash,
+        net::{
+            IpAddr,
+            Ipv4Addr,
+            Ipv6Addr,
+            SocketAddr,
+            SocketAddrV4,
+            SocketAddrV6,
+        },
+        rc::Rc,
+        sync::{
+            atomic::{
+                AtomicUsize,
+                Ordering,
+            },
+            Arc,
+        },
+        time::{
+            Duration,
+            SystemTime,
+            UNIX_EPOCH,
+        },
+    },
+};
+
+// Collections made by `Random` hold fewer items than this.
+const MAX_RANDOM_LEN: usize = 8;
+
+/// Limits the fuzz targets decode with.
+pub const FUZZ_LIMITS: DecodeLimits = DecodeLimits {
+    max_alloc: 1024 * 1024,
+    max_elements: 64 * 1024,
+    max_depth: 32,
+};
+
+// Factor by which the heap used while decoding may exceed
+// `FUZZ_LIMITS.max_alloc`, for growing maps and other overhead.
+const FUZZ_HEAP_FACTOR: usize = 4;
+
+/// Check that `value` keeps the `size`/`encode`/`decode` contract.
+///
+/// Also checks that encoding through an `Encoder` gives the same bytes, that
+/// decoding stops at the end of the value when more bytes follow, and that
+/// `Value::decode_with` reads the same bytes by `T::schema()` and that the
+/// value survives `to_text` and `from_text`, unless part of the schema is
+/// opaque. Panics with a description of the first broken promise.
+pub fn assert_roundtrip<T: Codec + PartialEq + Debug>(value: &T) {
+
+    // encode behind some bytes that are already in the buffer
+    let mut buffer = vec![0xA5; 3];
+    let len = value.encode(&mut buffer);
+    let encoded = &buffer[3..];
+    assert_eq!(len,encoded.len(),"encode of {:?} returns {} but writes {} bytes",value,len,encoded.len());
+    assert_eq!(value.size(),len,"size of {:?} is {} but encode writes {} bytes",value,value.size(),len);
+
+    let mut encoder = Encoder::new(Vec::<u8>::new());
+    let streamed = encoder.encode(value).expect("encoding into a vector cannot fail");
+    assert_eq!(streamed,len,"encode_to of {:?} returns {} but encode {}",value,streamed,len);
+    assert_eq!(&encoder.into_inner()[..],encoded,"encode_to of {:?} writes different bytes than encode",value);
+
+    match T::decode(encoded) {
+        Ok((l,target)) => {
+            assert_eq!(l,len,"decode of {:?} consumes {} of {} bytes",value,l,len);
+            assert_eq!(&target,value,"decode gives a different value");
+        },
+        Err(e) => panic!("decode of {:?} failed: {}",value,e),
+    }
+
+    let mut padded = encoded.to_vec();
+    padded.extend_from_slice(&[0xA5; 3]);
+    match T::decode(&padded) {
+        Ok((l,_)) => assert_eq!(l,len,"decode of {:?} with bytes after it consumes {} of {} bytes",value,l,len),
+        Err(e) => panic!("decode of {:?} with bytes after it failed: {}",value,e),
+    }
+
+    match Value::decode_with(&T::schema(),encoded) {
+        Ok((l,_)) => assert_eq!(l,len,"schema of {:?} describes {} of {} bytes",value,l,len),
+        Err(DecodeError::Opaque { .. }) => { },
+        Err(e) => panic!("dynamic decode of {:?} failed: {}",value,e),
+    }
+
+    match to_text(value) {
+        Ok(text) => match from_text::<T>(&text) {
+            Ok(target) => assert_eq!(&target,value,"text {} reads back as {:?}",text,target),
+            Err(e) => panic!("text {} of {:?} does not read back: {}",text,value,e),
+        },
+        Err(TextError::Opaque) => { },
+        Err(e) => panic!("to_text of {:?} failed: {}",value,e),
+    }
+}
+
+/// Run `assert_roundtrip` on `count` random values of `T`.
+///
+/// The values always come from the same seed, so a failure can be
+/// reproduced.
+pub fn assert_random_roundtrips<T: Random + Codec + PartialEq + Debug>(count: usize) {
+    let mut rng = Rng::new(0x2545_F491_4F6C_DD1D);
+    for _ in 0..count {
+        assert_roundtrip(&T::random(&mut rng));
+    }
+}
+
+/// Decode arbitrary `data` as `T` and check what comes out.
+///
+/// Decoding must not panic, and must not use more heap than `FUZZ_LIMITS`
+/// allow (only checked when `PeakAllocator` is installed). When decoding
+/// succeeds, encoding the value again has to give exactly the bytes that
+/// were decoded.
+pub fn fuzz_decode<'de,T: BorrowDecode<'de> + Debug>(data: &'de [u8]) {
+    let mut ctx = DecodeContext::new(FUZZ_LIMITS);
+    let (result,heap) = measure_peak(|| T::borrow_decode_with(data,&mut ctx));
+    assert!(heap <= FUZZ_HEAP_FACTOR * FUZZ_LIMITS.max_alloc,"decoding {} bytes uses {} bytes of heap",data.len(),heap);
+    if let Ok((len,value)) = result {
+        assert!(len <= data.len(),"decode consumes {} of {} bytes",len,data.len());
+        let mut buffer = Vec::<u8>::new();
+        let encoded = value.encode(&mut buffer);
+        assert_eq!(&buffer[..],&data[..len],"{:?} encodes into different bytes than it was decoded from",value);
+        assert_eq!(encoded,len,"encode of {:?} returns {} but writes {} bytes",value,encoded,len);
+        assert_eq!(value.size(),len,"size of {:?} is {} but encode writes {} bytes",value,value.size(),len);
+    }
+}
+
+/// Same as `fuzz_decode`, for types that can be encoded in more than one
+/// way, like a `HashMap`, which encodes its entries in any order.
+///
+/// When decoding succeeds, the value is encoded again, and that has to have
+/// the same length and decode into an equal value.
+pub fn fuzz_decode_unordered<T: Codec + PartialEq + Debug>(data: &[u8]) {
+    fuzz_decode_equal::<T>(data,true);
+}
+
+/// Same as `fuzz_decode_unordered`, for types that can encode again into
+/// other bytes of another length: `#[codec(versioned)]` structs, because a
+/// struct of another version encodes again as this version, and `Compressed`
+/// values, because the same bytes can be compressed in more than one way.
+///
+/// Only the value has to be the same.
+pub fn fuzz_decode_value_equal<T: Codec + PartialEq + Debug>(data: &[u8]) {
+    fuzz_decode_equal::<T>(data,false);
+}
+
+/// Decode arbitrary `data` as a `Value` laid out as the schema of `T`, and
+/// check what comes out.
+///
+/// Decoding must not panic, and must not use more heap than `FUZZ_LIMITS`
+/// allow. When `T` decodes from `data` as well, both have to take the same
+/// number of bytes.
+pub fn fuzz_value<T: Codec + Debug>(data: &[u8]) {
+    let schema = T::schema();
+    let mut ctx = DecodeContext::new(FUZZ_LIMITS);
+    let (result,heap) = measure_peak(|| decode_value(&schema,data,&mut ctx,&mut Vec::new()));
+    assert!(heap <= FUZZ_HEAP_FACTOR * FUZZ_LIMITS.max_alloc,"decoding {} bytes uses {} bytes of heap",data.len(),heap);
+    if let Ok((len,_)) = result {
+        assert!(len <= data.len(),"dynamic decode consumes {} of {} bytes",len,data.len());
+        if let Ok((l,target)) = T::decode_with(data,&mut DecodeContext::new(FUZZ_LIMITS)) {
+            assert_eq!(l,len,"{:?} takes {} bytes, but {} as a value",target,l,len);
+        }
+    }
+    fuzz_stream::<T>(data);
+}
+
+/// Read arbitrary `data` as text with `from_text`, and check what comes
+/// out.
+///
+/// Reading must not panic. When it succeeds, `to_text` of the value has to
+/// read back into an equal value. `T` should not hold floats, a NaN is not
+/// equal to itself.
+pub fn fuzz_from_text<T: Codec + PartialEq + Debug>(data: &[u8]) {
+    if let Ok(text) = std::str::from_utf8(data) {
+        if let Ok(value) = from_text::<T>(text) {
+            match to_text(&value) {
+                Ok(text) => match from_text::<T>(&text) {
+                    Ok(target) => assert_eq!(target,value,"text {} reads back as {:?}",text,target),
+                    Err(e) => panic!("text {} of {:?} does not read back: {}",text,value,e),
+                },
+                Err(e) => panic!("to_text of {:?} failed: {}",value,e),
+            }
+        }
+    }
+}
+
+// Decode `data` with a `Decoder`, which has to come to an end, with a value
+// or with an error.
+fn fuzz_stream<T: Codec>(data: &[u8]) {
+    let _ = Decoder::with_limits(data,FUZZ_LIMITS).decode::<T>();
+}
+
+// Decode `data`, and check that the value encodes again into bytes that
+// decode into an equal value, of the same length if `same_length`.
+fn fuzz_decode_equal<T: Codec + PartialEq + Debug>(data: &[u8],same_length: bool) {
+    let mut ctx = DecodeContext::new(FUZZ_LIMITS);
+    let (result,heap) = measure_peak(|| T::decode_with(data,&mut ctx));
+    assert!(heap <= FUZZ_HEAP_FACTOR * FUZZ_LIMITS.max_alloc,"decoding {} bytes uses {} bytes of heap",data.len(),heap);
+    if let Ok((len,value)) = result {
+        assert!(len <= data.len(),"decode consumes {} of {} bytes",len,data.len());
+        let mut buffer = Vec::<u8>::new();
+        let encoded = value.encode(&mut buffer);
+        if same_length {
+            assert_eq!(buffer.len(),len,"{:?} encodes into {} bytes but was decoded from {}",value,buffer.len(),len);
+        }
+        assert_eq!(encoded,buffer.len(),"encode of {:?} returns {} but writes {} bytes",value,encoded,buffer.len());
+        assert_eq!(value.size(),buffer.len(),"size of {:?} is {} but encode writes {} bytes",value,value.size(),buffer.len());
+        match T::decode_with(&buffer,&mut DecodeContext::new(FUZZ_LIMITS)) {
+            Ok((l,target)) => {
+                assert_eq!(l,buffer.len(),"decode of re-encoded {:?} consumes {} of {} bytes",value,l,buffer.len());
+                assert_eq!(target,value,"re-encoded value decodes into a different value");
+            },
+            Err(e) => panic!("decode of re-encoded {:?} failed: {}",value,e),
+        }
+    }
+    fuzz_stream::<T>(data);
+}
+
+// Heap in use and the most that was in use since the last `measure_peak`,
+// kept by `PeakAllocator`.
+static HEAP_CURRENT: AtomicUsize = AtomicUsize::new(0);
+static HEAP_PEAK: AtomicUsize = AtomicUsize::new(0);
+
+/// Global allocator that keeps track of the heap in use, so `fuzz_decode`
+/// can check how much decoding allocates.
+///
+/// Install it in a fuzz target with:
+///
+/// ```
+/// #[global_allocator]
+/// static ALLOCATOR: codec::testing::PeakAllocator = codec::testing::PeakAllocator;
+/// ```
+pub struct PeakAllocator;
+
+fn heap_grown(bytes: usize) {
+    let current = HEAP_CURRENT.fetch_add(bytes,Ordering::Relaxed) + bytes;
+    HEAP_PEAK.fetch_max(current,Ordering::Relaxed);
+}
+
+fn heap_shrunk(bytes: usize) {
+    HEAP_CURRENT.fetch_sub(bytes,Ordering::Relaxed);
+}
+
+unsafe impl GlobalAlloc for PeakAllocator {
+    unsafe fn alloc(&self,layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            heap_grown(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn alloc_zeroed(&self,layout: Layout) -> *mut u8 {
+        let ptr = System.alloc_zeroed(layout);
+        if !ptr.is_null() {
+            heap_grown(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self,ptr: *mut u8,layout: Layout) {
+        System.dealloc(ptr,layout);
+        heap_shrunk(layout.size());
+    }
+
+    unsafe fn realloc(&self,ptr: *mut u8,layout: Layout,new_size: usize) -> *mut u8 {
+        let new_ptr = System.realloc(ptr,layout,new_size);
+        if !new_ptr.is_null() {
+            if new_size > layout.size() {
+                heap_grown(new_size - layout.size());
+            }
+            else {
+                heap_shrunk(layout.size() - new_size);
+            }
+        }
+        new_ptr
+    }
+}
+
+/// Run `f`, and return its result with the most heap it had in use at any
+/// time on top of what was in use before.
+///
+/// Always 0 when `PeakAllocator` is not the global allocator.
+pub fn measure_peak<R>(f: impl FnOnce() -> R) -> (R,usize) {
+    let start = HEAP_CURRENT.load(Ordering::Relaxed);
+    HEAP_PEAK.store(start,Ordering::Relaxed);
+    let result = f();
+    let peak = HEAP_PEAK.load(Ordering::Relaxed);
+    (result,peak.saturating_sub(start))
+}
+
+/// Small deterministic pseudo-random generator (xorshift64*).
+pub struct Rng(u64);
+
+impl Rng {
+
+    pub fn new(seed: u64) -> Rng {
+        Rng(if seed == 0 { 1 } else { seed })
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+
+    /// Random number in `0..n`, `n` must not be 0.
+    pub fn below(&mut self,n: usize) -> usize {
+        (self.next_u64() % n as u64) as usize
+    }
+
+    /// Random length for a collection.
+    pub fn length(&mut self) -> usize {
+        self.below(MAX_RANDOM_LEN)
+    }
+}
+
+/// Type of which `Rng` can make random values.
+///
+/// Implemented for all types that have a built-in `Codec`. Implement it for
+/// derived types to run them through `assert_random_roundtrips`.
+pub trait Random {
+    fn random(rng: &mut Rng) -> Self;
+}
+
+macro_rules! random_int {
+    ($($ty:ty),+) => {
+        $(
+            impl Random for $ty {
+                fn random(rng: &mut Rng) -> Self {
+
+                    // favor the small values and the edges
+                    match rng.below(4) {
+                        0 => (rng.next_u64() % 256) as $ty,
+                        1 => <$ty>::MIN.wrapping_add((rng.next_u64() % 4) as $ty),
+                        2 => <$ty>::MAX.wrapping_sub((rng.next_u64() % 4) as $ty),
+                        _ => rng.next_u64() as $ty,
+                    }
+                }
+            }
+        )+
+    };
+}
+
+random_int!(u8,u16,u32,u64,usize,i8,i16,i32,i64,isize);
+
+impl Random for u128 {
+    fn random(rng: &mut Rng) -> Self {
+        ((rng.next_u64() as u128) << 64) | rng.next_u64() as u128
+    }
+}
+
+impl Random for i128 {
+    fn random(rng: &mut Rng) -> Self {
+        u128::random(rng) as i128
+    }
+}
+
+// NaN never equals itself, so floats are random bit patterns other than NaN.
+
+impl Random for f32 {
+    fn random(rng: &mut Rng) -> Self {
+        loop {
+            let value = f32::from_bits(rng.next_u64() as u32);
+            if !value.is_nan() {
+                return value;
+            }
+        }
+    }
+}
+
+impl Random for f64 {
+    fn random(rng: &mut Rng) -> Self {
+        loop {
+            let value = f64::from_bits(rng.next_u64());
+            if !value.is_nan() {
+                return value;
+            }
+        }
+    }
+}
+
+impl Random for bool {
+    fn random(rng: &mut Rng) -> Self {
+        rng.next_u64() & 1 != 0
+    }
+}
+
+impl Random for char {
+    fn random(rng: &mut Rng) -> Self {
+        loop {
+            let code = if rng.below(2) == 0 { rng.below(0x80) as u32 } else { rng.below(0x110000) as u32 };
+            if let Some(c) = char::from_u32(code) {
+                return c;
+            }
+        }
+    }
+}
+
+impl Random for String {
+    fn random(rng: &mut Rng) -> Self {
+        let len = rng.length();
+        (0..len).map(|_| char::random(rng)).collect()
+    }
+}
+
+impl Random for () {
+    fn random(_rng: &mut Rng) -> Self { }
+}
+
+impl<T: Random> Random for Option<T> {
+    fn random(rng: &mut Rng) -> Self {
+        if rng.below(2) == 0 { None } else { Some(T::random(rng)) }
+    }
+}
+
+impl<T: Random> Random for Box<T> {
+    fn random(rng: &mut Rng) -> Self {
+        Box::new(T::random(rng))
+    }
+}
+
+impl<T: Random> Random for Rc<T> {
+    fn random(rng: &mut Rng) -> Self {
+        Rc::new(T::random(rng))
+    }
+}
+
+impl<T: Random> Random for Arc<T> {
+    fn random(rng: &mut Rng) -> Self {
+        Arc::new(T::random(rng))
+    }
+}
+
+macro_rules! random_tuple {
+    ($($ty:ident),+) => {
+        impl<$($ty: Random),+> Random for ($($ty,)+) {
+            fn random(rng: &mut Rng) -> Self {
+                ($($ty::random(rng),)+)
+            }
+        }
+    };
+}
+
+random_tuple!(A);
+random_tuple!(A,B);
+random_tuple!(A,B,C);
+random_tuple!(A,B,C,D);
+random_tuple!(A,B,C,D,E);
+random_tuple!(A,B,C,D,E,F);
+random_tuple!(A,B,C,D,E,F,G);
+random_tuple!(A,B,C,D,E,F,G,H);
+random_tuple!(A,B,C,D,E,F,G,H,I);
+random_tuple!(A,B,C,D,E,F,G,H,I,J);
+random_tuple!(A,B,C,D,E,F,G,H,I,J,K);
+random_tuple!(A,B,C,D,E,F,G,H,I,J,K,L);
+
+impl<T: Random,const N: usize> Random for [T; N] {
+    fn random(rng: &mut Rng) -> Self {
+        std::array::from_fn(|_| T::random(rng))
+    }
+}
+
+impl<T: Random> Random for Vec<T> {
+    fn random(rng: &mut Rng) -> Self {
+        let len = rng.length();
+        (0..len).map(|_| T::random(rng)).collect()
+    }
+}
+
+impl<T: Random> Random for VecDeque<T> {
+    fn random(rng: &mut Rng) -> Self {
+        Vec::<T>::random(rng).into()
+    }
+}
+
+impl<K: Random + Eq + Hash,V: Random> Random for HashMap<K,V> {
+    fn random(rng: &mut Rng) -> Self {
+        Vec::<(K,V)>::random(rng).into_iter().collect()
+    }
+}
+
+impl<K: Random + Ord,V: Random> Random for BTreeMap<K,V> {
+    fn random(rng: &mut Rng) -> Self {
+        Vec::<(K,V)>::random(rng).into_iter().collect()
+    }
+}
+
+impl<T: Random + Eq + Hash> Random for HashSet<T> {
+    fn random(rng: &mut Rng) -> Self {
+        Vec::<T>::random(rng).into_iter().collect()
+    }
+}
+
+impl<T: Random + Ord> Random for BTreeSet<T> {
+    fn random(rng: &mut Rng) -> Self {
+        Vec::<T>::random(rng).into_iter().collect()
+    }
+}
+
+impl<T: Random> Random for Varint<T> {
+    fn random(rng: &mut Rng) -> Self {
+        Varint(T::random(rng))
+    }
+}
+
+impl<T: Random> Random for BigEndian<T> {
+    fn random(rng: &mut Rng) -> Self {
+        BigEndian(T::random(rng))
+    }
+}
+
+impl<T: Random> Random for Checked<T> {
+    fn random(rng: &mut Rng) -> Self {
+        Checked(T::random(rng))
+    }
+}
+
+impl<T: Random> Random for Compressed<T> {
+    fn random(rng: &mut Rng) -> Self {
+        Compressed(T::random(rng))
+    }
+}
+
+impl Random for Ipv4Addr {
+    fn random(rng: &mut Rng) -> Self {
+        Ipv4Addr::from(u32::random(rng))
+    }
+}
+
+impl Random for Ipv6Addr {
+    fn random(rng: &mut Rng) -> Self {
+        Ipv6Addr::from(u128::random(rng))
+    }
+}
+
+impl Random for IpAddr {
+    fn random(rng: &mut Rng) -> Self {
+        if rng.below(2) == 0 { IpAddr::V4(Ipv4Addr::random(rng)) } else { IpAddr::V6(Ipv6Addr::random(rng)) }
+    }
+}
+
+impl Random for SocketAddrV4 {
+    fn random(rng: &mut Rng) -> Self {
+        SocketAddrV4::new(Ipv4Addr::random(rng),u16::random(rng))
+    }
+}
+
+// Flow info and scope ID are not encoded, so they are left 0.
+impl Random for SocketAddrV6 {
+    fn random(rng: &mut Rng) -> Self {
+        SocketAddrV6::new(Ipv6Addr::random(rng),u16::random(rng),0,0)
+    }
+}
+
+impl Random for SocketAddr {
+    fn random(rng: &mut Rng) -> Self {
+        if rng.below(2) == 0 { SocketAddr::V4(SocketAddrV4::random(rng)) } else { SocketAddr::V6(SocketAddrV6::random(rng)) }
+    }
+}
+
+impl Random for Duration {
+    fn random(rng: &mut Rng) -> Self {
+        Duration::new(u64::random(rng),rng.below(1_000_000_000) as u32)
+    }
+}
+
+// Within a few centuries of the epoch, so the time fits any platform.
+impl Random for SystemTime {
+    fn random(rng: &mut Rng) -> Self {
+        let offset = Duration::new(rng.below(1 << 33) as u64,rng.below(1_000_000_000) as u32);
+        if rng.below(2) == 0 { UNIX_EPOCH + offset } else { UNIX_EPOCH - offset }
+    }
+}
+
+impl Random for Timestamp {
+    fn random(rng: &mut Rng) -> Self {
+        Timestamp::from_nanos(u64::random(rng))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use {
+        crate::*,
+        crate::testing::*,
+        std::{
+            collections::{
+                BTreeMap,
+                BTreeSet,
+                HashMap,
+                HashSet,
+                VecDeque,
+            },
+            net::{
+                IpAddr,
+                Ipv4Addr,
+                Ipv6Addr,
+                SocketAddr,
+                SocketAddrV4,
+                SocketAddrV6,
+            },
+            rc::Rc,
+            sync::Arc,
+            time::{
+                Duration,
+                SystemTime,
+            },
+        },
+    };
+
+    const COUNT: usize = 200;
+
+    #[test]
+    fn test_roundtrip_scalars() {
+        assert_random_roundtrips::<bool>(COUNT);
+        assert_random_roundtrips::<u8>(COUNT);
+        assert_random_roundtrips::<u16>(COUNT);
+        assert_random_roundtrips::<u32>(COUNT);
+        assert_random_roundtrips::<u64>(COUNT);
+        assert_random_roundtrips::<u128>(COUNT);
+        assert_random_roundtrips::<usize>(COUNT);
+        assert_random_roundtrips::<i8>(COUNT);
+        assert_random_roundtrips::<i16>(COUNT);
+        assert_random_roundtrips::<i32>(COUNT);
+        assert_random_roundtrips::<i64>(COUNT);
+        assert_random_roundtrips::<i128>(COUNT);
+        assert_random_roundtrips::<isize>(COUNT);
+        assert_random_roundtrips::<f32>(COUNT);
+        assert_random_roundtrips::<f64>(COUNT);
+        assert_random_roundtrips::<char>(COUNT);
+        assert_random_roundtrips::<()>(COUNT);
+        assert_random_roundtrips::<Varint<u16>>(COUNT);
+        assert_random_roundtrips::<Varint<u32>>(COUNT);
+        assert_random_roundtrips::<Varint<u64>>(COUNT);
+        assert_random_roundtrips::<Varint<i16>>(COUNT);
+        assert_random_roundtrips::<Varint<i32>>(COUNT);
+        assert_random_roundtrips::<Varint<i64>>(COUNT);
+    }
+
+    #[test]
+    fn test_roundtrip_containers() {
+        assert_random_roundtrips::<String>(COUNT);
+        assert_random_roundtrips::<Option<String>>(COUNT);
+        assert_random_roundtrips::<Box<u32>>(COUNT);
+        assert_random_roundtrips::<Rc<String>>(COUNT);
+        assert_random_roundtrips::<Arc<Vec<u8>>>(COUNT);
+        assert_random_roundtrips::<(u8,String,f64)>(COUNT);
+        assert_random_roundtrips::<(u8,u16,u32,u64,i8,i16,i32,i64,bool,char,String,())>(COUNT);
+        assert_random_roundtrips::<[u16; 3]>(COUNT);
+        assert_random_roundtrips::<[String; 2]>(COUNT);
+        assert_random_roundtrips::<Vec<u8>>(COUNT);
+        assert_random_roundtrips::<Vec<Vec<String>>>(COUNT);
+        assert_random_roundtrips::<VecDeque<i32>>(COUNT);
+        assert_random_roundtrips::<HashMap<u32,String>>(COUNT);
+        assert_random_roundtrips::<BTreeMap<String,Option<u8>>>(COUNT);
+        assert_random_roundtrips::<HashSet<char>>(COUNT);
+        assert_random_roundtrips::<BTreeSet<i64>>(COUNT);
+    }
+
+    #[test]
+    fn test_roundtrip_std() {
+        assert_random_roundtrips::<Ipv4Addr>(COUNT);
+        assert_random_roundtrips::<Ipv6Addr>(COUNT);
+        assert_random_roundtrips::<IpAddr>(COUNT);
+        assert_random_roundtrips::<SocketAddrV4>(COUNT);
+        assert_random_roundtrips::<SocketAddrV6>(COUNT);
+        assert_random_roundtrips::<SocketAddr>(COUNT);
+        assert_random_roundtrips::<Duration>(COUNT);
+        assert_random_roundtrips::<SystemTime>(COUNT);
+        assert_random_roundtrips::<Timestamp>(COUNT);
+    }
+
+    // Encodings of random values, cut short, with a byte changed, or with
+    // extra bytes after them.
+    fn mutations<T: Random + Encode>(count: usize) -> Vec<Vec<u8>> {
+        let mut rng = Rng::new(count as u64);
+        let mut result = Vec::<Vec<u8>>::new();
+        for _ in 0..count {
+            let mut buffer = Vec::<u8>::new();
+            T::random(&mut rng).encode(&mut buffer);
+            let cut = rng.below(buffer.len() + 1);
+            result.push(buffer[..cut].to_vec());
+            if !buffer.is_empty() {
+                let mut changed = buffer.clone();
+                let index = rng.below(buffer.len());
+                changed[index] = rng.next_u64() as u8;
+                result.push(changed);
+            }
+            buffer.push(rng.next_u64() as u8);
+            result.push(buffer);
+        }
+        result
+    }
+
+    #[test]
+    fn test_fuzz_decode() {
+        for data in mutations::<(bool,char,Varint<i32>,Option<f64>)>(COUNT) {
+            fuzz_decode::<(bool,char,Varint<i32>,Option<f64>)>(&data);
+        }
+        for data in mutations::<Vec<(String,[u8; 2])>>(COUNT) {
+            fuzz_decode::<Vec<(String,[u8; 2])>>(&data);
+        }
+        for data in mutations::<String>(COUNT) {
+            fuzz_decode::<&str>(&data);
+        }
+        for data in mutations::<(SocketAddr,Duration,SystemTime)>(COUNT) {
+            fuzz_decode::<(SocketAddr,Duration,SystemTime)>(&data);
+        }
+        for data in mutations::<HashMap<u16,BTreeSet<u8>>>(COUNT) {
+            fuzz_decode_unordered::<HashMap<u16,BTreeSet<u8>>>(&data);
+        }
+
+        // a length prefix far beyond the limits
+        fuzz_decode::<Vec<u64>>(&[0xFF,0xFF,0xFF,0x7F]);
+        fuzz_decode_unordered::<HashSet<u32>>(&[0xFF,0xFF,0xFF,0x7F]);
+    }
+
+    #[test]
+    fn test_fuzz_wrappers() {
+        for data in mutations::<(BigEndian<u16>,Checked<Vec<String>>)>(COUNT) {
+            fuzz_decode::<(BigEndian<u16>,Checked<Vec<String>>)>(&data);
+            fuzz_value::<(BigEndian<u16>,Checked<Vec<String>>)>(&data);
+        }
+        for data in mutations::<Checked<Compressed<Vec<u16>>>>(COUNT) {
+            fuzz_decode_value_equal::<Checked<Compressed<Vec<u16>>>>(&data);
+            fuzz_value::<Checked<Compressed<Vec<u16>>>>(&data);
+        }
+
+        // stored as they are, though they compress, and too short inside
+        fuzz_decode_value_equal::<Compressed<Vec<u8>>>(&[8,0,0,0,8,0,0,0,4,0,0,0,7,7,7,7]);
+        fuzz_decode_value_equal::<Compressed<u32>>(&[2,0,0,0,2,0,0,0,1,2,3,4]);
+        fuzz_value::<Compressed<u32>>(&[2,0,0,0,2,0,0,0,1,2,3,4]);
+    }
+
+    #[test]
+    fn test_fuzz_values() {
+        for data in mutations::<(Option<Option<u8>>,HashMap<u16,String>,IpAddr)>(COUNT) {
+            fuzz_value::<(Option<Option<u8>>,HashMap<u16,String>,IpAddr)>(&data);
+        }
+        for data in mutations::<Vec<(char,Duration,Varint<i64>)>>(COUNT) {
+            fuzz_value::<Vec<(char,Duration,Varint<i64>)>>(&data);
+        }
+    }
+
+    #[test]
+    fn test_fuzz_from_text() {
+        let mut rng = Rng::new(3);
+        for _ in 0..COUNT {
+            let value = <(Vec<Option<String>>,BTreeMap<u8,char>)>::random(&mut rng);
+            let text = to_text(&value).unwrap();
+            fuzz_from_text::<(Vec<Option<String>>,BTreeMap<u8,char>)>(text.as_bytes());
+            let cut = rng.below(text.len() + 1);
+            fuzz_from_text::<(Vec<Option<String>>,BTreeMap<u8,char>)>(&text.as_bytes()[..cut]);
+        }
+        fuzz_from_text::<Option<Option<()>>>(b"[null]");
+        fuzz_from_text::<Vec<u8>>(&[b'[',0xFF,b']']);
+        fuzz_from_text::<Vec<u8>>("[".repeat(1000).as_bytes());
+    }
+
+    #[test]
+    #[should_panic(expected = "encodes into different bytes")]
+    fn test_fuzz_decode_broken() {
+        fuzz_decode::<BTreeSet<u8>>(&[2,0,0,0,2,1]);
+    }
+
+    #[test]
+    #[should_panic(expected = "size of")]
+    fn test_roundtrip_broken() {
+
+        struct Broken(u32);
+
+        impl Encode for Broken {
+            fn encode(&self,buffer: &mut Vec<u8>) -> usize {
+                self.0.encode(buffer)
+            }
+
+            fn size(&self) -> usize {
+                2
+            }
+        }
+
+        impl Codec for Broken {
+            fn decode_with(buffer: &[u8],ctx: &mut DecodeContext) -> Result<(usize,Self),DecodeError> {
+                let (l,a) = u32::decode_with(buffer,ctx)?;
+                Ok((l,Broken(a)))
+            }
+        }
+
+        impl PartialEq for Broken {
+            fn eq(&self,other: &Broken) -> bool {
+                self.0 == other.0
+            }
+        }
+
+        impl std::fmt::Debug for Broken {
+            fn fmt(&self,f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                write!(f,"Broken({})",self.0)
+            }
+        }
+
+        assert_roundtrip(&Broken(1));
+    }
+}
@@ -0,0 +1,740 @@
+// Echidna - Codec
+
+use {
+    crate::*,
+    std::str::FromStr,
+};
+
+/// Write `value` as JSON-like text, for logs, config files and test fixtures.
+///
+/// Works for every type with a `Schema`:
+/// - structs are objects of their fields, tuple structs are arrays,
+/// - unit variants are strings, other variants are objects with the variant
+///   name as the only key, like `{"Moved": {"x": 1, "y": 2}}`,
+/// - `None` and `()` are `null`, `Some` is the value itself, or an array of
+///   the value if that is `null` too, like `[null]` for `Some(None)`,
+/// - tuples, arrays, lists and sets are arrays,
+/// - maps with string keys are objects, other maps are arrays of
+///   `[key, value]` pairs,
+/// - chars are strings, floats that are not finite are `nan`, `inf` and
+///   `-inf`,
+/// - `BigEndian`, `Checked` and `Compressed` values are the value itself.
+///
+/// Fails with `TextError::Opaque` for types or fields without a layout.
+pub fn to_text<T: Encode + ?Sized>(value: &T) -> Result<String,TextError> {
+    let schema = T::schema();
+    let mut buffer = Vec::<u8>::new();
+    value.encode(&mut buffer);
+    let (_,value) = Value::decode_with(&schema,&buffer).map_err(|e| match e {
+        DecodeError::Opaque { .. } => TextError::Opaque,
+        e => TextError::Decode(e),
+    })?;
+    let mut text = String::new();
+    write_value(&mut text,&schema,&value,&mut Vec::new());
+    Ok(text)
+}
+
+/// Read a value from text in the format of `to_text`.
+///
+/// Object fields can be in any order, but all of them have to be there,
+/// except the ones the type skips. Fails with `TextError::Invalid` at the
+/// first place where the text is not valid or does not fit `T`, or where
+/// arrays and objects nest deeper than the `max_depth` of the default
+/// `DecodeLimits`.
+pub fn from_text<T: Codec>(text: &str) -> Result<T,TextError> {
+    let mut parser = Parser { text, pos: 0, line: 1, column: 1, depth: 0, };
+    let node = parser.parse_value()?;
+    parser.skip_whitespace();
+    if parser.pos < text.len() {
+        return Err(parser.invalid("text after the value"));
+    }
+    let mut buffer = Vec::<u8>::new();
+    encode_node(&node,&T::schema(),&mut buffer,&mut Vec::new())?;
+    let (_,value) = T::decode(&buffer).map_err(TextError::Decode)?;
+    Ok(value)
+}
+
+fn write_string(text: &mut String,value: &str) {
+    text.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => text.push_str("\\\""),
+            '\\' => text.push_str("\\\\"),
+            '\n' => text.push_str("\\n"),
+            '\r' => text.push_str("\\r"),
+            '\t' => text.push_str("\\t"),
+            c if (c as u32) < 0x20 => text.push_str(&format!("\\u{:04X}",c as u32)),
+            c => text.push(c),
+        }
+    }
+    text.push('"');
+}
+
+// Shortest text that reads back as the same float, `nan`, `inf` or `-inf`.
+fn write_float(text: &mut String,value: String) {
+    match value.as_str() {
+        "NaN" => text.push_str("nan"),
+        _ => text.push_str(&value),
+    }
+}
+
+fn write_list<'s>(text: &mut String,items: impl Iterator<Item = (&'s Schema,&'s Value)>,enclosing: &mut Vec<&'s Schema>) {
+    text.push('[');
+    for (i,(schema,value)) in items.enumerate() {
+        if i > 0 {
+            text.push_str(", ");
+        }
+        write_value(text,schema,value,enclosing);
+    }
+    text.push(']');
+}
+
+// Object of named fields, array of tuple fields.
+fn write_fields<'s>(text: &mut String,fields: &'s [Field],values: &'s [(String,Value)],enclosing: &mut Vec<&'s Schema>) {
+    if fields.first().is_some_and(|field| field.name == "0") {
+        write_list(text,fields.iter().map(|field| &field.schema).zip(values.iter().map(|(_,value)| value)),enclosing);
+    }
+    else {
+        text.push('{');
+        for (i,(field,(name,value))) in fields.iter().zip(values.iter()).enumerate() {
+            if i > 0 {
+                text.push_str(", ");
+            }
+            write_string(text,name);
+            text.push_str(": ");
+            write_value(text,&field.schema,value,enclosing);
+        }
+        text.push('}');
+    }
+}
+
+// Whether values of `schema` can be `null`, then `Some` of them is written
+// as an array of one, or it would read back as `None`.
+fn nullable(schema: &Schema) -> bool {
+    match schema {
+        Schema::Unit | Schema::Option(_) => true,
+        Schema::BigEndian(inner) | Schema::Checked(inner) | Schema::Compressed(inner) => nullable(inner),
+        _ => false,
+    }
+}
+
+// `value` was decoded by `schema`, so they always fit together.
+fn write_value<'s>(text: &mut String,schema: &'s Schema,value: &'s Value,enclosing: &mut Vec<&'s Schema>) {
+    match schema {
+        Schema::Recursive(name) => {
+            if let Some(schema) = enclosing.iter().rev().copied().find(|schema| schema_name(schema) == name) {
+                write_value(text,schema,value,enclosing);
+            }
+            return;
+        },
+
+        // byte order, checksums and compression don't show in the text
+        Schema::BigEndian(inner) | Schema::Checked(inner) | Schema::Compressed(inner) => {
+            write_value(text,inner,value,enclosing);
+            return;
+        },
+        _ => { },
+    }
+    match value {
+        Value::Unit | Value::Option(None) => text.push_str("null"),
+        Value::Bool(value) => text.push_str(if *value { "true" } else { "false" }),
+        Value::UInt(value) => text.push_str(&value.to_string()),
+        Value::Int(value) => text.push_str(&value.to_string()),
+        Value::Float(value) => if let Schema::F32 = schema {
+            write_float(text,format!("{:?}",*value as f32));
+        }
+        else {
+            write_float(text,format!("{:?}",value));
+        },
+        Value::Char(value) => write_string(text,&value.to_string()),
+        Value::String(value) => write_string(text,value),
+        Value::Bytes(bytes) => {
+            text.push('[');
+            for (i,byte) in bytes.iter().enumerate() {
+                if i > 0 {
+                    text.push_str(", ");
+                }
+                text.push_str(&byte.to_string());
+            }
+            text.push(']');
+        },
+        Value::Option(Some(value)) => if let Schema::Option(item) = schema {
+            if nullable(item) {
+                text.push('[');
+                write_value(text,item,value,enclosing);
+                text.push(']');
+            }
+            else {
+                write_value(text,item,value,enclosing);
+            }
+        },
+        Value::List(items) => match schema {
+            Schema::Tuple(schemas) => write_list(text,schemas.iter().zip(items.iter()),enclosing),
+            Schema::List(item) | Schema::Array(item,_) => write_list(text,std::iter::repeat(item.as_ref()).zip(items.iter()),enclosing),
+            _ => { },
+        },
+        Value::Map(pairs) => if let Schema::Map(key,value) = schema {
+            if let Schema::String = key.as_ref() {
+                text.push('{');
+                for (i,(k,v)) in pairs.iter().enumerate() {
+                    if i > 0 {
+                        text.push_str(", ");
+                    }
+                    write_value(text,key,k,enclosing);
+                    text.push_str(": ");
+                    write_value(text,value,v,enclosing);
+                }
+                text.push('}');
+            }
+            else {
+                text.push('[');
+                for (i,(k,v)) in pairs.iter().enumerate() {
+                    if i > 0 {
+                        text.push_str(", ");
+                    }
+                    text.push('[');
+                    write_value(text,key,k,enclosing);
+                    text.push_str(", ");
+                    write_value(text,value,v,enclosing);
+                    text.push(']');
+                }
+                text.push(']');
+            }
+        },
+        Value::Struct { fields: values,.. } => {
+            let fields = match schema {
+                Schema::Struct { fields,.. } => fields,
+                Schema::Versioned(_,_,inner) => match inner.as_ref() {
+                    Schema::Struct { fields,.. } => fields,
+                    _ => { return; },
+                },
+                _ => { return; },
+            };
+            enclosing.push(schema);
+            write_fields(text,fields,values,enclosing);
+            enclosing.pop();
+        },
+        Value::Variant { variant,fields: values,.. } => if let Schema::Enum { variants,.. } = schema {
+            if let Some(v) = variants.iter().find(|v| &v.name == variant) {
+                if v.fields.is_empty() {
+                    write_string(text,variant);
+                }
+                else {
+                    enclosing.push(schema);
+                    text.push('{');
+                    write_string(text,variant);
+                    text.push_str(": ");
+                    write_fields(text,&v.fields,values,enclosing);
+                    text.push('}');
+                    enclosing.pop();
+                }
+            }
+        },
+    }
+}
+
+// Parsed text, before it meets the schema.
+struct Node {
+    line: usize,
+    column: usize,
+    kind: Kind,
+}
+
+enum Kind {
+    Null,
+    Bool(bool),
+    Number(String),
+    String(String),
+    List(Vec<Node>),
+    Object(Vec<(String,Node)>),
+}
+
+impl Node {
+
+    fn invalid(&self,message: impl Into<String>) -> TextError {
+        TextError::Invalid { line: self.line, column: self.column, message: message.into(), }
+    }
+
+    fn expected(&self,what: &str) -> TextError {
+        self.invalid(format!("expected {}",what))
+    }
+}
+
+struct Parser<'a> {
+    text: &'a str,
+    pos: usize,
+    line: usize,
+    column: usize,
+    depth: usize,
+}
+
+impl Parser<'_> {
+
+    fn peek(&self) -> Option<char> {
+        self.text[self.pos..].chars().next()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += c.len_utf8();
+        if c == '\n' {
+            self.line += 1;
+            self.column = 1;
+        }
+        else {
+            self.column += 1;
+        }
+        Some(c)
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(|c| c.is_whitespace()) {
+            self.bump();
+        }
+    }
+
+    fn invalid(&self,message: impl Into<String>) -> TextError {
+        TextError::Invalid { line: self.line, column: self.column, message: message.into(), }
+    }
+
+    fn unexpected(&self) -> TextError {
+        match self.peek() {
+            Some(c) => self.invalid(format!("unexpected `{}`",c)),
+            None => self.invalid("unexpected end of text"),
+        }
+    }
+
+    // Skip whitespace, then `c`.
+    fn expect(&mut self,c: char) -> Result<(),TextError> {
+        self.skip_whitespace();
+        if self.peek() == Some(c) {
+            self.bump();
+            Ok(())
+        }
+        else {
+            Err(self.unexpected())
+        }
+    }
+
+    // Enter an array or object at its opening bracket. Untrusted text could
+    // nest deep enough to overflow the stack otherwise.
+    fn enter(&mut self) -> Result<(),TextError> {
+        if self.depth >= DecodeLimits::default().max_depth {
+            return Err(self.invalid("nested too deep"));
+        }
+        self.depth += 1;
+        self.bump();
+        Ok(())
+    }
+
+    // Whether the list or object goes on after a `,`, or ends at `end`.
+    fn more(&mut self,end: char) -> Result<bool,TextError> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some(',') => {
+                self.bump();
+                Ok(true)
+            },
+            Some(c) if c == end => {
+                self.bump();
+                Ok(false)
+            },
+            _ => Err(self.unexpected()),
+        }
+    }
+
+    fn parse_string(&mut self) -> Result<String,TextError> {
+        self.expect('"')?;
+        let mut r = String::new();
+        loop {
+            match self.bump() {
+                Some('"') => { return Ok(r); },
+                Some('\\') => {
+                    let c = match self.bump() {
+                        Some('"') => '"',
+                        Some('\\') => '\\',
+                        Some('/') => '/',
+                        Some('b') => '\u{8}',
+                        Some('f') => '\u{C}',
+                        Some('n') => '\n',
+                        Some('r') => '\r',
+                        Some('t') => '\t',
+                        Some('u') => self.parse_unicode()?,
+                        _ => { return Err(self.invalid("invalid escape")); },
+                    };
+                    r.push(c);
+                },
+                Some(c) => r.push(c),
+                None => { return Err(self.invalid("unexpected end of text")); },
+            }
+        }
+    }
+
+    // `XXXX` after `\u`, or two of them for a surrogate pair.
+    fn parse_unicode(&mut self) -> Result<char,TextError> {
+        let high = self.parse_hex()?;
+        let code = if (0xD800..0xDC00).contains(&high) {
+            if (self.bump() != Some('\\')) || (self.bump() != Some('u')) {
+                return Err(self.invalid("unpaired surrogate"));
+            }
+            let low = self.parse_hex()?;
+            if !(0xDC00..0xE000).contains(&low) {
+                return Err(self.invalid("unpaired surrogate"));
+            }
+            0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00)
+        }
+        else {
+            high
+        };
+        char::from_u32(code).ok_or_else(|| self.invalid("invalid escape"))
+    }
+
+    fn parse_hex(&mut self) -> Result<u32,TextError> {
+        let mut r = 0u32;
+        for _ in 0..4 {
+            match self.bump().and_then(|c| c.to_digit(16)) {
+                Some(digit) => r = (r << 4) | digit,
+                None => { return Err(self.invalid("invalid escape")); },
+            }
+        }
+        Ok(r)
+    }
+
+    fn parse_value(&mut self) -> Result<Node,TextError> {
+        self.skip_whitespace();
+        let line = self.line;
+        let column = self.column;
+        let kind = match self.peek() {
+            Some('{') => {
+                self.enter()?;
+                let mut entries = Vec::<(String,Node)>::new();
+                self.skip_whitespace();
+                if self.peek() == Some('}') {
+                    self.bump();
+                }
+                else {
+                    loop {
+                        self.skip_whitespace();
+                        let key = self.parse_string()?;
+                        self.expect(':')?;
+                        entries.push((key,self.parse_value()?));
+                        if !self.more('}')? {
+                            break;
+                        }
+                    }
+                }
+                self.depth -= 1;
+                Kind::Object(entries)
+            },
+            Some('[') => {
+                self.enter()?;
+                let mut items = Vec::<Node>::new();
+                self.skip_whitespace();
+                if self.peek() == Some(']') {
+                    self.bump();
+                }
+                else {
+                    loop {
+                        items.push(self.parse_value()?);
+                        if !self.more(']')? {
+                            break;
+                        }
+                    }
+                }
+                self.depth -= 1;
+                Kind::List(items)
+            },
+            Some('"') => Kind::String(self.parse_string()?),
+
+            // numbers and words, the schema decides if a number fits
+            Some(c) if c.is_ascii_alphanumeric() || (c == '-') || (c == '+') => {
+                let start = self.pos;
+                while self.peek().is_some_and(|c| c.is_ascii_alphanumeric() || (c == '.') || (c == '-') || (c == '+')) {
+                    self.bump();
+                }
+                match &self.text[start..self.pos] {
+                    "null" => Kind::Null,
+                    "true" => Kind::Bool(true),
+                    "false" => Kind::Bool(false),
+                    word if word.starts_with(|c: char| c.is_ascii_alphabetic()) && (word != "nan") && (word != "inf") => {
+                        return Err(TextError::Invalid { line, column, message: format!("unexpected `{}`",word), });
+                    },
+                    number => Kind::Number(number.to_string()),
+                }
+            },
+            _ => { return Err(self.unexpected()); },
+        };
+        Ok(Node { line, column, kind, })
+    }
+}
+
+fn parse_number<T: FromStr>(node: &Node,what: &str) -> Result<T,TextError> {
+    match &node.kind {
+        Kind::Number(number) => number.parse::<T>().map_err(|_| node.invalid(format!("`{}` is not {}",number,what))),
+        _ => Err(node.expected(what)),
+    }
+}
+
+fn encode_items<'s>(nodes: &[Node],schemas: impl Iterator<Item = &'s Schema>,buffer: &mut Vec<u8>,enclosing: &mut Vec<&'s Schema>) -> Result<(),TextError> {
+    for (node,schema) in nodes.iter().zip(schemas) {
+        encode_node(node,schema,buffer,enclosing)?;
+    }
+    Ok(())
+}
+
+// Fields from an object by name, or from an array for tuple fields.
+fn encode_fields<'s>(node: &Node,fields: &'s [Field],buffer: &mut Vec<u8>,enclosing: &mut Vec<&'s Schema>) -> Result<(),TextError> {
+    if fields.first().is_some_and(|field| field.name == "0") {
+        match &node.kind {
+            Kind::List(items) if items.len() == fields.len() => encode_items(items,fields.iter().map(|field| &field.schema),buffer,enclosing),
+            _ => Err(node.expected(&format!("an array of {} fields",fields.len()))),
+        }
+    }
+    else {
+        let entries = match &node.kind {
+            Kind::Object(entries) => entries,
+            _ => { return Err(node.expected("an object")); },
+        };
+        for (i,(name,value)) in entries.iter().enumerate() {
+            if !fields.iter().any(|field| &field.name == name) {
+                return Err(value.invalid(format!("unknown field `{}`",name)));
+            }
+            if entries[..i].iter().any(|(other,_)| other == name) {
+                return Err(value.invalid(format!("field `{}` appears twice",name)));
+            }
+        }
+        for field in fields {
+            match entries.iter().find(|(name,_)| name == &field.name) {
+                Some((_,value)) => encode_node(value,&field.schema,buffer,enclosing)?,
+                None => { return Err(node.invalid(format!("missing field `{}`",field.name))); },
+            }
+        }
+        Ok(())
+    }
+}
+
+fn encode_node<'s>(node: &Node,schema: &'s Schema,buffer: &mut Vec<u8>,enclosing: &mut Vec<&'s Schema>) -> Result<(),TextError> {
+    match schema {
+        Schema::Unit => match node.kind {
+            Kind::Null => { },
+            _ => { return Err(node.expected("null")); },
+        },
+        Schema::Bool => match node.kind {
+            Kind::Bool(value) => { value.encode(buffer); },
+            _ => { return Err(node.expected("true or false")); },
+        },
+        Schema::U8 => { parse_number::<u8>(node,"a u8")?.encode(buffer); },
+        Schema::U16 => { parse_number::<u16>(node,"a u16")?.encode(buffer); },
+        Schema::U32 => { parse_number::<u32>(node,"a u32")?.encode(buffer); },
+        Schema::U64 => { parse_number::<u64>(node,"a u64")?.encode(buffer); },
+        Schema::U128 => { parse_number::<u128>(node,"a u128")?.encode(buffer); },
+        Schema::I8 => { parse_number::<i8>(node,"an i8")?.encode(buffer); },
+        Schema::I16 => { parse_number::<i16>(node,"an i16")?.encode(buffer); },
+        Schema::I32 => { parse_number::<i32>(node,"an i32")?.encode(buffer); },
+        Schema::I64 => { parse_number::<i64>(node,"an i64")?.encode(buffer); },
+        Schema::I128 => { parse_number::<i128>(node,"an i128")?.encode(buffer); },
+        Schema::F32 => { parse_number::<f32>(node,"an f32")?.encode(buffer); },
+        Schema::F64 => { parse_number::<f64>(node,"an f64")?.encode(buffer); },
+        Schema::Varint { signed: false } => { Varint(parse_number::<u64>(node,"a u64")?).encode(buffer); },
+        Schema::Varint { signed: true } => { Varint(parse_number::<i64>(node,"an i64")?).encode(buffer); },
+        Schema::Char => match &node.kind {
+            Kind::String(value) if value.chars().count() == 1 => { value.chars().next().unwrap().encode(buffer); },
+            _ => { return Err(node.expected("a string of one char")); },
+        },
+        Schema::String => match &node.kind {
+            Kind::String(value) => { value.encode(buffer); },
+            _ => { return Err(node.expected("a string")); },
+        },
+        Schema::Option(item) => match &node.kind {
+            Kind::Null => { 0u8.encode(buffer); },
+            Kind::List(items) if nullable(item) => {
+                if items.len() != 1 {
+                    return Err(node.expected("null or an array of one value"));
+                }
+                1u8.encode(buffer);
+                encode_node(&items[0],item,buffer,enclosing)?;
+            },
+            _ if nullable(item) => { return Err(node.expected("null or an array of one value")); },
+            _ => {
+                1u8.encode(buffer);
+                encode_node(node,item,buffer,enclosing)?;
+            },
+        },
+        Schema::List(item) => match &node.kind {
+            Kind::List(items) => {
+                (items.len() as u32).encode(buffer);
+                encode_items(items,std::iter::repeat(item.as_ref()),buffer,enclosing)?;
+            },
+            _ => { return Err(node.expected("an array")); },
+        },
+        Schema::Array(item,count) => match &node.kind {
+            Kind::List(items) if items.len() == *count => encode_items(items,std::iter::repeat(item.as_ref()),buffer,enclosing)?,
+            _ => { return Err(node.expected(&format!("an array of {} items",count))); },
+        },
+        Schema::Tuple(schemas) => match &node.kind {
+            Kind::List(items) if items.len() == schemas.len() => encode_items(items,schemas.iter(),buffer,enclosing)?,
+            _ => { return Err(node.expected(&format!("an array of {} items",schemas.len()))); },
+        },
+        Schema::Map(key,value) => match (&node.kind,key.as_ref()) {
+            (Kind::Object(entries),Schema::String) => {
+                (entries.len() as u32).encode(buffer);
+                for (k,v) in entries {
+                    k.encode(buffer);
+                    encode_node(v,value,buffer,enclosing)?;
+                }
+            },
+            (Kind::List(pairs),_) if !matches!(key.as_ref(),Schema::String) => {
+                (pairs.len() as u32).encode(buffer);
+                for pair in pairs {
+                    match &pair.kind {
+                        Kind::List(kv) if kv.len() == 2 => {
+                            encode_node(&kv[0],key,buffer,enclosing)?;
+                            encode_node(&kv[1],value,buffer,enclosing)?;
+                        },
+                        _ => { return Err(pair.expected("a [key, value] pair")); },
+                    }
+                }
+            },
+            (_,Schema::String) => { return Err(node.expected("an object")); },
+            _ => { return Err(node.expected("an array of [key, value] pairs")); },
+        },
+        Schema::Struct { fields,.. } => {
+            enclosing.push(schema);
+            encode_fields(node,fields,buffer,enclosing)?;
+            enclosing.pop();
+        },
+        Schema::Enum { tag_size,big_endian,variants,.. } => {
+            let (name,fields) = match &node.kind {
+                Kind::String(name) => (name,None),
+                Kind::Object(entries) if entries.len() == 1 => (&entries[0].0,Some(&entries[0].1)),
+                _ => { return Err(node.expected("a variant")); },
+            };
+            let variant = match variants.iter().find(|variant| &variant.name == name) {
+                Some(variant) => variant,
+                None => { return Err(node.invalid(format!("unknown variant `{}`",name))); },
+            };
+            match (tag_size,big_endian) {
+                (1,_) => { (variant.tag as u8).encode(buffer); },
+                (2,false) => { (variant.tag as u16).encode(buffer); },
+                (2,true) => { BigEndian(variant.tag as u16).encode(buffer); },
+                (_,false) => { variant.tag.encode(buffer); },
+                (_,true) => { BigEndian(variant.tag).encode(buffer); },
+            }
+            match fields {
+                Some(fields) => {
+                    enclosing.push(schema);
+                    encode_fields(fields,&variant.fields,buffer,enclosing)?;
+                    enclosing.pop();
+                },
+                None => if !variant.fields.is_empty() {
+                    return Err(node.invalid(format!("variant `{}` has fields",name)));
+                },
+            }
+        },
+        Schema::Versioned(version,_,inner) => {
+            let fields = match inner.as_ref() {
+                Schema::Struct { fields,.. } => fields,
+                _ => { return Err(TextError::Opaque); },
+            };
+            let start = buffer.len();
+            version.encode(buffer);
+            0u32.encode(buffer);
+            enclosing.push(schema);
+            encode_fields(node,fields,buffer,enclosing)?;
+            enclosing.pop();
+            let length = (buffer.len() - start - 6) as u32;
+            buffer[start + 2..start + 6].copy_from_slice(&length.to_le_bytes());
+        },
+        Schema::BigEndian(inner) => {
+            if number_size(inner).is_none() {
+                return Err(TextError::Opaque);
+            }
+            let start = buffer.len();
+            encode_node(node,inner,buffer,enclosing)?;
+            buffer[start..].reverse();
+        },
+        Schema::Checked(inner) => {
+            let start = buffer.len();
+            encode_node(node,inner,buffer,enclosing)?;
+            crc32c(&buffer[start..]).encode(buffer);
+        },
+        Schema::Compressed(inner) => {
+            let mut raw = Vec::<u8>::new();
+            encode_node(node,inner,&mut raw,enclosing)?;
+            encode_compressed(&raw,buffer);
+        },
+        Schema::Recursive(name) => {
+            match enclosing.iter().rev().copied().find(|schema| schema_name(schema) == name) {
+                Some(schema) => encode_node(node,schema,buffer,enclosing)?,
+                None => { return Err(TextError::Opaque); },
+            }
+        },
+        Schema::Opaque(_) => { return Err(TextError::Opaque); },
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+
+    use {
+        crate::*,
+        std::collections::{
+            BTreeMap,
+            HashSet,
+        },
+    };
+
+    #[test]
+    fn test_builtin() {
+        assert_eq!(to_text(&-3i16),Ok("-3".to_string()));
+        assert_eq!(to_text(&0.1f32),Ok("0.1".to_string()));
+        assert_eq!(to_text(&(f64::NAN,f32::INFINITY,-f64::INFINITY)),Ok("[nan, inf, -inf]".to_string()));
+        assert_eq!(to_text(&"a\"b\\\n\u{1}ü".to_string()),Ok("\"a\\\"b\\\\\\n\\u0001ü\"".to_string()));
+        assert_eq!(to_text(&(Some('x'),None::<u8>,())),Ok("[\"x\", null, null]".to_string()));
+        assert_eq!(to_text(&vec![1u8,2]),Ok("[1, 2]".to_string()));
+        let mut map = BTreeMap::<String,Vec<u32>>::new();
+        map.insert("b".to_string(),vec![2,3]);
+        map.insert("a".to_string(),vec![]);
+        assert_eq!(to_text(&map),Ok("{\"a\": [], \"b\": [2, 3]}".to_string()));
+        let mut map = BTreeMap::<u16,bool>::new();
+        map.insert(1,true);
+        assert_eq!(to_text(&map),Ok("[[1, true]]".to_string()));
+        assert_eq!(to_text(&std::time::Duration::new(3,4)),Ok("{\"secs\": 3, \"nanos\": 4}".to_string()));
+
+        assert_eq!(from_text::<f32>("0.1"),Ok(0.1));
+        assert!(from_text::<f64>("nan").unwrap().is_nan());
+        assert_eq!(from_text::<String>(" \"\\u00fc\\ud83d\\ude00\\/\" "),Ok("ü😀/".to_string()));
+        assert_eq!(from_text::<Option<Varint<i32>>>("-300"),Ok(Some(Varint(-300))));
+        assert_eq!(from_text::<BTreeMap<u16,bool>>("[[1, true], [2, false]]").unwrap().len(),2);
+        assert_eq!(from_text::<[char; 2]>("[\"a\",\"b\"]"),Ok(['a','b']));
+    }
+
+    #[test]
+    fn test_nested_option() {
+        assert_eq!(to_text(&Some(())),Ok("[null]".to_string()));
+        assert_eq!(to_text(&None::<()>),Ok("null".to_string()));
+        assert_eq!(to_text(&Some(None::<u8>)),Ok("[null]".to_string()));
+        assert_eq!(to_text(&Some(Some(5u8))),Ok("[5]".to_string()));
+        assert_eq!(to_text(&Some(Some(Some(())))),Ok("[[[null]]]".to_string()));
+        for value in [None,Some(None),Some(Some(None)),Some(Some(Some(())))] {
+            testing::assert_roundtrip(&value);
+            assert_eq!(from_text::<Option<Option<Option<()>>>>(&to_text(&value).unwrap()),Ok(value));
+        }
+        testing::assert_roundtrip(&(Some(()),Checked(Some(None::<String>)),vec![None,Some(None),Some(Some(1u16))]));
+        assert_eq!(from_text::<Option<Option<u8>>>("5"),Err(TextError::Invalid { line: 1, column: 1, message: "expected null or an array of one value".to_string(), }));
+        assert_eq!(from_text::<Option<()>>("[null, null]"),Err(TextError::Invalid { line: 1, column: 1, message: "expected null or an array of one value".to_string(), }));
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(from_text::<u8>("256"),Err(TextError::Invalid { line: 1, column: 1, message: "`256` is not a u8".to_string(), }));
+        assert_eq!(from_text::<Vec<u8>>("[1,\n  \"2\"]"),Err(TextError::Invalid { line: 2, column: 3, message: "expected a u8".to_string(), }));
+        assert_eq!(from_text::<Vec<u8>>("[1 2]"),Err(TextError::Invalid { line: 1, column: 4, message: "unexpected `2`".to_string(), }));
+        assert_eq!(from_text::<Vec<u8>>("[1,"),Err(TextError::Invalid { line: 1, column: 4, message: "unexpected end of text".to_string(), }));
+        assert_eq!(from_text::<bool>("yes"),Err(TextError::Invalid { line: 1, column: 1, message: "unexpected `yes`".to_string(), }));
+        assert_eq!(from_text::<bool>("true false"),Err(TextError::Invalid { line: 1, column: 6, message: "text after the value".to_string(), }));
+        assert_eq!(from_text::<(u8,u8)>("[1]"),Err(TextError::Invalid { line: 1, column: 1, message: "expected an array of 2 items".to_string(), }));
+        assert!(matches!(from_text::<HashSet<u8>>("[1, 1]"),Err(TextError::Decode(DecodeError::DuplicateKey { .. }))));
+    }
+}
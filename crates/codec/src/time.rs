@@ -0,0 +1,289 @@
+// Echidna - Codec
+
+use {
+    crate::*,
+    std::{
+        sync::atomic::{
+            AtomicU64,
+            Ordering,
+        },
+        time::{
+            Duration,
+            SystemTime,
+            UNIX_EPOCH,
+        },
+    },
+};
+
+const NANOS_PER_SEC: u32 = 1_000_000_000;
+
+/// Point in time as nanoseconds since the Unix epoch (1970-01-01 00:00:00
+/// UTC, not counting leap seconds).
+///
+/// This is the time base for sensor data that is shared between
+/// participants, so timestamps from different machines can be compared as
+/// long as their clocks are synchronized. It is encoded as a `u64`, which
+/// covers the years 1970 to 2554.
+#[derive(Clone,Copy,Debug,Default,PartialEq,Eq,PartialOrd,Ord,Hash)]
+pub struct Timestamp(u64);
+
+// Last timestamp handed out by `Timestamp::now`.
+static LAST_NOW: AtomicU64 = AtomicU64::new(0);
+
+impl Timestamp {
+
+    /// Current time.
+    ///
+    /// Never goes backwards within a process: if the system clock is set
+    /// back, `now` keeps returning the latest time it returned before
+    /// until the clock catches up.
+    pub fn now() -> Timestamp {
+        let nanos = Timestamp::from_system_time(SystemTime::now()).map_or(0,|t| t.0);
+        let last = LAST_NOW.fetch_max(nanos,Ordering::Relaxed);
+        Timestamp(last.max(nanos))
+    }
+
+    pub fn from_nanos(nanos: u64) -> Timestamp {
+        Timestamp(nanos)
+    }
+
+    /// Nanoseconds since the Unix epoch.
+    pub fn as_nanos(&self) -> u64 {
+        self.0
+    }
+
+    /// Convert from `SystemTime`, `None` if `time` is before the epoch or
+    /// after 2554.
+    pub fn from_system_time(time: SystemTime) -> Option<Timestamp> {
+        let since = time.duration_since(UNIX_EPOCH).ok()?;
+        if since.as_nanos() > u64::MAX as u128 {
+            return None;
+        }
+        Some(Timestamp(since.as_nanos() as u64))
+    }
+
+    pub fn to_system_time(&self) -> SystemTime {
+        UNIX_EPOCH + Duration::from_nanos(self.0)
+    }
+
+    /// Time elapsed from `earlier` to `self`, `None` if `earlier` is later.
+    pub fn duration_since(&self,earlier: Timestamp) -> Option<Duration> {
+        self.0.checked_sub(earlier.0).map(Duration::from_nanos)
+    }
+}
+
+impl Codec for Timestamp {
+    fn decode_with(buffer: &[u8],ctx: &mut DecodeContext) -> Result<(usize,Self),DecodeError> {
+        let (l,nanos) = u64::decode_with(buffer,ctx)?;
+        Ok((l,Timestamp(nanos)))
+    }
+}
+
+impl Encode for Timestamp {
+    const FIXED_SIZE: Option<usize> = Some(8);
+
+    fn schema() -> Schema {
+        Schema::Struct {
+            name: "Timestamp".to_string(),
+            fields: vec![
+                Field { name: "0".to_string(), schema: Schema::U64, },
+            ],
+        }
+    }
+
+    fn encode(&self,buffer: &mut Vec<u8>) -> usize {
+        self.0.encode(buffer)
+    }
+
+    fn size(&self) -> usize {
+        8
+    }
+}
+
+// Duration is encoded as seconds (u64) and nanoseconds (u32).
+
+impl Codec for Duration {
+    fn decode_with(buffer: &[u8],ctx: &mut DecodeContext) -> Result<(usize,Self),DecodeError> {
+        let (l,secs) = u64::decode_with(buffer,ctx)?;
+        let (nl,nanos) = u32::decode_with(&buffer[l..],ctx).map_err(|e| e.at(l))?;
+        if nanos >= NANOS_PER_SEC {
+            return Err(DecodeError::InvalidTime { offset: l, });
+        }
+        Ok((l + nl,Duration::new(secs,nanos)))
+    }
+}
+
+impl Encode for Duration {
+    const FIXED_SIZE: Option<usize> = Some(12);
+
+    fn schema() -> Schema {
+        Schema::Struct {
+            name: "Duration".to_string(),
+            fields: vec![
+                Field { name: "secs".to_string(), schema: Schema::U64, },
+                Field { name: "nanos".to_string(), schema: Schema::U32, },
+            ],
+        }
+    }
+
+    fn encode(&self,buffer: &mut Vec<u8>) -> usize {
+        self.as_secs().encode(buffer) + self.subsec_nanos().encode(buffer)
+    }
+
+    fn size(&self) -> usize {
+        12
+    }
+}
+
+// SystemTime is encoded as seconds (i64) and nanoseconds (u32) since the
+// Unix epoch. Times before the epoch have negative seconds, the nanoseconds
+// always count forward from there.
+
+impl Codec for SystemTime {
+    fn decode_with(buffer: &[u8],ctx: &mut DecodeContext) -> Result<(usize,Self),DecodeError> {
+        let (l,secs) = i64::decode_with(buffer,ctx)?;
+        let (nl,nanos) = u32::decode_with(&buffer[l..],ctx).map_err(|e| e.at(l))?;
+        if nanos >= NANOS_PER_SEC {
+            return Err(DecodeError::InvalidTime { offset: l, });
+        }
+        let time = if secs >= 0 {
+            UNIX_EPOCH.checked_add(Duration::new(secs as u64,nanos))
+        }
+        else {
+            UNIX_EPOCH.checked_sub(Duration::new(secs.unsigned_abs(),0)).and_then(|t| t.checked_add(Duration::new(0,nanos)))
+        };
+        match time {
+            Some(time) => Ok((l + nl,time)),
+            None => Err(DecodeError::InvalidTime { offset: 0, }),
+        }
+    }
+}
+
+impl Encode for SystemTime {
+    const FIXED_SIZE: Option<usize> = Some(12);
+
+    fn schema() -> Schema {
+        Schema::Struct {
+            name: "SystemTime".to_string(),
+            fields: vec![
+                Field { name: "secs".to_string(), schema: Schema::I64, },
+                Field { name: "nanos".to_string(), schema: Schema::U32, },
+            ],
+        }
+    }
+
+    fn encode(&self,buffer: &mut Vec<u8>) -> usize {
+        let (secs,nanos) = match self.duration_since(UNIX_EPOCH) {
+            Ok(since) => (since.as_secs() as i64,since.subsec_nanos()),
+            Err(e) => {
+
+                // wrapping, because 2^63 seconds before is i64::MIN
+                let before = e.duration();
+                let secs = (before.as_secs() as i64).wrapping_neg();
+                if before.subsec_nanos() == 0 {
+                    (secs,0)
+                }
+                else {
+                    (secs - 1,NANOS_PER_SEC - before.subsec_nanos())
+                }
+            },
+        };
+        secs.encode(buffer) + nanos.encode(buffer)
+    }
+
+    fn size(&self) -> usize {
+        12
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use {
+        crate::*,
+        std::time::{
+            Duration,
+            SystemTime,
+            UNIX_EPOCH,
+        },
+    };
+
+    #[test]
+    fn test_duration() {
+        let source = Duration::new(3,141592653);
+        let mut buffer = Vec::<u8>::new();
+        let len = source.encode(&mut buffer);
+        assert_eq!(len,source.size());
+        assert_eq!(buffer,vec![3,0,0,0,0,0,0,0,0x4D,0x88,0x70,0x08]);
+        if let Ok((_,target)) = Duration::decode(&buffer) {
+            assert_eq!(source,target)
+        }
+        else {
+            panic!("decode failed");
+        }
+    }
+
+    #[test]
+    fn test_system_time() {
+        let sources = vec![
+            SystemTime::now(),
+            UNIX_EPOCH,
+            UNIX_EPOCH - Duration::new(10,0),
+            UNIX_EPOCH - Duration::new(10,250),
+        ];
+        for source in sources {
+            let mut buffer = Vec::<u8>::new();
+            let len = source.encode(&mut buffer);
+            assert_eq!(len,source.size());
+            if let Ok((_,target)) = SystemTime::decode(&buffer) {
+                assert_eq!(source,target)
+            }
+            else {
+                panic!("decode failed");
+            }
+        }
+        let mut buffer = Vec::<u8>::new();
+        (UNIX_EPOCH - Duration::new(0,1)).encode(&mut buffer);
+        assert_eq!(buffer,vec![0xFF,0xFF,0xFF,0xFF,0xFF,0xFF,0xFF,0xFF,0xFF,0xC9,0x9A,0x3B]);
+
+        // the earliest time that can be encoded, if the platform has it
+        let mut buffer = Vec::<u8>::new();
+        i64::MIN.encode(&mut buffer);
+        0u32.encode(&mut buffer);
+        if let Ok((_,earliest)) = SystemTime::decode(&buffer) {
+            let mut target = Vec::<u8>::new();
+            earliest.encode(&mut target);
+            assert_eq!(buffer,target);
+        }
+    }
+
+    #[test]
+    fn test_timestamp() {
+        let first = Timestamp::now();
+        let second = Timestamp::now();
+        assert!(second >= first);
+        assert!(second.duration_since(first).is_some());
+        let source = Timestamp::from_nanos(1_600_000_000_123_456_789);
+        assert_eq!(Timestamp::from_system_time(source.to_system_time()),Some(source));
+        assert_eq!(Timestamp::from_system_time(UNIX_EPOCH - Duration::new(1,0)),None);
+        let mut buffer = Vec::<u8>::new();
+        source.encode(&mut buffer);
+        assert_eq!(buffer,source.as_nanos().to_le_bytes().to_vec());
+        if let Ok((_,target)) = Timestamp::decode(&buffer) {
+            assert_eq!(source,target)
+        }
+        else {
+            panic!("decode failed");
+        }
+    }
+
+    #[test]
+    fn test_time_invalid() {
+        let mut buffer = Vec::<u8>::new();
+        0u64.encode(&mut buffer);
+        1_000_000_000u32.encode(&mut buffer);
+        assert_eq!(Duration::decode(&buffer),Err(DecodeError::InvalidTime { offset: 8, }));
+        assert_eq!(SystemTime::decode(&buffer),Err(DecodeError::InvalidTime { offset: 8, }));
+        assert_eq!(Duration::decode(&buffer[..10]),Err(DecodeError::UnexpectedEof { offset: 8, needed: 4, available: 2, }));
+    }
+}
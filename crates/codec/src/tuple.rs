@@ -0,0 +1,115 @@
+// Echidna - Codec
+
+use {
+    crate::*,
+    std::io,
+};
+
+// The unit type takes no bytes at all. Tuples are encoded as their fields,
+// one after the other.
+
+impl Codec for () {
+    fn decode_with(_buffer: &[u8],_ctx: &mut DecodeContext) -> Result<(usize,Self),DecodeError> {
+        Ok((0,()))
+    }
+}
+
+impl Encode for () {
+    const FIXED_SIZE: Option<usize> = Some(0);
+
+    fn schema() -> Schema {
+        Schema::Unit
+    }
+
+    fn encode(&self,_buffer: &mut Vec<u8>) -> usize {
+        0
+    }
+
+    fn size(&self) -> usize {
+        0
+    }
+}
+
+macro_rules! tuple_codec {
+    ($($ty:ident $field:ident $index:tt),+) => {
+        impl<$($ty: Codec),+> Codec for ($($ty,)+) {
+            fn decode_with(buffer: &[u8],ctx: &mut DecodeContext) -> Result<(usize,Self),DecodeError> {
+                ctx.nested(|ctx| {
+                    let mut ofs = 0usize;
+                    $(
+                        let (l,$field) = $ty::decode_with(&buffer[ofs..],ctx).map_err(|e| e.at(ofs))?;
+                        ofs += l;
+                    )+
+                    Ok((ofs,($($field,)+)))
+                })
+            }
+        }
+
+        impl<$($ty: Encode),+> Encode for ($($ty,)+) {
+            const FIXED_SIZE: Option<usize> = fixed_size_sum(&[$($ty::FIXED_SIZE),+]);
+            const MIN_SIZE: usize = min_size_sum(&[$($ty::MIN_SIZE),+]);
+
+            fn schema() -> Schema {
+                Schema::Tuple(vec![$($ty::schema()),+])
+            }
+
+            fn encode(&self,buffer: &mut Vec<u8>) -> usize {
+                let mut len = 0;
+                $(len += self.$index.encode(buffer);)+
+                len
+            }
+
+            fn size(&self) -> usize {
+                let mut len = 0;
+                $(len += self.$index.size();)+
+                len
+            }
+
+            fn encode_to(&self,sink: &mut Sink<'_>) -> io::Result<usize> {
+                let mut len = 0;
+                $(len += self.$index.encode_to(sink)?;)+
+                Ok(len)
+            }
+        }
+    };
+}
+
+tuple_codec!(A a 0);
+tuple_codec!(A a 0,B b 1);
+tuple_codec!(A a 0,B b 1,C c 2);
+tuple_codec!(A a 0,B b 1,C c 2,D d 3);
+tuple_codec!(A a 0,B b 1,C c 2,D d 3,E e 4);
+tuple_codec!(A a 0,B b 1,C c 2,D d 3,E e 4,F f 5);
+tuple_codec!(A a 0,B b 1,C c 2,D d 3,E e 4,F f 5,G g 6);
+tuple_codec!(A a 0,B b 1,C c 2,D d 3,E e 4,F f 5,G g 6,H h 7);
+tuple_codec!(A a 0,B b 1,C c 2,D d 3,E e 4,F f 5,G g 6,H h 7,I i 8);
+tuple_codec!(A a 0,B b 1,C c 2,D d 3,E e 4,F f 5,G g 6,H h 7,I i 8,J j 9);
+tuple_codec!(A a 0,B b 1,C c 2,D d 3,E e 4,F f 5,G g 6,H h 7,I i 8,J j 9,K k 10);
+tuple_codec!(A a 0,B b 1,C c 2,D d 3,E e 4,F f 5,G g 6,H h 7,I i 8,J j 9,K k 10,L l 11);
+
+#[cfg(test)]
+mod tests {
+
+    use crate::*;
+
+    #[test]
+    fn test_unit() {
+        let mut buffer = Vec::<u8>::new();
+        assert_eq!(().encode(&mut buffer),0);
+        assert!(buffer.is_empty());
+        assert_eq!(<()>::decode(&buffer),Ok((0,())));
+    }
+
+    #[test]
+    fn test_tuple() {
+        let source: (u8,i32,String) = (1,-2,"three".to_string());
+        let mut buffer = Vec::<u8>::new();
+        source.encode(&mut buffer);
+        if let Ok((_,target)) = <(u8,i32,String)>::decode(&buffer) {
+            assert_eq!(source,target)
+        }
+        else {
+            panic!("decode failed");
+        }
+    }
+}
@@ -0,0 +1,94 @@
+// Echidna - Codec
+
+use crate::*;
+
+impl Codec for u128 {
+    fn decode_with(buffer: &[u8],ctx: &mut DecodeContext) -> Result<(usize,Self),DecodeError> {
+        ensure(buffer,16)?;
+        let (_,low) = u64::decode_with(&buffer[0..],ctx)?;
+        let (_,high) = u64::decode_with(&buffer[8..],ctx)?;
+        Ok((16,(low as u128) | ((high as u128) << 64)))
+    }
+
+    bulk_decode!(u128);
+}
+
+impl Encode for u128 {
+    const FIXED_SIZE: Option<usize> = Some(16);
+
+    fn schema() -> Schema {
+        Schema::U128
+    }
+
+    fn encode(&self,buffer: &mut Vec<u8>) -> usize {
+        (*self as u64).encode(buffer);
+        ((*self >> 64) as u64).encode(buffer);
+        16
+    }
+
+    fn size(&self) -> usize {
+        16
+    }
+
+    bulk_encode!(u128);
+}
+
+impl Codec for i128 {
+    fn decode_with(buffer: &[u8],ctx: &mut DecodeContext) -> Result<(usize,Self),DecodeError> {
+        let (l,a) = u128::decode_with(buffer,ctx)?;
+        Ok((l,a as i128))
+    }
+
+    bulk_decode!(i128);
+}
+
+impl Encode for i128 {
+    const FIXED_SIZE: Option<usize> = Some(16);
+
+    fn schema() -> Schema {
+        Schema::I128
+    }
+
+    fn encode(&self,buffer: &mut Vec<u8>) -> usize {
+        (*self as u128).encode(buffer)
+    }
+
+    fn size(&self) -> usize {
+        16
+    }
+
+    bulk_encode!(i128);
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::*;
+
+    #[test]
+    fn test_u128() {
+        let source: u128 = 0x0123456789ABCDEF_FEDCBA9876543210;
+        let mut buffer = Vec::<u8>::new();
+        source.encode(&mut buffer);
+        assert_eq!(buffer[0],0x10);
+        if let Ok((_,target)) = u128::decode(&buffer) {
+            assert_eq!(source,target)
+        }
+        else {
+            panic!("decode failed");
+        }
+    }
+
+    #[test]
+    fn test_i128() {
+        let source: i128 = -170141183460469231731687303715884105728;
+        let mut buffer = Vec::<u8>::new();
+        source.encode(&mut buffer);
+        if let Ok((_,target)) = i128::decode(&buffer) {
+            assert_eq!(source,target)
+        }
+        else {
+            panic!("decode failed");
+        }
+    }
+}
@@ -3,13 +3,24 @@
 use crate::*;
 
 impl Codec for u16 {
-    fn decode(buffer: &[u8]) -> Option<(usize,Self)> {
-        Some((2,
+    fn decode_with(buffer: &[u8],_ctx: &mut DecodeContext) -> Result<(usize,Self),DecodeError> {
+        ensure(buffer,2)?;
+        Ok((2,
             (buffer[0] as u16) |
             ((buffer[1] as u16) << 8)
         ))
     }
 
+    bulk_decode!(u16);
+}
+
+impl Encode for u16 {
+    const FIXED_SIZE: Option<usize> = Some(2);
+
+    fn schema() -> Schema {
+        Schema::U16
+    }
+
     fn encode(&self,buffer: &mut Vec<u8>) -> usize {
         buffer.push(*self as u8);
         buffer.push((self >> 8) as u8);
@@ -19,11 +30,14 @@ impl Codec for u16 {
     fn size(&self) -> usize {
         2
     }
+
+    bulk_encode!(u16);
 }
 
 impl Codec for i16 {
-    fn decode(buffer: &[u8]) -> Option<(usize,Self)> {
-        Some((2,
+    fn decode_with(buffer: &[u8],_ctx: &mut DecodeContext) -> Result<(usize,Self),DecodeError> {
+        ensure(buffer,2)?;
+        Ok((2,
             (
                 (buffer[0] as u16) |
                 ((buffer[1] as u16) << 8)
@@ -31,6 +45,16 @@ impl Codec for i16 {
         ))
     }
 
+    bulk_decode!(i16);
+}
+
+impl Encode for i16 {
+    const FIXED_SIZE: Option<usize> = Some(2);
+
+    fn schema() -> Schema {
+        Schema::I16
+    }
+
     fn encode(&self,buffer: &mut Vec<u8>) -> usize {
         buffer.push(*self as u8);
         buffer.push((self >> 8) as u8);
@@ -40,23 +64,25 @@ impl Codec for i16 {
     fn size(&self) -> usize {
         2
     }
+
+    bulk_encode!(i16);
 }
 
 #[cfg(test)]
 mod tests {
 
-    use crate::Codec;
+    use crate::*;
 
     #[test]
     fn test_u16() {
         let source: u16 = 16384;
         let mut buffer = Vec::<u8>::new();
         source.encode(&mut buffer);
-        if let Some((_,target)) = u16::decode(&buffer) {
+        if let Ok((_,target)) = u16::decode(&buffer) {
             assert_eq!(source,target)
         }
         else {
-            assert!(false)
+            panic!("decode failed");
         }
     }
 
@@ -65,11 +91,11 @@ mod tests {
         let source: i16 = -4096;
         let mut buffer = Vec::<u8>::new();
         source.encode(&mut buffer);
-        if let Some((_,target)) = i16::decode(&buffer) {
+        if let Ok((_,target)) = i16::decode(&buffer) {
             assert_eq!(source,target)
         }
         else {
-            assert!(false)
+            panic!("decode failed");
         }
     }
 }
@@ -3,8 +3,9 @@
 use crate::*;
 
 impl Codec for u32 {
-    fn decode(buffer: &[u8]) -> Option<(usize,Self)> {
-        Some((4,
+    fn decode_with(buffer: &[u8],_ctx: &mut DecodeContext) -> Result<(usize,Self),DecodeError> {
+        ensure(buffer,4)?;
+        Ok((4,
             (buffer[0] as u32) |
             ((buffer[1] as u32) << 8) |
             ((buffer[2] as u32) << 16) |
@@ -12,6 +13,16 @@ impl Codec for u32 {
         ))
     }
 
+    bulk_decode!(u32);
+}
+
+impl Encode for u32 {
+    const FIXED_SIZE: Option<usize> = Some(4);
+
+    fn schema() -> Schema {
+        Schema::U32
+    }
+
     fn encode(&self,buffer: &mut Vec<u8>) -> usize {
         buffer.push(*self as u8);
         buffer.push((*self >> 8) as u8);
@@ -23,11 +34,14 @@ impl Codec for u32 {
     fn size(&self) -> usize {
         4
     }
+
+    bulk_encode!(u32);
 }
 
 impl Codec for i32 {
-    fn decode(buffer: &[u8]) -> Option<(usize,Self)> {
-        Some((4,
+    fn decode_with(buffer: &[u8],_ctx: &mut DecodeContext) -> Result<(usize,Self),DecodeError> {
+        ensure(buffer,4)?;
+        Ok((4,
             (
                 (buffer[0] as u32) |
                 ((buffer[1] as u32) << 8) |
@@ -37,6 +51,16 @@ impl Codec for i32 {
         ))
     }
 
+    bulk_decode!(i32);
+}
+
+impl Encode for i32 {
+    const FIXED_SIZE: Option<usize> = Some(4);
+
+    fn schema() -> Schema {
+        Schema::I32
+    }
+
     fn encode(&self,buffer: &mut Vec<u8>) -> usize {
         buffer.push(*self as u8);
         buffer.push((*self >> 8) as u8);
@@ -48,23 +72,25 @@ impl Codec for i32 {
     fn size(&self) -> usize {
         4
     }
+
+    bulk_encode!(i32);
 }
 
 #[cfg(test)]
 mod tests {
 
-    use crate::Codec;
+    use crate::*;
 
     #[test]
     fn test_u32() {
         let source: u32 = 262144;
         let mut buffer = Vec::<u8>::new();
         source.encode(&mut buffer);
-        if let Some((_,target)) = u32::decode(&buffer) {
+        if let Ok((_,target)) = u32::decode(&buffer) {
             assert_eq!(source,target)
         }
         else {
-            assert!(false)
+            panic!("decode failed");
         }
     }
 
@@ -73,11 +99,11 @@ mod tests {
         let source: i32 = -100000;
         let mut buffer = Vec::<u8>::new();
         source.encode(&mut buffer);
-        if let Some((_,target)) = i32::decode(&buffer) {
+        if let Ok((_,target)) = i32::decode(&buffer) {
             assert_eq!(source,target)
         }
         else {
-            assert!(false)
+            panic!("decode failed");
         }
     }
 }
@@ -3,8 +3,9 @@
 use crate::*;
 
 impl Codec for u64 {
-    fn decode(buffer: &[u8]) -> Option<(usize,Self)> {
-        Some((8,
+    fn decode_with(buffer: &[u8],_ctx: &mut DecodeContext) -> Result<(usize,Self),DecodeError> {
+        ensure(buffer,8)?;
+        Ok((8,
             (buffer[0] as u64) |
             ((buffer[1] as u64) << 8) |
             ((buffer[2] as u64) << 16) |
@@ -16,6 +17,16 @@ impl Codec for u64 {
         ))
     }
 
+    bulk_decode!(u64);
+}
+
+impl Encode for u64 {
+    const FIXED_SIZE: Option<usize> = Some(8);
+
+    fn schema() -> Schema {
+        Schema::U64
+    }
+
     fn encode(&self,buffer: &mut Vec<u8>) -> usize {
         buffer.push(*self as u8);
         buffer.push((*self >> 8) as u8);
@@ -31,11 +42,14 @@ impl Codec for u64 {
     fn size(&self) -> usize {
         8
     }   
+
+    bulk_encode!(u64);
 }
 
 impl Codec for i64 {
-    fn decode(buffer: &[u8]) -> Option<(usize,Self)> {
-        Some((8,
+    fn decode_with(buffer: &[u8],_ctx: &mut DecodeContext) -> Result<(usize,Self),DecodeError> {
+        ensure(buffer,8)?;
+        Ok((8,
             (
                 (buffer[0] as u64) |
                 ((buffer[1] as u64) << 8) |
@@ -49,6 +63,16 @@ impl Codec for i64 {
         ))
     }
 
+    bulk_decode!(i64);
+}
+
+impl Encode for i64 {
+    const FIXED_SIZE: Option<usize> = Some(8);
+
+    fn schema() -> Schema {
+        Schema::I64
+    }
+
     fn encode(&self,buffer: &mut Vec<u8>) -> usize {
         buffer.push(*self as u8);
         buffer.push((*self >> 8) as u8);
@@ -64,23 +88,25 @@ impl Codec for i64 {
     fn size(&self) -> usize {
         8
     }   
+
+    bulk_encode!(i64);
 }
 
 #[cfg(test)]
 mod tests {
 
-    use crate::Codec;
+    use crate::*;
 
     #[test]
     fn test_u64() {
         let source: u64 = 9999999999;
         let mut buffer = Vec::<u8>::new();
         source.encode(&mut buffer);
-        if let Some((_,target)) = u64::decode(&buffer) {
+        if let Ok((_,target)) = u64::decode(&buffer) {
             assert_eq!(source,target)
         }
         else {
-            assert!(false)
+            panic!("decode failed");
         }
     }
 
@@ -89,11 +115,11 @@ mod tests {
         let source: i64 = -1234567890;
         let mut buffer = Vec::<u8>::new();
         source.encode(&mut buffer);
-        if let Some((_,target)) = i64::decode(&buffer) {
+        if let Ok((_,target)) = i64::decode(&buffer) {
             assert_eq!(source,target)
         }
         else {
-            assert!(false)
+            panic!("decode failed");
         }
     }
 }
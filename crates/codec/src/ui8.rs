@@ -3,8 +3,19 @@
 use crate::*;
 
 impl Codec for u8 {
-    fn decode(buffer: &[u8]) -> Option<(usize,Self)> {
-        Some((1,buffer[0]))
+    fn decode_with(buffer: &[u8],_ctx: &mut DecodeContext) -> Result<(usize,Self),DecodeError> {
+        ensure(buffer,1)?;
+        Ok((1,buffer[0]))
+    }
+
+    bulk_decode!(u8);
+}
+
+impl Encode for u8 {
+    const FIXED_SIZE: Option<usize> = Some(1);
+
+    fn schema() -> Schema {
+        Schema::U8
     }
 
     fn encode(&self,buffer: &mut Vec<u8>) -> usize {
@@ -15,11 +26,24 @@ impl Codec for u8 {
     fn size(&self) -> usize {
         1
     }
+
+    bulk_encode!(u8);
 }
 
 impl Codec for i8 {
-    fn decode(buffer: &[u8]) -> Option<(usize,Self)> {
-        Some((1,buffer[0] as i8))
+    fn decode_with(buffer: &[u8],_ctx: &mut DecodeContext) -> Result<(usize,Self),DecodeError> {
+        ensure(buffer,1)?;
+        Ok((1,buffer[0] as i8))
+    }
+
+    bulk_decode!(i8);
+}
+
+impl Encode for i8 {
+    const FIXED_SIZE: Option<usize> = Some(1);
+
+    fn schema() -> Schema {
+        Schema::I8
     }
 
     fn encode(&self,buffer: &mut Vec<u8>) -> usize {
@@ -30,23 +54,25 @@ impl Codec for i8 {
     fn size(&self) -> usize {
         1
     }
+
+    bulk_encode!(i8);
 }
 
 #[cfg(test)]
 mod tests {
 
-    use crate::Codec;
+    use crate::*;
 
     #[test]
     fn test_u8() {
         let source: u8 = 127;
         let mut buffer = Vec::<u8>::new();
         source.encode(&mut buffer);
-        if let Some((_,target)) = u8::decode(&buffer) {
+        if let Ok((_,target)) = u8::decode(&buffer) {
             assert_eq!(source,target)
         }
         else {
-            assert!(false)
+            panic!("decode failed");
         }
     }
 
@@ -55,11 +81,11 @@ mod tests {
         let source: i8 = -100;
         let mut buffer = Vec::<u8>::new();
         source.encode(&mut buffer);
-        if let Some((_,target)) = i8::decode(&buffer) {
+        if let Ok((_,target)) = i8::decode(&buffer) {
             assert_eq!(source,target)
         }
         else {
-            assert!(false)
+            panic!("decode failed");
         }
     }
 }
@@ -0,0 +1,91 @@
+// Echidna - Codec
+
+use crate::*;
+
+// usize and isize are encoded as 64 bits, so both sides agree regardless
+// of their pointer width.
+
+impl Codec for usize {
+    fn decode_with(buffer: &[u8],ctx: &mut DecodeContext) -> Result<(usize,Self),DecodeError> {
+        let (l,a) = u64::decode_with(buffer,ctx)?;
+        if a > usize::MAX as u64 {
+            return Err(DecodeError::LengthOverflow { offset: 0, length: a, });
+        }
+        Ok((l,a as usize))
+    }
+}
+
+impl Encode for usize {
+    const FIXED_SIZE: Option<usize> = Some(8);
+
+    fn schema() -> Schema {
+        Schema::U64
+    }
+
+    fn encode(&self,buffer: &mut Vec<u8>) -> usize {
+        (*self as u64).encode(buffer)
+    }
+
+    fn size(&self) -> usize {
+        8
+    }
+}
+
+impl Codec for isize {
+    fn decode_with(buffer: &[u8],ctx: &mut DecodeContext) -> Result<(usize,Self),DecodeError> {
+        let (l,a) = i64::decode_with(buffer,ctx)?;
+        if (a < isize::MIN as i64) || (a > isize::MAX as i64) {
+            return Err(DecodeError::LengthOverflow { offset: 0, length: a as u64, });
+        }
+        Ok((l,a as isize))
+    }
+}
+
+impl Encode for isize {
+    const FIXED_SIZE: Option<usize> = Some(8);
+
+    fn schema() -> Schema {
+        Schema::I64
+    }
+
+    fn encode(&self,buffer: &mut Vec<u8>) -> usize {
+        (*self as i64).encode(buffer)
+    }
+
+    fn size(&self) -> usize {
+        8
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::*;
+
+    #[test]
+    fn test_usize() {
+        let source: usize = 0x12345678;
+        let mut buffer = Vec::<u8>::new();
+        source.encode(&mut buffer);
+        assert_eq!(buffer.len(),8);
+        if let Ok((_,target)) = usize::decode(&buffer) {
+            assert_eq!(source,target)
+        }
+        else {
+            panic!("decode failed");
+        }
+    }
+
+    #[test]
+    fn test_isize() {
+        let source: isize = -0x12345678;
+        let mut buffer = Vec::<u8>::new();
+        source.encode(&mut buffer);
+        if let Ok((_,target)) = isize::decode(&buffer) {
+            assert_eq!(source,target)
+        }
+        else {
+            panic!("decode failed");
+        }
+    }
+}
@@ -0,0 +1,345 @@
+// Echidna - Codec
+
+use {
+    crate::*,
+    std::fmt,
+};
+
+/// Value of any type that has a `Schema`, for tools that show messages
+/// without knowing their Rust type.
+///
+/// Integers of all widths become `UInt` or `Int`, `f32` and `f64` become
+/// `Float`, and lists or arrays of `u8` become `Bytes`. Tuples become a
+/// `List` of their fields.
+#[derive(Clone,Debug,PartialEq)]
+pub enum Value {
+    Unit,
+    Bool(bool),
+    UInt(u128),
+    Int(i128),
+    Float(f64),
+    Char(char),
+    String(String),
+    Bytes(Vec<u8>),
+    Option(Option<Box<Value>>),
+    List(Vec<Value>),
+    Map(Vec<(Value,Value)>),
+    Struct { name: String, fields: Vec<(String,Value)>, },
+    Variant { name: String, variant: String, fields: Vec<(String,Value)>, },
+}
+
+impl Value {
+
+    /// Decode a value laid out as `schema` from `buffer`.
+    ///
+    /// If successful, returns the number of bytes decoded and the value.
+    /// Uses the default `DecodeLimits`. Fails with `DecodeError::Opaque` at
+    /// parts of the schema that are `Schema::Opaque`.
+    pub fn decode_with(schema: &Schema,buffer: &[u8]) -> Result<(usize,Value),DecodeError> {
+        let mut ctx = DecodeContext::new(DecodeLimits::default());
+        decode_value(schema,buffer,&mut ctx,&mut Vec::new())
+    }
+}
+
+// Decode a struct or enum field by field, `enclosing` holds the structs and
+// enums around it so `Schema::Recursive` can find them.
+fn decode_fields<'s>(fields: &'s [Field],buffer: &[u8],ctx: &mut DecodeContext,enclosing: &mut Vec<&'s Schema>) -> Result<(usize,Vec<(String,Value)>),DecodeError> {
+    let mut ofs = 0usize;
+    let mut r = Vec::<(String,Value)>::new();
+    for field in fields {
+        let (l,value) = decode_value(&field.schema,&buffer[ofs..],ctx,enclosing).map_err(|e| e.at(ofs))?;
+        ofs += l;
+        r.push((field.name.clone(),value));
+    }
+    Ok((ofs,r))
+}
+
+// Decode `count` items, `u8` items as `Bytes`.
+fn decode_list<'s>(item: &'s Schema,count: usize,buffer: &[u8],ctx: &mut DecodeContext,enclosing: &mut Vec<&'s Schema>) -> Result<(usize,Value),DecodeError> {
+    if let Schema::U8 = item {
+        ctx.alloc(count)?;
+        ensure(buffer,count)?;
+        return Ok((count,Value::Bytes(buffer[..count].to_vec())));
+    }
+    ctx.elements(count,std::mem::size_of::<Value>())?;
+    let mut ofs = 0usize;
+    let mut r = Vec::<Value>::new();
+    for _ in 0..count {
+        let (l,value) = decode_value(item,&buffer[ofs..],ctx,enclosing).map_err(|e| e.at(ofs))?;
+        ofs += l;
+        r.push(value);
+    }
+    Ok((ofs,Value::List(r)))
+}
+
+pub(crate) fn decode_value<'s>(schema: &'s Schema,buffer: &[u8],ctx: &mut DecodeContext,enclosing: &mut Vec<&'s Schema>) -> Result<(usize,Value),DecodeError> {
+    match schema {
+        Schema::Unit => Ok((0,Value::Unit)),
+        Schema::Bool => bool::decode_with(buffer,ctx).map(|(l,v)| (l,Value::Bool(v))),
+        Schema::U8 => u8::decode_with(buffer,ctx).map(|(l,v)| (l,Value::UInt(v as u128))),
+        Schema::U16 => u16::decode_with(buffer,ctx).map(|(l,v)| (l,Value::UInt(v as u128))),
+        Schema::U32 => u32::decode_with(buffer,ctx).map(|(l,v)| (l,Value::UInt(v as u128))),
+        Schema::U64 => u64::decode_with(buffer,ctx).map(|(l,v)| (l,Value::UInt(v as u128))),
+        Schema::U128 => u128::decode_with(buffer,ctx).map(|(l,v)| (l,Value::UInt(v))),
+        Schema::I8 => i8::decode_with(buffer,ctx).map(|(l,v)| (l,Value::Int(v as i128))),
+        Schema::I16 => i16::decode_with(buffer,ctx).map(|(l,v)| (l,Value::Int(v as i128))),
+        Schema::I32 => i32::decode_with(buffer,ctx).map(|(l,v)| (l,Value::Int(v as i128))),
+        Schema::I64 => i64::decode_with(buffer,ctx).map(|(l,v)| (l,Value::Int(v as i128))),
+        Schema::I128 => i128::decode_with(buffer,ctx).map(|(l,v)| (l,Value::Int(v))),
+        Schema::F32 => f32::decode_with(buffer,ctx).map(|(l,v)| (l,Value::Float(v as f64))),
+        Schema::F64 => f64::decode_with(buffer,ctx).map(|(l,v)| (l,Value::Float(v))),
+        Schema::Char => char::decode_with(buffer,ctx).map(|(l,v)| (l,Value::Char(v))),
+        Schema::String => String::decode_with(buffer,ctx).map(|(l,v)| (l,Value::String(v))),
+        Schema::Varint { signed: false } => Varint::<u64>::decode_with(buffer,ctx).map(|(l,v)| (l,Value::UInt(v.0 as u128))),
+        Schema::Varint { signed: true } => Varint::<i64>::decode_with(buffer,ctx).map(|(l,v)| (l,Value::Int(v.0 as i128))),
+        Schema::Option(item) => {
+            ensure(buffer,1)?;
+            match buffer[0] {
+                0 => Ok((1,Value::Option(None))),
+                1 => {
+                    let (l,value) = decode_value(item,&buffer[1..],ctx,enclosing).map_err(|e| e.at(1))?;
+                    Ok((1 + l,Value::Option(Some(Box::new(value)))))
+                },
+                tag => Err(DecodeError::InvalidTag { offset: 0, tag: tag as u32, }),
+            }
+        },
+        Schema::List(item) => {
+            let (_,len) = u32::decode_with(buffer,ctx)?;
+            let (l,value) = ctx.nested(|ctx| decode_list(item,len as usize,&buffer[4..],ctx,enclosing)).map_err(|e| e.at(4))?;
+            Ok((4 + l,value))
+        },
+        Schema::Array(item,count) => {
+            ctx.nested(|ctx| decode_list(item,*count,buffer,ctx,enclosing))
+        },
+        Schema::Map(key,value) => {
+            let (_,len) = u32::decode_with(buffer,ctx)?;
+            ctx.nested(|ctx| {
+                ctx.elements(len as usize,2 * std::mem::size_of::<Value>())?;
+                let mut ofs = 4usize;
+                let mut r = Vec::<(Value,Value)>::new();
+                for _ in 0..len {
+                    let (l,k) = decode_value(key,&buffer[ofs..],ctx,enclosing).map_err(|e| e.at(ofs))?;
+                    ofs += l;
+                    let (l,v) = decode_value(value,&buffer[ofs..],ctx,enclosing).map_err(|e| e.at(ofs))?;
+                    ofs += l;
+                    r.push((k,v));
+                }
+                Ok((ofs,Value::Map(r)))
+            })
+        },
+        Schema::Tuple(items) => {
+            ctx.nested(|ctx| {
+                let mut ofs = 0usize;
+                let mut r = Vec::<Value>::new();
+                for item in items {
+                    let (l,value) = decode_value(item,&buffer[ofs..],ctx,enclosing).map_err(|e| e.at(ofs))?;
+                    ofs += l;
+                    r.push(value);
+                }
+                Ok((ofs,Value::List(r)))
+            })
+        },
+        Schema::Struct { name,fields } => {
+            ctx.nested(|ctx| {
+                enclosing.push(schema);
+                let (l,fields) = decode_fields(fields,buffer,ctx,enclosing)?;
+                enclosing.pop();
+                Ok((l,Value::Struct { name: name.clone(), fields, }))
+            })
+        },
+        Schema::Enum { name,tag_size,big_endian,variants } => {
+            ctx.nested(|ctx| {
+                let tag = match (tag_size,big_endian) {
+                    (1,_) => u8::decode_with(buffer,ctx)?.1 as u32,
+                    (2,false) => u16::decode_with(buffer,ctx)?.1 as u32,
+                    (2,true) => BigEndian::<u16>::decode_with(buffer,ctx)?.1.0 as u32,
+                    (_,false) => u32::decode_with(buffer,ctx)?.1,
+                    (_,true) => BigEndian::<u32>::decode_with(buffer,ctx)?.1.0,
+                };
+                let variant = match variants.iter().find(|variant| variant.tag == tag) {
+                    Some(variant) => variant,
+                    None => { return Err(DecodeError::InvalidTag { offset: 0, tag, }); },
+                };
+                enclosing.push(schema);
+                let (l,fields) = decode_fields(&variant.fields,&buffer[*tag_size..],ctx,enclosing).map_err(|e| e.at(*tag_size))?;
+                enclosing.pop();
+                Ok((tag_size + l,Value::Variant { name: name.clone(), variant: variant.name.clone(), fields, }))
+            })
+        },
+        Schema::Versioned(_,_,inner) => {
+            u16::decode_with(buffer,ctx)?;
+            let (_,length) = u32::decode_with(&buffer[2..],ctx).map_err(|e| e.at(2))?;
+            let end = 6usize.checked_add(length as usize).ok_or(DecodeError::LengthOverflow { offset: 2, length: length as u64, })?;
+            ensure(buffer,end)?;
+            let fields = match inner.as_ref() {
+                Schema::Struct { fields,.. } => fields,
+                _ => { return Err(DecodeError::Opaque { offset: 0, }); },
+            };
+
+            // the fields the encoder knows, as far as the schema knows them
+            ctx.nested(|ctx| {
+                enclosing.push(schema);
+                let mut ofs = 6usize;
+                let mut r = Vec::<(String,Value)>::new();
+                for field in fields {
+                    if ofs >= end {
+                        break;
+                    }
+                    let (l,value) = decode_value(&field.schema,&buffer[ofs..end],ctx,enclosing).map_err(|e| versioned_field_error(e,ofs,length))?;
+                    ofs += l;
+                    r.push((field.name.clone(),value));
+                }
+                enclosing.pop();
+                Ok((end,Value::Struct { name: schema_name(inner).to_string(), fields: r, }))
+            })
+        },
+        Schema::BigEndian(inner) => {
+            let size = number_size(inner).ok_or(DecodeError::Opaque { offset: 0, })?;
+            ensure(buffer,size)?;
+            let mut bytes = buffer[..size].to_vec();
+            bytes.reverse();
+            decode_value(inner,&bytes,ctx,enclosing)
+        },
+        Schema::Checked(inner) => {
+            let (l,value) = decode_value(inner,buffer,ctx,enclosing)?;
+            Ok((verify_crc32c(buffer,l)?,value))
+        },
+        Schema::Compressed(inner) => {
+            let (end,raw) = decode_compressed(buffer,ctx)?;
+            let (l,value) = decode_value(inner,&raw,ctx,enclosing).map_err(inner_error)?;
+            if l != raw.len() {
+                return Err(DecodeError::InvalidCompression { offset: 8, });
+            }
+            Ok((end,value))
+        },
+        Schema::Recursive(name) => {
+            let found = enclosing.iter().rev().copied().find(|schema| schema_name(schema) == name);
+            match found {
+                Some(schema) => decode_value(schema,buffer,ctx,enclosing),
+                None => Err(DecodeError::Opaque { offset: 0, }),
+            }
+        },
+        Schema::Opaque(_) => Err(DecodeError::Opaque { offset: 0, }),
+    }
+}
+
+// Name of a struct or enum, as `Schema::Recursive` refers to it.
+pub(crate) fn schema_name(schema: &Schema) -> &str {
+    match schema {
+        Schema::Struct { name,.. } | Schema::Enum { name,.. } => name,
+        Schema::Versioned(_,_,inner) => schema_name(inner),
+        _ => "",
+    }
+}
+
+// `Name`, `Name(a, b)` or `Name { a: 1, b: 2 }`, like Rust source.
+fn fmt_fields(f: &mut fmt::Formatter<'_>,name: &str,fields: &[(String,Value)]) -> fmt::Result {
+    write!(f,"{}",name)?;
+    if fields.is_empty() {
+        return Ok(());
+    }
+    if fields[0].0 == "0" {
+        write!(f,"(")?;
+        for (i,(_,value)) in fields.iter().enumerate() {
+            if i > 0 {
+                write!(f,", ")?;
+            }
+            write!(f,"{}",value)?;
+        }
+        write!(f,")")
+    }
+    else {
+        write!(f," {{ ")?;
+        for (i,(name,value)) in fields.iter().enumerate() {
+            if i > 0 {
+                write!(f,", ")?;
+            }
+            write!(f,"{}: {}",name,value)?;
+        }
+        write!(f," }}")
+    }
+}
+
+fn fmt_list<'a>(f: &mut fmt::Formatter<'_>,items: impl Iterator<Item = &'a dyn fmt::Display>) -> fmt::Result {
+    write!(f,"[")?;
+    for (i,item) in items.enumerate() {
+        if i > 0 {
+            write!(f,", ")?;
+        }
+        write!(f,"{}",item)?;
+    }
+    write!(f,"]")
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self,f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Unit => write!(f,"()"),
+            Value::Bool(value) => write!(f,"{}",value),
+            Value::UInt(value) => write!(f,"{}",value),
+            Value::Int(value) => write!(f,"{}",value),
+            Value::Float(value) => write!(f,"{:?}",value),
+            Value::Char(value) => write!(f,"{:?}",value),
+            Value::String(value) => write!(f,"{:?}",value),
+            Value::Bytes(bytes) => fmt_list(f,bytes.iter().map(|b| b as &dyn fmt::Display)),
+            Value::Option(None) => write!(f,"None"),
+            Value::Option(Some(value)) => write!(f,"Some({})",value),
+            Value::List(items) => fmt_list(f,items.iter().map(|v| v as &dyn fmt::Display)),
+            Value::Map(pairs) => {
+                write!(f,"{{")?;
+                for (i,(key,value)) in pairs.iter().enumerate() {
+                    if i > 0 {
+                        write!(f,", ")?;
+                    }
+                    write!(f,"{}: {}",key,value)?;
+                }
+                write!(f,"}}")
+            },
+            Value::Struct { name,fields } => fmt_fields(f,name,fields),
+            Value::Variant { name,variant,fields } => fmt_fields(f,&format!("{}::{}",name,variant),fields),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use {
+        crate::*,
+        std::collections::BTreeMap,
+    };
+
+    fn decode<T: Encode>(value: &T) -> Value {
+        let mut buffer = Vec::<u8>::new();
+        value.encode(&mut buffer);
+        let (len,value) = Value::decode_with(&T::schema(),&buffer).expect("dynamic decode failed");
+        assert_eq!(len,buffer.len());
+        value
+    }
+
+    #[test]
+    fn test_builtin() {
+        assert_eq!(decode(&-3i16),Value::Int(-3));
+        assert_eq!(decode(&0x1234usize),Value::UInt(0x1234));
+        assert_eq!(decode(&1.5f32),Value::Float(1.5));
+        assert_eq!(decode(&"hi".to_string()),Value::String("hi".to_string()));
+        assert_eq!(decode(&vec![1u8,2,3]),Value::Bytes(vec![1,2,3]));
+        assert_eq!(decode(&Some(Varint(-300i32))),Value::Option(Some(Box::new(Value::Int(-300)))));
+        assert_eq!(decode(&(true,'x')),Value::List(vec![Value::Bool(true),Value::Char('x')]));
+        let mut map = BTreeMap::<u16,Vec<u32>>::new();
+        map.insert(1,vec![2,3]);
+        assert_eq!(decode(&map),Value::Map(vec![(Value::UInt(1),Value::List(vec![Value::UInt(2),Value::UInt(3)]))]));
+    }
+
+    #[test]
+    fn test_display() {
+        let value = decode(&(vec![-1i8,2],Some("a".to_string()),None::<u8>,std::time::Duration::new(3,4)));
+        assert_eq!(value.to_string(),"[[-1, 2], Some(\"a\"), None, Duration { secs: 3, nanos: 4 }]");
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(Value::decode_with(&Schema::List(Box::new(Schema::U32)),&[2,0,0,0,1,0,0,0,2,0]),Err(DecodeError::UnexpectedEof { offset: 8, needed: 4, available: 2, }));
+        assert_eq!(Value::decode_with(&Schema::Tuple(vec![Schema::U8,Schema::Opaque("x".to_string())]),&[1,2]),Err(DecodeError::Opaque { offset: 1, }));
+    }
+}
@@ -0,0 +1,244 @@
+// Echidna - Codec
+
+use crate::*;
+
+/// Integer encoded as LEB128 instead of fixed-width little-endian.
+///
+/// Each byte holds 7 bits of the value, least significant first, with the
+/// top bit set on all bytes but the last. Small values take a single byte,
+/// a `u64` takes at most 10. Signed values are zigzag-mapped first
+/// (0, -1, 1, -2, ... become 0, 1, 2, 3, ...), so small negative values are
+/// short too. Only the shortest encoding of a value decodes.
+#[derive(Clone,Copy,Debug,Default,PartialEq,Eq,PartialOrd,Ord,Hash)]
+pub struct Varint<T>(pub T);
+
+/// Integer type that can be wrapped in `Varint`.
+pub trait VarintInt: Copy + Sized {
+
+    /// Whether the value is zigzag-mapped.
+    const SIGNED: bool;
+
+    /// Map to the unsigned value that goes on the wire.
+    fn to_wire(self) -> u64;
+
+    /// Map back from the wire, `None` if it does not fit.
+    fn from_wire(wire: u64) -> Option<Self>;
+}
+
+impl VarintInt for u16 {
+    const SIGNED: bool = false;
+
+    fn to_wire(self) -> u64 {
+        self as u64
+    }
+
+    fn from_wire(wire: u64) -> Option<Self> {
+        if wire <= u16::MAX as u64 { Some(wire as u16) } else { None }
+    }
+}
+
+impl VarintInt for u32 {
+    const SIGNED: bool = false;
+
+    fn to_wire(self) -> u64 {
+        self as u64
+    }
+
+    fn from_wire(wire: u64) -> Option<Self> {
+        if wire <= u32::MAX as u64 { Some(wire as u32) } else { None }
+    }
+}
+
+impl VarintInt for u64 {
+    const SIGNED: bool = false;
+
+    fn to_wire(self) -> u64 {
+        self
+    }
+
+    fn from_wire(wire: u64) -> Option<Self> {
+        Some(wire)
+    }
+}
+
+impl VarintInt for i16 {
+    const SIGNED: bool = true;
+
+    fn to_wire(self) -> u64 {
+        zigzag(self as i64)
+    }
+
+    fn from_wire(wire: u64) -> Option<Self> {
+        let value = unzigzag(wire);
+        if (value >= i16::MIN as i64) && (value <= i16::MAX as i64) { Some(value as i16) } else { None }
+    }
+}
+
+impl VarintInt for i32 {
+    const SIGNED: bool = true;
+
+    fn to_wire(self) -> u64 {
+        zigzag(self as i64)
+    }
+
+    fn from_wire(wire: u64) -> Option<Self> {
+        let value = unzigzag(wire);
+        if (value >= i32::MIN as i64) && (value <= i32::MAX as i64) { Some(value as i32) } else { None }
+    }
+}
+
+impl VarintInt for i64 {
+    const SIGNED: bool = true;
+
+    fn to_wire(self) -> u64 {
+        zigzag(self)
+    }
+
+    fn from_wire(wire: u64) -> Option<Self> {
+        Some(unzigzag(wire))
+    }
+}
+
+fn zigzag(value: i64) -> u64 {
+    ((value << 1) ^ (value >> 63)) as u64
+}
+
+fn unzigzag(wire: u64) -> i64 {
+    ((wire >> 1) as i64) ^ -((wire & 1) as i64)
+}
+
+/// Number of bytes `wire` takes as LEB128.
+pub fn varint_size(wire: u64) -> usize {
+    let bits = 64 - (wire | 1).leading_zeros() as usize;
+    bits.div_ceil(7)
+}
+
+/// Append `wire` to `buffer` as LEB128, returns the number of bytes.
+pub fn encode_varint(wire: u64,buffer: &mut Vec<u8>) -> usize {
+    let mut rest = wire;
+    let mut len = 1;
+    while rest >= 0x80 {
+        buffer.push((rest as u8) | 0x80);
+        rest >>= 7;
+        len += 1;
+    }
+    buffer.push(rest as u8);
+    len
+}
+
+/// Decode a LEB128 value from the front of `buffer`.
+pub fn decode_varint(buffer: &[u8]) -> Result<(usize,u64),DecodeError> {
+    let mut wire = 0u64;
+    for i in 0..10 {
+        ensure(buffer,i + 1)?;
+        let byte = buffer[i];
+        let bits = (byte & 0x7F) as u64;
+
+        // the 10th byte can only hold the top bit of a u64
+        if (i == 9) && (byte > 1) {
+            return Err(DecodeError::InvalidVarint { offset: 0, });
+        }
+        wire |= bits << (7 * i);
+        if byte & 0x80 == 0 {
+
+            // a trailing zero byte means a longer encoding than needed
+            if (i > 0) && (byte == 0) {
+                return Err(DecodeError::InvalidVarint { offset: 0, });
+            }
+            return Ok((i + 1,wire));
+        }
+    }
+    Err(DecodeError::InvalidVarint { offset: 0, })
+}
+
+impl<T: VarintInt> Codec for Varint<T> {
+    fn decode_with(buffer: &[u8],_ctx: &mut DecodeContext) -> Result<(usize,Self),DecodeError> {
+        let (len,wire) = decode_varint(buffer)?;
+        match T::from_wire(wire) {
+            Some(value) => Ok((len,Varint(value))),
+            None => Err(DecodeError::InvalidVarint { offset: 0, }),
+        }
+    }
+}
+
+impl<T: VarintInt> Encode for Varint<T> {
+    const MIN_SIZE: usize = 1;
+
+    fn schema() -> Schema {
+        Schema::Varint { signed: T::SIGNED, }
+    }
+
+    fn encode(&self,buffer: &mut Vec<u8>) -> usize {
+        encode_varint(self.0.to_wire(),buffer)
+    }
+
+    fn size(&self) -> usize {
+        varint_size(self.0.to_wire())
+    }
+}
+
+impl<T> From<T> for Varint<T> {
+    fn from(value: T) -> Self {
+        Varint(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::*;
+
+    #[test]
+    fn test_varint() {
+        let sources: Vec<u64> = vec![0,1,127,128,300,16383,16384,u32::MAX as u64,u64::MAX];
+        for source in sources {
+            let mut buffer = Vec::<u8>::new();
+            let len = Varint(source).encode(&mut buffer);
+            assert_eq!(len,buffer.len());
+            assert_eq!(len,Varint(source).size());
+            if let Ok((l,target)) = Varint::<u64>::decode(&buffer) {
+                assert_eq!(l,len);
+                assert_eq!(source,target.0);
+            }
+            else {
+                panic!("decode failed");
+            }
+        }
+        let mut buffer = Vec::<u8>::new();
+        Varint(300u32).encode(&mut buffer);
+        assert_eq!(buffer,vec![0xAC,0x02]);
+    }
+
+    #[test]
+    fn test_varint_zigzag() {
+        let sources: Vec<i64> = vec![0,-1,1,-2,2,-64,64,i32::MIN as i64,i64::MIN,i64::MAX];
+        for source in sources {
+            let mut buffer = Vec::<u8>::new();
+            let len = Varint(source).encode(&mut buffer);
+            assert_eq!(len,Varint(source).size());
+            if let Ok((_,target)) = Varint::<i64>::decode(&buffer) {
+                assert_eq!(source,target.0);
+            }
+            else {
+                panic!("decode failed");
+            }
+        }
+        let mut buffer = Vec::<u8>::new();
+        Varint(-1i16).encode(&mut buffer);
+        Varint(1i32).encode(&mut buffer);
+        assert_eq!(buffer,vec![1,2]);
+    }
+
+    #[test]
+    fn test_varint_invalid() {
+        assert_eq!(Varint::<u32>::decode(&[0x80]),Err(DecodeError::UnexpectedEof { offset: 0, needed: 2, available: 1, }));
+        assert_eq!(Varint::<u32>::decode(&[0x80,0x00]),Err(DecodeError::InvalidVarint { offset: 0, }));
+        assert_eq!(Varint::<u16>::decode(&[0x80,0x80,0x04]),Err(DecodeError::InvalidVarint { offset: 0, }));
+        assert_eq!(Varint::<u64>::decode(&[0xFF,0xFF,0xFF,0xFF,0xFF,0xFF,0xFF,0xFF,0xFF,0x02]),Err(DecodeError::InvalidVarint { offset: 0, }));
+        let source: Vec<Varint<u32>> = vec![Varint(1),Varint(1000)];
+        let mut buffer = Vec::<u8>::new();
+        source.encode(&mut buffer);
+        buffer[6] = 0;
+        assert_eq!(Vec::<Varint<u32>>::decode(&buffer),Err(DecodeError::InvalidVarint { offset: 5, }));
+    }
+}
@@ -1,61 +1,103 @@
 // Echidna - Codec
 
-use crate::*;
+use {
+    crate::*,
+    std::io,
+};
 
 impl<T: Codec> Codec for Vec<T> {
-    fn decode(buffer: &[u8]) -> Option<(usize,Self)> {
-        if let Some((_,len)) = u32::decode(buffer) {
-            let mut r = Vec::<T>::new();
-            let mut ofs = 4usize;
-            for _ in 0..len {
-                if let Some((l,item)) = T::decode(&buffer[ofs..]) {
-                    ofs += l;
-                    r.push(item);    
-                }
-                else {
-                    return None;
-                }
-            }
-            Some((ofs,r))
-        }
-        else {
-            None
-        }
+    fn decode_with(buffer: &[u8],ctx: &mut DecodeContext) -> Result<(usize,Self),DecodeError> {
+        let (_,len) = u32::decode_with(buffer,ctx)?;
+        ctx.nested(|ctx| {
+            ctx.elements(len as usize,std::mem::size_of::<T>())?;
+            let (l,r) = T::decode_items(&buffer[4..],len as usize,ctx).map_err(|e| e.at(4))?;
+            Ok((4 + l,r))
+        })
+    }
+}
+
+// A slice encodes the same as a `Vec`.
+impl<T: Encode> Encode for [T] {
+    const MIN_SIZE: usize = 4;
+
+    fn schema() -> Schema {
+        Schema::List(Box::new(T::schema()))
     }
 
     fn encode(&self,buffer: &mut Vec<u8>) -> usize {
-        (self.len() as u32).encode(buffer);
-        let mut len = 4;
-        for item in self {
-            len += item.encode(buffer);
+        if let Some(size) = T::FIXED_SIZE {
+            buffer.reserve(4 + self.len() * size);
         }
-        len
+        (self.len() as u32).encode(buffer);
+        4 + T::encode_slice(self,buffer)
     }
 
     fn size(&self) -> usize {
+        if let Some(size) = T::FIXED_SIZE {
+            return 4 + self.len() * size;
+        }
         let mut len = 4;
         for item in self {
             len += item.size();
         }
         len
     }
+
+    fn encode_to(&self,sink: &mut Sink<'_>) -> io::Result<usize> {
+        let len = (self.len() as u32).encode_to(sink)?;
+        Ok(len + T::encode_slice_to(self,sink)?)
+    }
+}
+
+impl<T: Encode> Encode for Vec<T> {
+    const MIN_SIZE: usize = 4;
+
+    fn schema() -> Schema {
+        Schema::List(Box::new(T::schema()))
+    }
+
+    fn encode(&self,buffer: &mut Vec<u8>) -> usize {
+        self.as_slice().encode(buffer)
+    }
+
+    fn size(&self) -> usize {
+        self.as_slice().size()
+    }
+
+    fn encode_to(&self,sink: &mut Sink<'_>) -> io::Result<usize> {
+        self.as_slice().encode_to(sink)
+    }
 }
 
 #[cfg(test)]
 mod tests {
 
-    use crate::Codec;
+    use crate::*;
 
     #[test]
     fn test_i32_vec() {
         let source: Vec<i32> = vec![-2,-1,0,1,2];
         let mut buffer = Vec::<u8>::new();
         source.encode(&mut buffer);
-        if let Some((_,target)) = Vec::<i32>::decode(&buffer) {
+        if let Ok((_,target)) = Vec::<i32>::decode(&buffer) {
             assert_eq!(source,target)
         }
         else {
-            assert!(false)
+            panic!("decode failed");
+        }
+    }
+
+    #[test]
+    fn test_slice() {
+        let source: &[u16] = &[1,2,3];
+        let mut buffer = Vec::<u8>::new();
+        source.encode(&mut buffer);
+        assert_eq!(buffer.len(),source.size());
+        if let Ok((_,target)) = Vec::<u16>::decode(&buffer) {
+            assert_eq!(source,&target[..])
+        }
+        else {
+            panic!("decode failed");
         }
     }
 }
@@ -0,0 +1,78 @@
+// Echidna - Codec
+
+use {
+    crate::*,
+    std::{
+        collections::VecDeque,
+        io,
+    },
+};
+
+// Same as `Vec`: number of items as u32, followed by the items from front
+// to back.
+
+impl<T: Codec> Codec for VecDeque<T> {
+    fn decode_with(buffer: &[u8],ctx: &mut DecodeContext) -> Result<(usize,Self),DecodeError> {
+        let (l,r) = Vec::<T>::decode_with(buffer,ctx)?;
+        Ok((l,VecDeque::from(r)))
+    }
+}
+
+impl<T: Encode> Encode for VecDeque<T> {
+    const MIN_SIZE: usize = 4;
+
+    fn schema() -> Schema {
+        Schema::List(Box::new(T::schema()))
+    }
+
+    fn encode(&self,buffer: &mut Vec<u8>) -> usize {
+        if let Some(size) = T::FIXED_SIZE {
+            buffer.reserve(4 + self.len() * size);
+        }
+        (self.len() as u32).encode(buffer);
+        let (front,back) = self.as_slices();
+        4 + T::encode_slice(front,buffer) + T::encode_slice(back,buffer)
+    }
+
+    fn size(&self) -> usize {
+        if let Some(size) = T::FIXED_SIZE {
+            return 4 + self.len() * size;
+        }
+        let mut len = 4;
+        for item in self {
+            len += item.size();
+        }
+        len
+    }
+
+    fn encode_to(&self,sink: &mut Sink<'_>) -> io::Result<usize> {
+        let len = (self.len() as u32).encode_to(sink)?;
+        let (front,back) = self.as_slices();
+        Ok(len + T::encode_slice_to(front,sink)? + T::encode_slice_to(back,sink)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use {
+        crate::*,
+        std::collections::VecDeque,
+    };
+
+    #[test]
+    fn test_vecdeque() {
+        let mut source: VecDeque<u32> = VecDeque::new();
+        source.push_back(2);
+        source.push_front(1);
+        let mut buffer = Vec::<u8>::new();
+        source.encode(&mut buffer);
+        assert_eq!(buffer,vec![2,0,0,0,1,0,0,0,2,0,0,0]);
+        if let Ok((_,target)) = VecDeque::<u32>::decode(&buffer) {
+            assert_eq!(source,target)
+        }
+        else {
+            panic!("decode failed");
+        }
+    }
+}
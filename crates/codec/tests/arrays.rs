@@ -0,0 +1,73 @@
+// Echidna - Codec - tests
+
+use codec::{
+    Codec,
+    Encode,
+    testing::assert_roundtrip,
+};
+
+const DISTORTION: usize = 4;
+
+#[derive(Codec,Debug,PartialEq)]
+struct Pose {
+    pos: [f32; 3],
+    rot: [[f32; 3]; 3],
+}
+
+#[derive(Codec,Debug,PartialEq)]
+struct Intrinsics {
+    focal: [f64; 2],
+    distortion: [f64; DISTORTION + 1],
+    flags: [bool; 0x2],
+}
+
+#[derive(Codec,Debug,PartialEq)]
+struct Descriptor<const N: usize> {
+    id: u32,
+    coeffs: [u8; N],
+}
+
+#[derive(Codec,Debug,PartialEq)]
+struct Match(Descriptor<8>,Descriptor<{ 2 * DISTORTION }>);
+
+#[derive(Codec,Debug,PartialEq)]
+enum Landmark {
+    Point([f32; 3]),
+    Plane { normal: [f32; 3], d: f32, },
+}
+
+#[test]
+fn test_arrays() {
+    let pose = Pose { pos: [1.0,2.0,3.0], rot: [[1.0,0.0,0.0],[0.0,1.0,0.0],[0.0,0.0,1.0]], };
+    assert_eq!(pose.size(),48);
+    assert_roundtrip(&pose);
+    let intrinsics = Intrinsics { focal: [500.0,501.0], distortion: [0.1,-0.2,0.0,0.0,0.3], flags: [true,false], };
+    assert_eq!(intrinsics.size(),58);
+    assert_roundtrip(&intrinsics);
+    assert_roundtrip(&Landmark::Point([4.0,5.0,6.0]));
+    assert_roundtrip(&Landmark::Plane { normal: [0.0,0.0,1.0], d: -2.5, });
+}
+
+#[test]
+fn test_const_generics() {
+    let descriptor = Descriptor { id: 7, coeffs: [0xA5u8; 256], };
+    assert_eq!(descriptor.size(),260);
+    assert_roundtrip(&descriptor);
+    let m = Match(Descriptor { id: 1, coeffs: [1; 8], },Descriptor { id: 2, coeffs: [2; 8], });
+    let mut buffer = Vec::<u8>::new();
+    m.encode(&mut buffer);
+    assert_eq!(buffer,vec![1,0,0,0,1,1,1,1,1,1,1,1,2,0,0,0,2,2,2,2,2,2,2,2]);
+    assert_roundtrip(&m);
+}
+
+#[test]
+fn test_fixed_size() {
+    assert_eq!(Pose::FIXED_SIZE,Some(48));
+    assert_eq!(Intrinsics::FIXED_SIZE,Some(58));
+    assert_eq!(Descriptor::<256>::FIXED_SIZE,Some(260));
+    assert_eq!(Match::FIXED_SIZE,Some(24));
+    assert_eq!(Landmark::FIXED_SIZE,None);
+    let poses: Vec<Pose> = (0..10).map(|i| Pose { pos: [i as f32; 3], rot: [[0.0; 3]; 3], }).collect();
+    assert_eq!(poses.size(),484);
+    assert_roundtrip(&poses);
+}
@@ -0,0 +1,166 @@
+// Echidna - Codec - tests
+
+use {
+    codec::{
+        Codec,
+        DecodeError,
+        Encode,
+        testing::assert_roundtrip,
+    },
+    std::cell::Cell,
+};
+
+// A foreign type without `Codec`, encoded as two u16s.
+#[derive(Debug,PartialEq)]
+struct Extent {
+    width: u16,
+    height: u16,
+}
+
+mod extent {
+
+    use {
+        codec::*,
+        super::Extent,
+    };
+
+    pub fn encode(value: &Extent,buffer: &mut Vec<u8>) -> usize {
+        value.width.encode(buffer) + value.height.encode(buffer)
+    }
+
+    pub fn size(_: &Extent) -> usize {
+        4
+    }
+
+    pub fn decode_with(buffer: &[u8],ctx: &mut DecodeContext) -> Result<(usize,Extent),DecodeError> {
+        let (_,width) = u16::decode_with(buffer,ctx)?;
+        let (_,height) = u16::decode_with(&buffer[2..],ctx).map_err(|e| e.at(2))?;
+        Ok((4,Extent { width, height, }))
+    }
+}
+
+fn unknown() -> String {
+    "unknown".to_string()
+}
+
+#[derive(Codec,Debug,PartialEq)]
+struct Frame {
+    id: u32,
+    #[codec(skip)]
+    hits: Cell<u32>,
+    #[codec(with = "extent")]
+    extent: Extent,
+    #[codec(skip,default = "unknown")]
+    source: String,
+}
+
+#[derive(Codec,Debug,PartialEq)]
+struct Scaled(#[codec(with = "extent")] Extent,#[codec(skip)] f32,u8);
+
+#[derive(Codec,Debug,PartialEq)]
+enum Command {
+    #[codec(tag = 10)]
+    Stop,
+    Start { speed: u16, #[codec(skip)] retries: u8, },
+    #[codec(tag = 0x20)]
+    Resize(#[codec(with = "extent")] Extent),
+    Reset(#[codec(skip)] u64),
+}
+
+#[derive(Codec,Debug,PartialEq)]
+enum Mode {
+    Idle = 1,
+    Run = 5,
+    Walk,
+    #[codec(tag = 2)]
+    Stop = 0x40,
+}
+
+#[derive(Codec,Debug,PartialEq)]
+#[codec(tag_type = "u8")]
+enum Status {
+    Ok,
+    Failed(String),
+    #[codec(tag = 255)]
+    Unknown,
+}
+
+#[derive(Codec,Debug,PartialEq)]
+#[codec(tag_type = "u16")]
+enum Level {
+    Low = 300,
+    High,
+}
+
+#[test]
+fn test_skip_with() {
+    assert_roundtrip(&Frame { id: 3, hits: Cell::new(0), extent: Extent { width: 640, height: 480, }, source: unknown(), });
+    let frame = Frame { id: 3, hits: Cell::new(12), extent: Extent { width: 640, height: 480, }, source: "camera".to_string(), };
+    let mut buffer = Vec::<u8>::new();
+    frame.encode(&mut buffer);
+    assert_eq!(buffer,vec![3,0,0,0,0x80,2,0xE0,1]);
+    assert_eq!(frame.size(),8);
+    let (l,target) = Frame::decode(&buffer).unwrap();
+    assert_eq!(l,8);
+    assert_eq!(target.hits.get(),0);
+    assert_eq!(target.source,"unknown");
+    assert_eq!(target.extent,frame.extent);
+    assert_roundtrip(&Scaled(Extent { width: 1, height: 2, },0.0,7));
+    assert_eq!(Scaled(Extent { width: 1, height: 2, },3.5,7).size(),5);
+    assert_eq!(Frame::decode(&buffer[..6]),Err(DecodeError::UnexpectedEof { offset: 6, needed: 2, available: 0, }));
+}
+
+#[test]
+fn test_tags() {
+    let mut buffer = Vec::<u8>::new();
+    Command::Stop.encode(&mut buffer);
+    Command::Start { speed: 5, retries: 3, }.encode(&mut buffer);
+    Command::Resize(Extent { width: 1, height: 2, }).encode(&mut buffer);
+    Command::Reset(9).encode(&mut buffer);
+    assert_eq!(buffer,vec![10,0,0,0,11,0,0,0,5,0,0x20,0,0,0,1,0,2,0,0x21,0,0,0]);
+    assert_roundtrip(&Command::Stop);
+    assert_roundtrip(&Command::Start { speed: 5, retries: 0, });
+    assert_roundtrip(&Command::Resize(Extent { width: 1, height: 2, }));
+    assert_roundtrip(&Command::Reset(0));
+    assert_eq!(Command::decode(&[0,0,0,0]),Err(DecodeError::InvalidTag { offset: 0, tag: 0, }));
+}
+
+#[test]
+fn test_discriminants() {
+    let mut buffer = Vec::<u8>::new();
+    Mode::Idle.encode(&mut buffer);
+    Mode::Run.encode(&mut buffer);
+    Mode::Walk.encode(&mut buffer);
+    Mode::Stop.encode(&mut buffer);
+    assert_eq!(buffer,vec![1,0,0,0,5,0,0,0,6,0,0,0,2,0,0,0]);
+    assert_roundtrip(&Mode::Idle);
+    assert_roundtrip(&Mode::Walk);
+    assert_roundtrip(&Mode::Stop);
+    assert_eq!(Mode::decode(&[0x40,0,0,0]),Err(DecodeError::InvalidTag { offset: 0, tag: 0x40, }));
+}
+
+#[test]
+fn test_tag_type() {
+    let mut buffer = Vec::<u8>::new();
+    Status::Ok.encode(&mut buffer);
+    Status::Failed("x".to_string()).encode(&mut buffer);
+    Status::Unknown.encode(&mut buffer);
+    Level::High.encode(&mut buffer);
+    assert_eq!(buffer,vec![0,1,1,0,0,0,b'x',255,0x2D,0x01]);
+    assert_eq!(Status::Ok.size(),1);
+    assert_eq!(Level::Low.size(),2);
+    assert_eq!(Level::FIXED_SIZE,Some(2));
+    assert_eq!(Mode::FIXED_SIZE,Some(4));
+    assert_eq!(Status::FIXED_SIZE,None);
+    assert_eq!(Scaled::FIXED_SIZE,None);
+    assert_eq!(Status::MIN_SIZE,1);
+    assert_eq!(Command::MIN_SIZE,4);
+    assert_eq!(Scaled::MIN_SIZE,1);
+    assert_roundtrip(&Status::Ok);
+    assert_roundtrip(&Status::Failed("broken".to_string()));
+    assert_roundtrip(&Status::Unknown);
+    assert_roundtrip(&Level::Low);
+    assert_roundtrip(&Level::High);
+    assert_eq!(Status::decode(&[2]),Err(DecodeError::InvalidTag { offset: 0, tag: 2, }));
+    assert_eq!(Level::decode(&[0x2C]),Err(DecodeError::UnexpectedEof { offset: 0, needed: 2, available: 1, }));
+}
@@ -0,0 +1,93 @@
+// Echidna - Codec - tests
+
+use {
+    codec::{
+        Codec,
+        BorrowDecode,
+        Encode,
+    },
+    std::borrow::Cow,
+};
+
+#[derive(Codec)]
+pub struct Frame<'a> {
+    id: u64,
+    name: &'a str,
+    label: Cow<'a,str>,
+    data: &'a [u8],
+}
+
+#[derive(Codec)]
+pub struct Owned {
+    id: u64,
+    name: String,
+    label: String,
+    data: Vec<u8>,
+}
+
+#[derive(Codec)]
+pub enum Message<'a> {
+    Empty,
+    Frame(Frame<'a>),
+    Named { name: &'a str, },
+}
+
+#[test]
+fn test_borrow() {
+    let source = Frame {
+        id: 42,
+        name: "camera",
+        label: Cow::Owned("front".to_string()),
+        data: &[1,2,3,4,5],
+    };
+    let mut buffer = Vec::<u8>::new();
+    let size = source.encode(&mut buffer);
+    assert_eq!(size,buffer.len());
+    assert_eq!(size,source.size());
+
+    if let Ok((_,target)) = Frame::borrow_decode(&buffer) {
+        assert_eq!(target.id,42);
+        assert_eq!(target.name,"camera");
+        assert_eq!(target.label,"front");
+        assert!(matches!(target.label,Cow::Borrowed(_)));
+        assert_eq!(target.data,&[1,2,3,4,5]);
+        assert_eq!(target.data.as_ptr(),buffer[buffer.len() - 5..].as_ptr());
+    }
+    else {
+        panic!("decode failed");
+    }
+
+    // borrowed and owned versions are wire-compatible
+    if let Ok((_,target)) = Owned::decode(&buffer) {
+        assert_eq!(target.id,42);
+        assert_eq!(target.name,"camera");
+        assert_eq!(target.label,"front");
+        assert_eq!(target.data,vec![1,2,3,4,5]);
+    }
+    else {
+        panic!("decode failed");
+    }
+}
+
+#[test]
+fn test_borrow_enum() {
+    let source = Message::Named { name: "lidar", };
+    let mut buffer = Vec::<u8>::new();
+    source.encode(&mut buffer);
+    if let Ok((_,Message::Named { name })) = Message::borrow_decode(&buffer) {
+        assert_eq!(name,"lidar");
+    }
+    else {
+        panic!("decode failed");
+    }
+    let source = Message::Frame(Frame {
+        id: 1,
+        name: "",
+        label: Cow::Borrowed(""),
+        data: &[],
+    });
+    let mut buffer = Vec::<u8>::new();
+    source.encode(&mut buffer);
+    assert!(matches!(Message::borrow_decode(&buffer),Ok((_,Message::Frame(_)))));
+    assert!(matches!(Message::borrow_decode(&[0,0,0,0]),Ok((4,Message::Empty))));
+}
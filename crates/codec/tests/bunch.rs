@@ -3,7 +3,10 @@
 //mod dump;
 //use dump::*;
 
-use codec::Codec;
+use codec::{
+    Codec,
+    Encode,
+};
 
 #[derive(Codec)]
 pub struct PublisherId {
@@ -28,12 +31,11 @@ fn test_bunch() {
 
     //dump(&buffer);
 
-    if let Some((_,target)) = PublisherId::decode(&buffer) {
+    if let Ok((_,target)) = PublisherId::decode(&buffer) {
         println!("{}.{}.{}.{}:{}",target.a,target.b,target.c,target.d,target.port);
-        assert!(true);
     }
     else {
-        assert!(false);
+        panic!("decode failed");
     }
 }
 
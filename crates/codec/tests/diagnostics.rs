@@ -0,0 +1,9 @@
+// Echidna - Codec - tests
+
+// Every file in tests/ui should fail to compile with the error in the
+// matching .stderr file. Run with `TRYBUILD=overwrite` to update them.
+#[test]
+fn test_diagnostics() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/ui/*.rs");
+}
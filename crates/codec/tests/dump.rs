@@ -7,7 +7,7 @@ pub fn dump(buffer: &[u8]) {
         for k in 0..16 {
             print!(" {:02X}",buffer[ofs + k]);
         }
-        print!("\n");
+        println!();
         ofs += 16;
     }
     let rem = buffer.len() - ofs;
@@ -16,6 +16,6 @@ pub fn dump(buffer: &[u8]) {
         for k in 0..rem {
             print!(" {:02X}",buffer[ofs + k]);
         }
-        print!("\n");
+        println!();
     }
 }
@@ -0,0 +1,118 @@
+// Echidna - Codec - tests
+
+use codec::{
+    BigEndian,
+    Codec,
+    Encode,
+    Field,
+    Schema,
+    from_text,
+    to_text,
+    testing::{
+        assert_random_roundtrips,
+        assert_roundtrip,
+    },
+};
+
+// Register read from a PLC gateway, all numbers big-endian.
+#[derive(Codec,Debug,PartialEq)]
+#[codec(big_endian)]
+struct Register {
+    station: u8,
+    address: u16,
+    value: i32,
+    scale: f32,
+    flags: [u8; 2],
+    #[codec(skip)]
+    polled: u64,
+}
+
+// Only some fields big-endian.
+#[derive(Codec,Debug,PartialEq)]
+struct Reading(#[codec(big_endian)] u16,u16,Option<BigEndian<u32>>);
+
+// Numbers spelled out in full, and fields without numbers in them.
+#[derive(Codec,Debug,PartialEq)]
+#[codec(big_endian)]
+struct Frame {
+    length: std::primitive::u16,
+    valid: Option<bool>,
+    samples: [BigEndian<u32>; 2],
+}
+
+#[derive(Codec,Debug,PartialEq)]
+#[codec(big_endian,tag_type = "u8")]
+enum Command {
+    Read { address: u16, count: u16, },
+    Write(u16,i64),
+    Stop,
+}
+
+#[derive(Codec,Debug,PartialEq)]
+#[codec(big_endian,tag_type = "u16")]
+enum Reply {
+    Value(u16),
+    #[codec(tag = 0x0102)]
+    Fault,
+}
+
+#[test]
+fn test_container() {
+    let register = Register { station: 3, address: 0x1234, value: -2, scale: 0.5, flags: [1,2], polled: 0, };
+    assert_roundtrip(&register);
+    let mut buffer = Vec::<u8>::new();
+    register.encode(&mut buffer);
+    assert_eq!(buffer,vec![3,0x12,0x34,0xFF,0xFF,0xFF,0xFE,0x3F,0x00,0x00,0x00,1,2]);
+    assert_eq!(Register::FIXED_SIZE,Some(13));
+    buffer.clear();
+    Command::Read { address: 0x0102, count: 8, }.encode(&mut buffer);
+    assert_eq!(buffer,vec![0,0x01,0x02,0x00,0x08]);
+    buffer.clear();
+    Command::Write(1,-1).encode(&mut buffer);
+    assert_eq!(buffer,vec![1,0x00,0x01,0xFF,0xFF,0xFF,0xFF,0xFF,0xFF,0xFF,0xFF]);
+    assert_roundtrip(&Command::Stop);
+    buffer.clear();
+    Reply::Value(3).encode(&mut buffer);
+    assert_eq!(buffer,vec![0x00,0x00,0x00,0x03]);
+    buffer.clear();
+    Reply::Fault.encode(&mut buffer);
+    assert_eq!(buffer,vec![0x01,0x02]);
+    assert_roundtrip(&Reply::Fault);
+    assert_roundtrip(&Reply::Value(0xABCD));
+    let frame = Frame { length: 0x0102, valid: Some(true), samples: [BigEndian(7),BigEndian(0x01020304)], };
+    assert_roundtrip(&frame);
+    buffer.clear();
+    frame.encode(&mut buffer);
+    assert_eq!(buffer,vec![0x01,0x02,1,1,0,0,0,7,0x01,0x02,0x03,0x04]);
+}
+
+#[test]
+fn test_fields() {
+    let mut buffer = Vec::<u8>::new();
+    Reading(0x0102,0x0304,Some(BigEndian(5))).encode(&mut buffer);
+    assert_eq!(buffer,vec![0x01,0x02,0x04,0x03,1,0,0,0,5]);
+    assert_eq!(Reading::FIXED_SIZE,None);
+    assert_roundtrip(&Reading(1,2,None));
+    assert_random_roundtrips::<(BigEndian<u128>,BigEndian<i16>,BigEndian<f64>)>(100);
+    assert_random_roundtrips::<Vec<BigEndian<u32>>>(50);
+}
+
+#[test]
+fn test_schema() {
+    assert_eq!(Reading::schema(),Schema::Struct {
+        name: "Reading".to_string(),
+        fields: vec![
+            Field { name: "0".to_string(), schema: Schema::BigEndian(Box::new(Schema::U16)), },
+            Field { name: "1".to_string(), schema: Schema::U16, },
+            Field { name: "2".to_string(), schema: Schema::Option(Box::new(Schema::BigEndian(Box::new(Schema::U32)))), },
+        ],
+    });
+    let text = to_text(&Command::Read { address: 0x0102, count: 8, }).unwrap();
+    assert_eq!(text,"{\"Read\": {\"address\": 258, \"count\": 8}}");
+    assert_eq!(from_text::<Command>(&text),Ok(Command::Read { address: 0x0102, count: 8, }));
+    assert!(matches!(Reply::schema(),Schema::Enum { tag_size: 2, big_endian: true, .. }));
+    let text = to_text(&Reply::Fault).unwrap();
+    assert_eq!(text,"\"Fault\"");
+    assert_eq!(from_text::<Reply>(&text),Ok(Reply::Fault));
+    assert_eq!(from_text::<Reply>("{\"Value\": [3]}"),Ok(Reply::Value(3)));
+}
@@ -3,7 +3,10 @@
 //mod dump;
 //use dump::*;
 
-use codec::Codec;
+use codec::{
+    Codec,
+    Encode,
+};
 
 #[derive(Codec)]
 enum MyEnum {
@@ -11,7 +14,7 @@ enum MyEnum {
     Two,
     Three(f32),
     Four(f32,f32),
-    Five { foo: u32, bar: f64, },
+    Five { num: u32, bar: f64, },
 }
 
 fn dump_myenum(e: &MyEnum) {
@@ -20,7 +23,7 @@ fn dump_myenum(e: &MyEnum) {
         MyEnum::Two => println!("MyEnum::Two"),
         MyEnum::Three(x) => println!("MyEnum::Three({})",x),
         MyEnum::Four(x,y) => println!("MyEnum::Four({},{})",x,y),
-        MyEnum::Five { foo,bar } => println!("MyEnum::Five {{ foo: {}, bar: {}, }}",foo,bar),
+        MyEnum::Five { num,bar } => println!("MyEnum::Five {{ num: {}, bar: {}, }}",num,bar),
     }
 }
 
@@ -32,11 +35,11 @@ fn test_enum() {
         let mut buffer = Vec::<u8>::new();
         source.encode(&mut buffer);
         //dump(&buffer);
-        if let Some((_,target)) = MyEnum::decode(&buffer) {
+        if let Ok((_,target)) = MyEnum::decode(&buffer) {
             dump_myenum(&target);
         }
         else {
-            assert!(false);
+            panic!("decode failed");
         }
     }
 
@@ -45,11 +48,11 @@ fn test_enum() {
         let mut buffer = Vec::<u8>::new();
         source.encode(&mut buffer);
         //dump(&buffer);
-        if let Some((_,target)) = MyEnum::decode(&buffer) {
+        if let Ok((_,target)) = MyEnum::decode(&buffer) {
             dump_myenum(&target);
         }
         else {
-            assert!(false);
+            panic!("decode failed");
         }
     }
 
@@ -58,11 +61,11 @@ fn test_enum() {
         let mut buffer = Vec::<u8>::new();
         source.encode(&mut buffer);
         //dump(&buffer);
-        if let Some((_,target)) = MyEnum::decode(&buffer) {
+        if let Ok((_,target)) = MyEnum::decode(&buffer) {
             dump_myenum(&target);
         }
         else {
-            assert!(false);
+            panic!("decode failed");
         }
     }
 
@@ -71,26 +74,25 @@ fn test_enum() {
         let mut buffer = Vec::<u8>::new();
         source.encode(&mut buffer);
         //dump(&buffer);
-        if let Some((_,target)) = MyEnum::decode(&buffer) {
+        if let Ok((_,target)) = MyEnum::decode(&buffer) {
             dump_myenum(&target);
         }
         else {
-            assert!(false);
+            panic!("decode failed");
         }
     }
 
     {
-        let source = MyEnum::Five { foo: 9, bar: -19.0, };
+        let source = MyEnum::Five { num: 9, bar: -19.0, };
         let mut buffer = Vec::<u8>::new();
         source.encode(&mut buffer);
         //dump(&buffer);
-        if let Some((_,target)) = MyEnum::decode(&buffer) {
+        if let Ok((_,target)) = MyEnum::decode(&buffer) {
             dump_myenum(&target);
         }
         else {
-            assert!(false);
+            panic!("decode failed");
         }
     }
 
-    assert!(true);
 }
@@ -0,0 +1,60 @@
+// Echidna - Codec - tests
+
+use {
+    codec::{
+        BorrowDecode,
+        Codec,
+        Encode,
+        testing::assert_roundtrip,
+    },
+    std::fmt::Debug,
+};
+
+#[derive(Codec,Debug,PartialEq)]
+struct Stamped<T> {
+    ts: u64,
+    value: T,
+}
+
+#[derive(Codec,Debug,PartialEq)]
+struct Pair<A: Clone + Debug,B = u32>(A,B) where A: PartialEq;
+
+#[derive(Codec,Debug,PartialEq)]
+enum Tree<T> where T: Ord {
+    Leaf(T),
+    Node { left: Box<Tree<T>>, right: Box<Tree<T>>, },
+}
+
+#[derive(Codec,Debug,PartialEq)]
+struct Tagged<'a,'b:'a,T> where T:Copy + 'b {
+    tag: &'a str,
+    note: &'b str,
+    value: T,
+}
+
+#[test]
+fn test_generics() {
+    assert_roundtrip(&Stamped { ts: 1, value: "camera".to_string(), });
+    assert_roundtrip(&Stamped { ts: 2, value: Stamped { ts: 3, value: vec![1u8,2,3], }, });
+    assert_roundtrip(&Pair::<String>("left".to_string(),5));
+    assert_roundtrip(&Pair(1.5f32,(2u8,'x')));
+    assert_roundtrip(&Tree::Node {
+        left: Box::new(Tree::Leaf(1)),
+        right: Box::new(Tree::Node { left: Box::new(Tree::Leaf(2)), right: Box::new(Tree::Leaf(3)), }),
+    });
+}
+
+#[test]
+fn test_generics_borrowed() {
+    let source = Tagged { tag: "front", note: "left", value: 7u16, };
+    let mut buffer = Vec::<u8>::new();
+    source.encode(&mut buffer);
+    assert_eq!(buffer.len(),source.size());
+    if let Ok((l,target)) = Tagged::<u16>::borrow_decode(&buffer) {
+        assert_eq!(l,buffer.len());
+        assert_eq!(source,target);
+    }
+    else {
+        panic!("decode failed");
+    }
+}
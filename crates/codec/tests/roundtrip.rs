@@ -0,0 +1,70 @@
+// Echidna - Codec - tests
+
+use {
+    codec::{
+        Codec,
+        testing::{
+            assert_random_roundtrips,
+            assert_roundtrip,
+            Random,
+            Rng,
+        },
+    },
+    std::{
+        collections::HashMap,
+        net::SocketAddr,
+    },
+};
+
+#[derive(Codec,Debug,PartialEq)]
+struct Header {
+    id: u64,
+    name: String,
+    peers: HashMap<u32,SocketAddr>,
+}
+
+#[derive(Codec,Debug,PartialEq)]
+struct Point(f32,f32,Option<char>);
+
+#[derive(Codec,Debug,PartialEq)]
+enum Shape {
+    Empty,
+    Line(Point,Point),
+    Polygon { header: Header, points: Vec<Point>, },
+}
+
+impl Random for Header {
+    fn random(rng: &mut Rng) -> Self {
+        Header {
+            id: Random::random(rng),
+            name: Random::random(rng),
+            peers: Random::random(rng),
+        }
+    }
+}
+
+impl Random for Point {
+    fn random(rng: &mut Rng) -> Self {
+        Point(Random::random(rng),Random::random(rng),Random::random(rng))
+    }
+}
+
+impl Random for Shape {
+    fn random(rng: &mut Rng) -> Self {
+        match rng.below(3) {
+            0 => Shape::Empty,
+            1 => Shape::Line(Random::random(rng),Random::random(rng)),
+            _ => Shape::Polygon { header: Random::random(rng), points: Random::random(rng), },
+        }
+    }
+}
+
+#[test]
+fn test_roundtrip_derived() {
+    assert_roundtrip(&Shape::Empty);
+    assert_roundtrip(&Point(1.0,-1.0,Some('x')));
+    assert_random_roundtrips::<Header>(200);
+    assert_random_roundtrips::<Point>(200);
+    assert_random_roundtrips::<Shape>(200);
+    assert_random_roundtrips::<Vec<Shape>>(50);
+}
@@ -0,0 +1,195 @@
+// Echidna - Codec - tests
+
+use codec::{
+    Codec,
+    DecodeError,
+    Encode,
+    Field,
+    Schema,
+    Value,
+    Variant,
+    Varint,
+};
+
+#[derive(Codec,Debug,PartialEq)]
+struct Reading {
+    sensor: String,
+    #[codec(skip)]
+    cached: bool,
+    samples: Vec<f32>,
+    seq: Varint<u32>,
+}
+
+#[derive(Codec,Debug,PartialEq)]
+struct Pair<T>(T,T);
+
+#[derive(Codec,Debug,PartialEq)]
+#[codec(tag_type = "u8")]
+enum Event {
+    Started,
+    #[codec(tag = 7)]
+    Moved { x: i16, y: i16, },
+    Stamped(Pair<u8>,Option<Box<Event>>),
+}
+
+#[derive(Codec,Debug,PartialEq)]
+struct Node {
+    value: u32,
+    children: Vec<Node>,
+}
+
+#[derive(Codec,Debug,PartialEq)]
+struct Wrapped {
+    id: u8,
+    #[codec(with = "raw")]
+    raw: u16,
+}
+
+mod raw {
+
+    use codec::*;
+
+    pub fn encode(value: &u16,buffer: &mut Vec<u8>) -> usize {
+        value.encode(buffer)
+    }
+
+    pub fn size(_: &u16) -> usize {
+        2
+    }
+
+    pub fn decode_with(buffer: &[u8],ctx: &mut DecodeContext) -> Result<(usize,u16),DecodeError> {
+        u16::decode_with(buffer,ctx)
+    }
+}
+
+fn decode<T: Encode>(value: &T) -> Value {
+    let mut buffer = Vec::<u8>::new();
+    value.encode(&mut buffer);
+    let (len,value) = Value::decode_with(&T::schema(),&buffer).expect("dynamic decode failed");
+    assert_eq!(len,buffer.len());
+    value
+}
+
+#[test]
+fn test_schema() {
+    assert_eq!(Reading::schema(),Schema::Struct {
+        name: "Reading".to_string(),
+        fields: vec![
+            Field { name: "sensor".to_string(), schema: Schema::String, },
+            Field { name: "samples".to_string(), schema: Schema::List(Box::new(Schema::F32)), },
+            Field { name: "seq".to_string(), schema: Schema::Varint { signed: false, }, },
+        ],
+    });
+    let pair = Schema::Struct {
+        name: "Pair".to_string(),
+        fields: vec![
+            Field { name: "0".to_string(), schema: Schema::U8, },
+            Field { name: "1".to_string(), schema: Schema::U8, },
+        ],
+    };
+    assert_eq!(Event::schema(),Schema::Enum {
+        name: "Event".to_string(),
+        tag_size: 1,
+        big_endian: false,
+        variants: vec![
+            Variant { name: "Started".to_string(), tag: 0, fields: vec![], },
+            Variant { name: "Moved".to_string(), tag: 7, fields: vec![
+                Field { name: "x".to_string(), schema: Schema::I16, },
+                Field { name: "y".to_string(), schema: Schema::I16, },
+            ], },
+            Variant { name: "Stamped".to_string(), tag: 8, fields: vec![
+                Field { name: "0".to_string(), schema: pair, },
+                Field { name: "1".to_string(), schema: Schema::Option(Box::new(Schema::Recursive("Event".to_string()))), },
+            ], },
+        ],
+    });
+}
+
+#[test]
+fn test_value() {
+    let reading = Reading { sensor: "lidar".to_string(), cached: true, samples: vec![0.5,-1.0], seq: Varint(300), };
+    assert_eq!(decode(&reading),Value::Struct {
+        name: "Reading".to_string(),
+        fields: vec![
+            ("sensor".to_string(),Value::String("lidar".to_string())),
+            ("samples".to_string(),Value::List(vec![Value::Float(0.5),Value::Float(-1.0)])),
+            ("seq".to_string(),Value::UInt(300)),
+        ],
+    });
+    assert_eq!(decode(&reading).to_string(),"Reading { sensor: \"lidar\", samples: [0.5, -1.0], seq: 300 }");
+    let event = Event::Stamped(Pair(1,2),Some(Box::new(Event::Moved { x: -3, y: 4, })));
+    assert_eq!(decode(&event).to_string(),"Event::Stamped(Pair(1, 2), Some(Event::Moved { x: -3, y: 4 }))");
+    assert_eq!(decode(&Event::Started).to_string(),"Event::Started");
+}
+
+#[test]
+fn test_recursive() {
+    let tree = Node { value: 1, children: vec![Node { value: 2, children: vec![], },Node { value: 3, children: vec![Node { value: 4, children: vec![], }], }], };
+    assert_eq!(decode(&tree).to_string(),"Node { value: 1, children: [Node { value: 2, children: [] }, Node { value: 3, children: [Node { value: 4, children: [] }] }] }");
+}
+
+#[test]
+fn test_opaque() {
+    let wrapped = Wrapped { id: 1, raw: 2, };
+    let mut buffer = Vec::<u8>::new();
+    wrapped.encode(&mut buffer);
+    assert_eq!(Value::decode_with(&Wrapped::schema(),&buffer),Err(DecodeError::Opaque { offset: 1, }));
+}
+
+// Same layout as `Node`, with other names.
+#[derive(Codec,Debug,PartialEq)]
+struct Tree {
+    id: u32,
+    leaves: Vec<Tree>,
+}
+
+#[derive(Codec,Debug,PartialEq)]
+struct Swapped {
+    samples: Vec<f32>,
+    sensor: String,
+    seq: Varint<u32>,
+}
+
+// Versioned structs of the same layout, with other names.
+#[derive(Codec,Debug,PartialEq)]
+#[codec(versioned)]
+struct Announce {
+    id: u64,
+    port: u16,
+}
+
+#[derive(Codec,Debug,PartialEq)]
+#[codec(versioned)]
+struct Beacon {
+    participant: u64,
+    port: u16,
+}
+
+#[test]
+fn test_type_hash() {
+
+    // pinned, so a change to the hash shows up here before it breaks
+    // compatibility between participants of different builds
+    assert_eq!(u32::type_hash(),0xAF63_B94C_8601_B113);
+    assert_eq!(String::type_hash(),0xAF63_C24C_8601_C05E);
+    assert_eq!(Node::type_hash(),0xF6E9_52E2_6316_27CC);
+
+    // names don't count, order and tags do
+    assert_eq!(Tree::type_hash(),Node::type_hash());
+    assert_eq!(Beacon::type_hash(),Announce::type_hash());
+    assert_eq!(Beacon::type_hash(),0x288B_BF18_18E0_8E59);
+    assert_eq!(usize::type_hash(),u64::type_hash());
+    assert_ne!(Swapped::type_hash(),Reading::type_hash());
+    assert_ne!(Pair::<u8>::type_hash(),Pair::<i8>::type_hash());
+    assert_ne!(Event::type_hash(),Schema::Enum { name: "Event".to_string(), tag_size: 1, big_endian: false, variants: vec![], }.fingerprint());
+    assert_ne!(<[u8; 4]>::type_hash(),<[u8; 5]>::type_hash());
+    assert_ne!(Vec::<u8>::type_hash(),Option::<u8>::type_hash());
+    let little = Schema::Enum { name: "Event".to_string(), tag_size: 1, big_endian: false, variants: vec![], };
+    let big = Schema::Enum { name: "Event".to_string(), tag_size: 1, big_endian: true, variants: vec![], };
+    assert_ne!(big.fingerprint(),little.fingerprint());
+    assert_ne!(big.fingerprint(),Schema::Checked(Box::new(little.clone())).fingerprint());
+    assert_ne!(big.fingerprint(),Schema::Checked(Box::new(Schema::U8)).fingerprint());
+
+    // the names of opaque types are not stable between Rust versions
+    assert_eq!(Schema::Opaque("alloc::string::String".to_string()).fingerprint(),Schema::Opaque("String".to_string()).fingerprint());
+}
@@ -0,0 +1,83 @@
+// Echidna - Codec - tests
+
+use {
+    codec::{
+        Codec,
+        Encode,
+    },
+    std::{
+        collections::{
+            BTreeMap,
+            HashSet,
+            VecDeque,
+        },
+        sync::Arc,
+    },
+};
+
+#[derive(Codec,Debug,PartialEq)]
+enum List {
+    Nil,
+    Cons(u32,Box<List>),
+}
+
+#[derive(Codec,Debug,PartialEq)]
+struct Pose {
+    name: Option<String>,
+    position: (f32,f32,f32),
+    frame: Arc<String>,
+    tags: HashSet<char>,
+    history: VecDeque<(u128,i128)>,
+    limits: BTreeMap<usize,isize>,
+    parent: Option<Box<Pose>>,
+    marker: (),
+}
+
+#[test]
+fn test_recursive() {
+    let source = List::Cons(1,Box::new(List::Cons(2,Box::new(List::Nil))));
+    let mut buffer = Vec::<u8>::new();
+    source.encode(&mut buffer);
+    assert_eq!(buffer.len(),source.size());
+    if let Ok((_,target)) = List::decode(&buffer) {
+        assert_eq!(source,target);
+    }
+    else {
+        panic!("decode failed");
+    }
+}
+
+#[test]
+fn test_std() {
+    let parent = Pose {
+        name: None,
+        position: (0.0,0.0,0.0),
+        frame: Arc::new("world".to_string()),
+        tags: HashSet::new(),
+        history: VecDeque::new(),
+        limits: BTreeMap::new(),
+        parent: None,
+        marker: (),
+    };
+    let mut limits = BTreeMap::new();
+    limits.insert(1,-1);
+    limits.insert(2,-2);
+    let source = Pose {
+        name: Some("camera".to_string()),
+        position: (1.0,2.0,3.0),
+        frame: Arc::new("base".to_string()),
+        tags: vec!['a','ß'].into_iter().collect(),
+        history: vec![(1,-1),(u128::MAX,i128::MIN)].into_iter().collect(),
+        limits,
+        parent: Some(Box::new(parent)),
+        marker: (),
+    };
+    let mut buffer = Vec::<u8>::new();
+    source.encode(&mut buffer);
+    if let Ok((_,target)) = Pose::decode(&buffer) {
+        assert_eq!(source,target);
+    }
+    else {
+        panic!("decode failed");
+    }
+}
@@ -0,0 +1,47 @@
+// Echidna - Codec - tests
+
+use codec::{
+    Codec,
+    Decoder,
+    Encode,
+    Encoder,
+};
+
+#[derive(Codec,Debug,PartialEq)]
+pub struct Image {
+    width: u32,
+    height: u32,
+    pixels: Vec<u8>,
+}
+
+#[derive(Codec,Debug,PartialEq)]
+pub enum Message {
+    Ping(u64),
+    Image(Image),
+    Caption { text: String, },
+}
+
+#[test]
+fn test_stream() {
+    let messages = vec![
+        Message::Ping(1),
+        Message::Image(Image { width: 320, height: 240, pixels: vec![0x55; 320 * 240], }),
+        Message::Caption { text: "front".to_string(), },
+    ];
+    let mut encoder = Encoder::new(Vec::<u8>::new());
+    for message in &messages {
+        encoder.encode(message).unwrap();
+    }
+    let data = encoder.into_inner();
+    let mut buffer = Vec::<u8>::new();
+    for message in &messages {
+        message.encode(&mut buffer);
+    }
+    assert_eq!(data,buffer);
+
+    let mut decoder = Decoder::new(&data[..]);
+    for message in messages {
+        assert_eq!(decoder.decode::<Message>().unwrap(),Some(message));
+    }
+    assert_eq!(decoder.decode::<Message>().unwrap(),None);
+}
@@ -3,7 +3,10 @@
 //mod dump;
 //use dump::*;
 
-use codec::Codec;
+use codec::{
+    Codec,
+    Encode,
+};
 
 #[derive(Codec)]
 pub struct MyStruct {
@@ -44,7 +47,7 @@ fn test_struct() {
 
     //dump(&buffer);
 
-    if let Some((_,target)) = MyStruct::decode(&buffer) {
+    if let Ok((_,target)) = MyStruct::decode(&buffer) {
         println!("yesno: {}",target.yesno);
         println!("ubyte: {}",target.ubyte);
         println!("byte: {}",target.byte);
@@ -58,9 +61,8 @@ fn test_struct() {
         println!("double: {}",target.double);
         println!("stuff: [{},{},{},{},{}]",target.stuff[0],target.stuff[1],target.stuff[2],target.stuff[3],target.stuff[4]);
         println!("hello: \"{}\"",target.hello);
-        assert!(true);
     }
     else {
-        assert!(false);
+        panic!("decode failed");
     }
 }
@@ -0,0 +1,129 @@
+// Echidna - Codec - tests
+
+use {
+    codec::{
+        Codec,
+        TextError,
+        from_text,
+        to_text,
+    },
+    std::collections::HashMap,
+};
+
+#[derive(Codec,Debug,PartialEq)]
+struct Config {
+    name: String,
+    #[codec(skip)]
+    loaded: bool,
+    rate: f32,
+    ports: Vec<u16>,
+    labels: HashMap<String,i32>,
+    mode: Mode,
+}
+
+#[derive(Codec,Debug,PartialEq)]
+#[codec(tag_type = "u8")]
+enum Mode {
+    Idle,
+    #[codec(tag = 5)]
+    Scan { from: i16, to: i16, },
+    Track(u32,Option<Box<Mode>>),
+}
+
+#[derive(Codec,Debug,PartialEq)]
+struct Node {
+    value: u32,
+    children: Vec<Node>,
+}
+
+#[derive(Codec,Debug,PartialEq)]
+struct Unit;
+
+#[derive(Codec,Debug,PartialEq)]
+#[codec(versioned)]
+struct Announce {
+    id: u64,
+    #[codec(since = 1)]
+    port: u16,
+}
+
+#[derive(Codec,Debug,PartialEq)]
+struct Wrapped {
+    #[codec(with = "raw")]
+    raw: u16,
+}
+
+mod raw {
+
+    use codec::*;
+
+    pub fn encode(value: &u16,buffer: &mut Vec<u8>) -> usize {
+        value.encode(buffer)
+    }
+
+    pub fn size(_: &u16) -> usize {
+        2
+    }
+
+    pub fn decode_with(buffer: &[u8],ctx: &mut DecodeContext) -> Result<(usize,u16),DecodeError> {
+        u16::decode_with(buffer,ctx)
+    }
+}
+
+fn config() -> Config {
+    let mut labels = HashMap::<String,i32>::new();
+    labels.insert("floor".to_string(),-1);
+    Config {
+        name: "lidar \"front\"".to_string(),
+        loaded: false,
+        rate: 12.5,
+        ports: vec![7331,7332],
+        labels,
+        mode: Mode::Track(3,Some(Box::new(Mode::Scan { from: -90, to: 90, }))),
+    }
+}
+
+#[test]
+fn test_to_text() {
+    assert_eq!(to_text(&config()),Ok("{\"name\": \"lidar \\\"front\\\"\", \"rate\": 12.5, \"ports\": [7331, 7332], \"labels\": {\"floor\": -1}, \"mode\": {\"Track\": [3, {\"Scan\": {\"from\": -90, \"to\": 90}}]}}".to_string()));
+    assert_eq!(to_text(&Mode::Idle),Ok("\"Idle\"".to_string()));
+    assert_eq!(to_text(&Mode::Track(7,None)),Ok("{\"Track\": [7, null]}".to_string()));
+    assert_eq!(to_text(&Mode::Scan { from: 1, to: 2, }),Ok("{\"Scan\": {\"from\": 1, \"to\": 2}}".to_string()));
+    assert_eq!(to_text(&Unit),Ok("{}".to_string()));
+    assert_eq!(to_text(&Announce { id: 1, port: 2, }),Ok("{\"id\": 1, \"port\": 2}".to_string()));
+    let tree = Node { value: 1, children: vec![Node { value: 2, children: vec![], }], };
+    assert_eq!(to_text(&tree),Ok("{\"value\": 1, \"children\": [{\"value\": 2, \"children\": []}]}".to_string()));
+}
+
+#[test]
+fn test_from_text() {
+    let text = r#"
+        {
+            "mode": { "Track": [ 3, { "Scan": { "to": 90, "from": -90 } } ] },
+            "labels": { "floor": -1 },
+            "ports": [ 7331, 7332 ],
+            "name": "lidar \"front\"",
+            "rate": 1.25e1
+        }
+    "#;
+    assert_eq!(from_text::<Config>(text),Ok(config()));
+    assert_eq!(from_text::<Unit>("{}"),Ok(Unit));
+    assert_eq!(from_text::<Announce>("{\"port\": 2, \"id\": 1}"),Ok(Announce { id: 1, port: 2, }));
+    assert_eq!(from_text::<Node>("{\"value\": 1, \"children\": [{\"value\": 2, \"children\": []}]}"),Ok(Node { value: 1, children: vec![Node { value: 2, children: vec![], }], }));
+}
+
+#[test]
+fn test_errors() {
+    assert_eq!(from_text::<Mode>("\"Park\""),Err(TextError::Invalid { line: 1, column: 1, message: "unknown variant `Park`".to_string(), }));
+    assert_eq!(from_text::<Mode>("\"Scan\""),Err(TextError::Invalid { line: 1, column: 1, message: "variant `Scan` has fields".to_string(), }));
+    assert_eq!(from_text::<Mode>("{\"Scan\": {\"from\": 1}}"),Err(TextError::Invalid { line: 1, column: 10, message: "missing field `to`".to_string(), }));
+    assert_eq!(from_text::<Mode>("{\"Scan\": {\"from\": 1, \"to\": 2, \"by\": 3}}"),Err(TextError::Invalid { line: 1, column: 37, message: "unknown field `by`".to_string(), }));
+    assert_eq!(from_text::<Mode>("{\"Track\": {\"0\": 1}}"),Err(TextError::Invalid { line: 1, column: 11, message: "expected an array of 2 fields".to_string(), }));
+    assert_eq!(from_text::<Announce>("{\"id\": 1, \"id\": 1, \"port\": 2}"),Err(TextError::Invalid { line: 1, column: 17, message: "field `id` appears twice".to_string(), }));
+    assert_eq!(from_text::<Vec<u8>>(&"[".repeat(1000000)),Err(TextError::Invalid { line: 1, column: 65, message: "nested too deep".to_string(), }));
+    let nested = |depth: usize| format!("{}{}","{\"value\": 1, \"children\": [".repeat(depth),"]}".repeat(depth));
+    assert!(from_text::<Node>(&nested(20)).is_ok());
+    assert!(matches!(from_text::<Node>(&nested(40)),Err(TextError::Invalid { message,.. }) if message == "nested too deep"));
+    assert_eq!(to_text(&Wrapped { raw: 1, }),Err(TextError::Opaque));
+    assert_eq!(from_text::<Wrapped>("{\"raw\": 1}"),Err(TextError::Opaque));
+}
@@ -3,7 +3,10 @@
 //mod dump;
 //use dump::*;
 
-use codec::Codec;
+use codec::{
+    Codec,
+    Encode,
+};
 
 #[derive(Codec)]
 struct MyTuple(f32,f32);
@@ -16,12 +19,11 @@ fn test_tuple() {
 
     //dump(&buffer);
 
-    if let Some((_,target)) = MyTuple::decode(&buffer) {
+    if let Ok((_,target)) = MyTuple::decode(&buffer) {
         println!(".0: {}",target.0);
         println!(".1: {}",target.1);
-        assert!(true);
     }
     else {
-        assert!(false);
+        panic!("decode failed");
     }
 }
@@ -0,0 +1,11 @@
+use codec::Codec;
+
+#[derive(Codec)]
+struct Frame {
+    #[codec(skip,with = "extent")]
+    extent: (u16,u16),
+    #[codec(rename = "x")]
+    x: u32,
+}
+
+fn main() { }
@@ -0,0 +1,9 @@
+use codec::Codec;
+
+#[derive(Codec)]
+struct Register {
+    #[codec(with = "raw",big_endian)]
+    value: u16,
+}
+
+fn main() { }
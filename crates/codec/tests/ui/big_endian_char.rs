@@ -0,0 +1,10 @@
+use codec::Codec;
+
+#[derive(Codec)]
+#[codec(big_endian)]
+struct Key {
+    code: u16,
+    symbol: char,
+}
+
+fn main() { }
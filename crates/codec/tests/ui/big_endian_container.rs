@@ -0,0 +1,10 @@
+use codec::Codec;
+
+#[derive(Codec)]
+#[codec(big_endian)]
+struct Samples {
+    station: u8,
+    values: [u32; 4],
+}
+
+fn main() { }
@@ -0,0 +1,11 @@
+use codec::Codec;
+
+#[derive(Codec)]
+#[codec(big_endian)]
+struct Register {
+    address: u16,
+    value: i32,
+    name: String,
+}
+
+fn main() { }
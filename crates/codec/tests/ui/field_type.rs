@@ -0,0 +1,9 @@
+use codec::Codec;
+
+#[derive(Codec)]
+struct Raw {
+    id: u32,
+    data: *const u8,
+}
+
+fn main() { }
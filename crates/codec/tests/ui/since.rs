@@ -0,0 +1,12 @@
+use codec::Codec;
+
+#[derive(Codec)]
+#[codec(versioned)]
+struct Announce {
+    id: u64,
+    #[codec(since = 1)]
+    port: u16,
+    domain: String,
+}
+
+fn main() { }
@@ -0,0 +1,27 @@
+use codec::Codec;
+
+#[derive(Codec)]
+enum Command {
+    Start,
+    #[codec(tag = 0)]
+    Stop,
+}
+
+#[derive(Codec)]
+#[codec(tag_type = "u8")]
+enum Level {
+    Low = 0x100,
+}
+
+#[derive(Codec)]
+enum Mode {
+    Idle = 1 << 2,
+}
+
+#[derive(Codec)]
+#[codec(tag_type = "u8")]
+struct Frame {
+    id: u32,
+}
+
+fn main() { }
@@ -0,0 +1,6 @@
+use codec::Codec;
+
+#[derive(Codec)]
+struct Callback(u32,fn(u32));
+
+fn main() { }
@@ -0,0 +1,9 @@
+use codec::Codec;
+
+#[derive(Codec)]
+union Bits {
+    i: u32,
+    f: f32,
+}
+
+fn main() { }
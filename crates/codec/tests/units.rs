@@ -0,0 +1,56 @@
+// Echidna - Codec - tests
+
+use codec::{
+    Codec,
+    DecodeError,
+    Encode,
+    testing::assert_roundtrip,
+};
+
+#[derive(Codec,Debug,PartialEq)]
+struct Ping;
+
+#[derive(Codec,Debug,PartialEq)]
+struct Pong { }
+
+#[derive(Codec,Debug,PartialEq)]
+struct Beat(Ping,u8,Pong);
+
+#[derive(Codec,Debug,PartialEq)]
+enum Never { }
+
+#[derive(Codec,Debug,PartialEq)]
+#[codec(tag_type = "u8")]
+enum Void { }
+
+#[derive(Codec,Debug,PartialEq)]
+enum Control {
+    Ping(Ping),
+    Stop(Option<Never>),
+}
+
+#[test]
+fn test_unit_struct() {
+    let mut buffer = Vec::<u8>::new();
+    assert_eq!(Ping.encode(&mut buffer),0);
+    assert_eq!(Ping.size(),0);
+    assert_eq!(Pong { }.size(),0);
+    assert_eq!(Ping::FIXED_SIZE,Some(0));
+    assert_eq!(Beat::FIXED_SIZE,Some(1));
+    assert_eq!(Never::FIXED_SIZE,None);
+    assert!(buffer.is_empty());
+    assert_eq!(Ping::decode(&[]),Ok((0,Ping)));
+    assert_roundtrip(&Ping);
+    assert_roundtrip(&Beat(Ping,3,Pong { }));
+    assert_eq!(Beat(Ping,3,Pong { }).size(),1);
+    assert_roundtrip(&Control::Ping(Ping));
+    assert_roundtrip(&Control::Stop(None));
+}
+
+#[test]
+fn test_uninhabited_enum() {
+    assert_eq!(Never::decode(&[0,0,0,0]),Err(DecodeError::InvalidTag { offset: 0, tag: 0, }));
+    assert_eq!(Never::decode(&[]),Err(DecodeError::UnexpectedEof { offset: 0, needed: 4, available: 0, }));
+    assert_eq!(Void::decode(&[7]),Err(DecodeError::InvalidTag { offset: 0, tag: 7, }));
+    assert_eq!(Control::decode(&[1,0,0,0,1,0,0,0,0]),Err(DecodeError::InvalidTag { offset: 5, tag: 0, }));
+}
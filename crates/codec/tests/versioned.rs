@@ -0,0 +1,218 @@
+// Echidna - Codec - tests
+
+use codec::{
+    Codec,
+    DecodeContext,
+    DecodeError,
+    Decoder,
+    Encode,
+    Value,
+    testing::{
+        assert_roundtrip,
+        fuzz_decode_value_equal,
+    },
+};
+
+// The same message as an older participant knows it...
+mod v1 {
+
+    use codec::Codec;
+
+    #[derive(Codec,Debug,PartialEq)]
+    #[codec(versioned)]
+    pub struct Announce {
+        pub id: u64,
+        pub domain: String,
+    }
+
+    #[derive(Codec,Debug,PartialEq)]
+    #[codec(versioned)]
+    pub struct Range(pub u16,pub u16);
+}
+
+// ...and as a newer one knows it.
+mod v2 {
+
+    use codec::Codec;
+
+    fn default_rate() -> f32 {
+        10.0
+    }
+
+    #[derive(Codec,Debug,PartialEq)]
+    #[codec(versioned)]
+    pub struct Announce {
+        pub id: u64,
+        pub domain: String,
+        #[codec(since = 1)]
+        pub port: u16,
+        #[codec(skip)]
+        pub seen: u32,
+        #[codec(since = 2,default = "default_rate")]
+        pub rate: f32,
+    }
+
+    #[derive(Codec,Debug,PartialEq)]
+    #[codec(versioned)]
+    pub struct Range(pub u16,pub u16,#[codec(since = 3)] pub Option<u8>);
+}
+
+// Another message that happens to have the same name.
+mod other {
+
+    use codec::Codec;
+
+    #[derive(Codec,Debug,PartialEq)]
+    #[codec(versioned)]
+    pub struct Announce {
+        pub id: u64,
+        pub port: u16,
+    }
+}
+
+// Encodes like a `u16`, but only finds out its size by encoding, like
+// `Compressed`.
+#[derive(Debug,PartialEq)]
+struct Sizeless(u16);
+
+impl Encode for Sizeless {
+    fn encode(&self,buffer: &mut Vec<u8>) -> usize {
+        self.0.encode(buffer)
+    }
+
+    fn size(&self) -> usize {
+        panic!("size of Sizeless");
+    }
+}
+
+impl Codec for Sizeless {
+    fn decode_with(buffer: &[u8],ctx: &mut DecodeContext) -> Result<(usize,Self),DecodeError> {
+        let (len,value) = u16::decode_with(buffer,ctx)?;
+        Ok((len,Sizeless(value)))
+    }
+}
+
+#[derive(Codec,Debug,PartialEq)]
+#[codec(versioned)]
+struct Sample {
+    id: u8,
+    value: Sizeless,
+}
+
+#[test]
+fn test_versioned() {
+    let old = v1::Announce { id: 7, domain: "lab".to_string(), };
+    let new = v2::Announce { id: 7, domain: "lab".to_string(), port: 7331, seen: 0, rate: 2.5, };
+    assert_roundtrip(&old);
+    assert_roundtrip(&new);
+    assert_roundtrip(&v1::Range(1,2));
+    assert_roundtrip(&v2::Range(1,2,Some(3)));
+
+    // version 2, 21 bytes of fields
+    let mut buffer = Vec::<u8>::new();
+    new.encode(&mut buffer);
+    assert_eq!(&buffer[..6],&[2,0,21,0,0,0]);
+    assert_eq!(buffer.len(),27);
+    assert_eq!(v2::Announce::FIXED_SIZE,None);
+    assert_eq!(v2::Range::FIXED_SIZE,None);
+    assert_eq!(v1::Range::FIXED_SIZE,Some(10));
+    assert_eq!(v2::Announce::MIN_SIZE,18);
+    assert_eq!(v2::Range::MIN_SIZE,10);
+
+    // versions of the same struct are compatible
+    assert_eq!(v1::Announce::type_hash(),v2::Announce::type_hash());
+    assert_eq!(v1::Range::type_hash(),v2::Range::type_hash());
+    assert_ne!(v1::Announce::type_hash(),v1::Range::type_hash());
+
+    // but the fields of version 0 count
+    assert_ne!(v1::Announce::type_hash(),other::Announce::type_hash());
+}
+
+#[test]
+fn test_length_after_fields() {
+    let sample = Sample { id: 1, value: Sizeless(0x0203), };
+    let mut buffer = vec![0xFF];
+    assert_eq!(sample.encode(&mut buffer),9);
+    assert_eq!(buffer,vec![0xFF,0,0,3,0,0,0,1,3,2]);
+    assert_eq!(Sample::decode(&buffer[1..]),Ok((9,sample)));
+    let mut frame = [0u8; 16];
+    assert_eq!(Sample { id: 1, value: Sizeless(0x0203), }.encode_into(&mut frame),Ok(9));
+    assert_eq!(&frame[..9],&buffer[1..]);
+}
+
+#[test]
+fn test_older_decoder() {
+    let new = vec![
+        v2::Announce { id: 1, domain: "a".to_string(), port: 1000, seen: 3, rate: 1.0, },
+        v2::Announce { id: 2, domain: "bc".to_string(), port: 2000, seen: 4, rate: 2.0, },
+    ];
+    let mut buffer = Vec::<u8>::new();
+    new.encode(&mut buffer);
+    let (len,old) = Vec::<v1::Announce>::decode(&buffer).unwrap();
+    assert_eq!(len,buffer.len());
+    assert_eq!(old,vec![
+        v1::Announce { id: 1, domain: "a".to_string(), },
+        v1::Announce { id: 2, domain: "bc".to_string(), },
+    ]);
+    let mut buffer = Vec::<u8>::new();
+    v2::Range(5,6,None).encode(&mut buffer);
+    assert_eq!(v1::Range::decode(&buffer),Ok((buffer.len(),v1::Range(5,6))));
+}
+
+#[test]
+fn test_newer_decoder() {
+    let mut buffer = Vec::<u8>::new();
+    v1::Announce { id: 3, domain: "lab".to_string(), }.encode(&mut buffer);
+    assert_eq!(v2::Announce::decode(&buffer),Ok((buffer.len(),v2::Announce { id: 3, domain: "lab".to_string(), port: 0, seen: 0, rate: 10.0, })));
+    let mut buffer = Vec::<u8>::new();
+    v1::Range(5,6).encode(&mut buffer);
+    assert_eq!(v2::Range::decode(&buffer),Ok((buffer.len(),v2::Range(5,6,None))));
+}
+
+#[test]
+fn test_truncated() {
+    let mut buffer = Vec::<u8>::new();
+    v2::Range(5,6,Some(7)).encode(&mut buffer);
+    buffer.truncate(9);
+    assert_eq!(v2::Range::decode(&buffer),Err(DecodeError::UnexpectedEof { offset: 0, needed: 12, available: 9, }));
+
+    // a length that is too short for the fields of the version, the bytes
+    // after it don't help
+    let buffer = vec![3,0,3,0,0,0,5,0,6,0,1];
+    assert_eq!(v2::Range::decode(&buffer),Err(DecodeError::LengthOverflow { offset: 2, length: 3, }));
+    assert_eq!(Value::decode_with(&v2::Range::schema(),&buffer),Err(DecodeError::LengthOverflow { offset: 2, length: 3, }));
+    let buffer = vec![0,0,2,0,0,0,1,2,3,4,5,6];
+    assert_eq!(v1::Range::decode(&buffer),Err(DecodeError::LengthOverflow { offset: 2, length: 2, }));
+    let mut decoder = Decoder::new(&buffer[..]);
+    assert_eq!(decoder.decode::<v1::Range>().unwrap_err().kind(),std::io::ErrorKind::InvalidData);
+}
+
+#[test]
+fn test_value() {
+    let mut buffer = Vec::<u8>::new();
+    v2::Announce { id: 1, domain: "a".to_string(), port: 1000, seen: 3, rate: 1.0, }.encode(&mut buffer);
+    let (len,value) = Value::decode_with(&v1::Announce::schema(),&buffer).unwrap();
+    assert_eq!(len,buffer.len());
+    assert_eq!(value.to_string(),"Announce { id: 1, domain: \"a\" }");
+    let mut buffer = Vec::<u8>::new();
+    v1::Announce { id: 1, domain: "a".to_string(), }.encode(&mut buffer);
+    let (len,value) = Value::decode_with(&v2::Announce::schema(),&buffer).unwrap();
+    assert_eq!(len,buffer.len());
+    assert_eq!(value.to_string(),"Announce { id: 1, domain: \"a\" }");
+}
+
+#[test]
+fn test_fuzz() {
+    // other versions decode fine, but encode again as this version
+    let mut buffer = Vec::<u8>::new();
+    v2::Announce { id: 1, domain: "a".to_string(), port: 1000, seen: 3, rate: 1.0, }.encode(&mut buffer);
+    fuzz_decode_value_equal::<v1::Announce>(&buffer);
+    let mut buffer = Vec::<u8>::new();
+    v1::Range(5,6).encode(&mut buffer);
+    fuzz_decode_value_equal::<v2::Range>(&buffer);
+    let mut buffer = Vec::<u8>::new();
+    v1::Range(5,6).encode(&mut buffer);
+    buffer[2] += 3;
+    buffer.extend_from_slice(&[1,2,3]);
+    fuzz_decode_value_equal::<v1::Range>(&buffer);
+}
@@ -0,0 +1,72 @@
+// Echidna - Codec - tests
+
+use codec::{
+    Checked,
+    Codec,
+    Compressed,
+    DecodeError,
+    Encode,
+    Value,
+    from_text,
+    to_text,
+    testing::{
+        assert_random_roundtrips,
+        assert_roundtrip,
+    },
+};
+
+#[derive(Codec,Debug,PartialEq)]
+struct Frame {
+    seq: u32,
+    pixels: Compressed<Vec<u8>>,
+    meta: Checked<String>,
+}
+
+fn frame() -> Frame {
+    Frame {
+        seq: 9,
+        pixels: Compressed((0..4096).map(|i| (i / 64) as u8).collect()),
+        meta: Checked("camera".to_string()),
+    }
+}
+
+#[test]
+fn test_fields() {
+    let frame = frame();
+    assert_roundtrip(&frame);
+    let mut buffer = Vec::<u8>::new();
+    frame.encode(&mut buffer);
+    assert!(buffer.len() < 1000);
+    let (len,value) = Value::decode_with(&Frame::schema(),&buffer).unwrap();
+    assert_eq!(len,frame.size());
+    assert!(value.to_string().starts_with("Frame { seq: 9, pixels: [0, 0,"));
+}
+
+#[test]
+fn test_envelope() {
+    let message = Checked(Compressed(vec![frame(),frame()]));
+    assert_roundtrip(&message);
+
+    // corruption either breaks the value, or shows in the checksum
+    let mut buffer = Vec::<u8>::new();
+    message.encode(&mut buffer);
+    let len = buffer.len();
+    buffer[len - 5] ^= 0x40;
+    assert!(Checked::<Compressed<Vec<Frame>>>::decode(&buffer).is_err());
+    buffer[len - 5] ^= 0x40;
+    buffer[len - 2] ^= 0x40;
+    assert_eq!(Checked::<Compressed<Vec<Frame>>>::decode(&buffer),Err(DecodeError::ChecksumMismatch { offset: len - 4, }));
+    assert_random_roundtrips::<Checked<Vec<u16>>>(100);
+    assert_random_roundtrips::<Compressed<Vec<String>>>(100);
+    assert_random_roundtrips::<Option<Compressed<Checked<(u8,char)>>>>(100);
+}
+
+#[test]
+fn test_text() {
+    let value = (Checked(3u8),Compressed("aaaaaaaaaaaaaaaa".to_string()));
+    let text = to_text(&value).unwrap();
+    assert_eq!(text,"[3, \"aaaaaaaaaaaaaaaa\"]");
+    assert_eq!(from_text::<(Checked<u8>,Compressed<String>)>(&text),Ok(value));
+    assert_ne!(Checked::<u8>::type_hash(),u8::type_hash());
+    assert_ne!(Compressed::<u8>::type_hash(),Checked::<u8>::type_hash());
+}
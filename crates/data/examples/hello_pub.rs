@@ -17,7 +17,7 @@ async fn async_main() {
     file.read_to_end(&mut buffer).await.expect("cannot read file");
 
     // create hello publisher
-    let publisher = Publisher::new_default(7332,"office_test","/hello").await;
+    let publisher = Publisher::new_default(7332,"office_test","/hello",RAW_TYPE_HASH).await;
 
     // publish message every 5 seconds
     for _ in 0..100 {
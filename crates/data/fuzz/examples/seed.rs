@@ -0,0 +1,18 @@
+// Echidna - Data - fuzz
+
+// Write the seed corpus for the protocol fuzz target.
+
+use std::{
+    fs,
+    path::Path,
+};
+
+fn main() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("corpus").join("protocol");
+    fs::create_dir_all(&dir).expect("cannot create corpus directory");
+    let seeds = data_fuzz::message_seeds();
+    for (i,seed) in seeds.iter().enumerate() {
+        fs::write(dir.join(format!("seed-{:03}",i)),seed).expect("cannot write seed");
+    }
+    println!("protocol: {} seeds",seeds.len());
+}
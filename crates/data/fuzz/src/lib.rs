@@ -0,0 +1,94 @@
+// Echidna - Data - fuzz
+
+//! Decoder for the protocol fuzz target.
+//!
+//! The first byte of the fuzz input picks the message enum, the rest is
+//! decoded as that message. Messages with versioned structs in them go
+//! through `fuzz_decode_value_equal`, because a struct of another version
+//! encodes again as this version. That also covers the messages with maps,
+//! whose entries can be in any order.
+
+use {
+    codec::{
+        Encode,
+        Varint,
+        Timestamp,
+        testing::{
+            fuzz_decode,
+            fuzz_decode_value_equal,
+        },
+    },
+    data::*,
+    std::{
+        collections::HashMap,
+        net::SocketAddr,
+    },
+};
+
+pub fn decode_message(data: &[u8]) {
+    if let Some((selector,data)) = data.split_first() {
+        match selector {
+            0 => fuzz_decode::<PublisherToSubscriber>(data),
+            1 => fuzz_decode::<SubscriberToPublisher>(data),
+            2 => fuzz_decode_value_equal::<Beacon>(data),
+            3 => fuzz_decode_value_equal::<ParticipantAnnounce>(data),
+            4 => fuzz_decode_value_equal::<ParticipantToParticipant>(data),
+            5 => fuzz_decode_value_equal::<ToParticipant>(data),
+            6 => fuzz_decode_value_equal::<ParticipantToPublisher>(data),
+            7 => fuzz_decode::<ParticipantToSubscriber>(data),
+            _ => { },
+        }
+    }
+}
+
+fn seed<M: Encode>(selector: u8,message: M) -> Vec<u8> {
+    let mut seed = vec![selector];
+    message.encode(&mut seed);
+    seed
+}
+
+/// Encodings of messages as the participants send them.
+pub fn message_seeds() -> Vec<Vec<u8>> {
+    let address: SocketAddr = "192.168.1.10:7332".parse().unwrap();
+    let address6: SocketAddr = "[fdec:c0bb:c329::1]:7333".parse().unwrap();
+    let publisher = PublisherRef { topic: "camera".to_string(), type_hash: 0x0123_4567_89AB_CDEF, };
+    let subscriber = SubscriberRef { address, topic: "camera".to_string(), type_hash: 0x0123_4567_89AB_CDEF, };
+    let subscriber6 = SubscriberRef { address: address6, topic: "lidar".to_string(), type_hash: RAW_TYPE_HASH, };
+    let mut subs = HashMap::new();
+    subs.insert(3,subscriber.clone());
+    subs.insert(4,subscriber6.clone());
+    let mut pubs = HashMap::new();
+    pubs.insert(1,publisher.clone());
+    let payload = [0x55; 50];
+    vec![
+        seed(0,PublisherToSubscriber::Heartbeat(0x1234_5678_9ABC_DEF0)),
+        seed(0,PublisherToSubscriber::Chunk(Chunk {
+            ts: Timestamp::from_nanos(1_600_000_000_000_000_000),
+            id: 0x1234_5678_9ABC_DEF0,
+            total_bytes: 250,
+            chunk_size: 100,
+            total: 3,
+            index: 2,
+            data: &payload,
+        })),
+        seed(1,SubscriberToPublisher::Ack(0x1234_5678_9ABC_DEF0,Varint(2))),
+        seed(1,SubscriberToPublisher::NAck(0x1234_5678_9ABC_DEF0,Varint(0),Varint(300))),
+        seed(2,Beacon { id: 1, domain: "default".to_string(), port: 7332, }),
+        seed(3,ParticipantAnnounce { id: 1, pubs: pubs.clone(), subs: subs.clone(), }),
+        seed(3,ParticipantAnnounce { id: 2, pubs: HashMap::new(), subs: HashMap::new(), }),
+        seed(4,ParticipantToParticipant::NewPub(1,publisher.clone())),
+        seed(4,ParticipantToParticipant::DropPub(1)),
+        seed(4,ParticipantToParticipant::NewSub(3,subscriber.clone())),
+        seed(4,ParticipantToParticipant::DropSub(3)),
+        seed(5,ToParticipant::InitPub(1,"default".to_string(),publisher)),
+        seed(5,ToParticipant::InitSub(3,"default".to_string(),subscriber6.clone())),
+        seed(6,ParticipantToPublisher::Init(subs,HashMap::new())),
+        seed(6,ParticipantToPublisher::InitFailed(PubInitFailed::DomainMismatch)),
+        seed(6,ParticipantToPublisher::NewLocalSub(3,subscriber.clone())),
+        seed(6,ParticipantToPublisher::NewPeerSub(4,subscriber6)),
+        seed(6,ParticipantToPublisher::DropLocalSub(3)),
+        seed(6,ParticipantToPublisher::DropPeerSub(4)),
+        seed(7,ParticipantToSubscriber::Init),
+        seed(7,ParticipantToSubscriber::InitFailed(SubInitFailed::DomainMismatch)),
+    ]
+}
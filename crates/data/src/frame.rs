@@ -0,0 +1,254 @@
+// Echidna - Data
+
+use {
+    tokio::{
+        io,
+        io::{
+            AsyncReadExt,
+            AsyncWriteExt,
+        },
+    },
+    codec::{
+        Codec,
+        Encode,
+        DecodeContext,
+        DecodeLimits,
+    },
+};
+
+// A frame is the payload length (u32), the message type (u32), and then
+// the encoded message itself.
+const HEADER_SIZE: usize = 8;
+
+// Minimum number of bytes to read at a time.
+const READ_SIZE: usize = 4096;
+
+/// Message that is sent over a TCP control stream.
+pub trait ControlMessage: Codec {
+
+    /// Message type in the frame header, unique for each message enum.
+    const MESSAGE_TYPE: u32;
+}
+
+/// Send `message` as one frame.
+///
+/// The length in the header is filled in after encoding, so `size` is not
+/// needed, it can be as expensive as encoding.
+pub async fn send_message<S: io::AsyncWrite + Unpin,M: ControlMessage>(stream: &mut S,message: M) {
+    let mut send_buffer = Vec::new();
+    0u32.encode(&mut send_buffer);
+    M::MESSAGE_TYPE.encode(&mut send_buffer);
+    message.encode(&mut send_buffer);
+    let length = (send_buffer.len() - HEADER_SIZE) as u32;
+    send_buffer[0..4].copy_from_slice(&length.to_le_bytes());
+    stream.write_all(&send_buffer).await.expect("cannot send message");
+}
+
+/// Reads frames from a control stream.
+///
+/// Partial reads are collected until a frame is complete, and whatever
+/// arrived beyond that frame is kept for the next one.
+pub struct FrameReader<R> {
+    reader: R,
+    buffer: Vec<u8>,
+    start: usize,
+    limits: DecodeLimits,
+}
+
+impl<R: io::AsyncRead + Unpin> FrameReader<R> {
+
+    pub fn new(reader: R) -> FrameReader<R> {
+        FrameReader::with_limits(reader,DecodeLimits::default())
+    }
+
+    /// Reader that checks every frame against `limits`.
+    pub fn with_limits(reader: R,limits: DecodeLimits) -> FrameReader<R> {
+        FrameReader {
+            reader,
+            buffer: Vec::new(),
+            start: 0,
+            limits,
+        }
+    }
+
+    /// Receive the next message.
+    ///
+    /// Returns `None` when the stream is closed between frames. A frame of
+    /// the wrong message type or with a malformed message is an
+    /// `io::ErrorKind::InvalidData` error.
+    pub async fn recv<M: ControlMessage>(&mut self) -> io::Result<Option<M>> {
+        loop {
+            let frame = &self.buffer[self.start..];
+            if frame.len() >= HEADER_SIZE {
+                let (length,message_type) = header(frame,&self.limits)?;
+                let end = HEADER_SIZE + length;
+                if frame.len() >= end {
+                    let result = self.decode::<M>(message_type,&frame[HEADER_SIZE..end]);
+                    self.start += end;
+                    return result.map(Some);
+                }
+            }
+            if !self.fill().await? {
+                return Ok(None);
+            }
+        }
+    }
+
+    /// Wait until the stream is closed, discarding any frames.
+    pub async fn closed(&mut self) {
+        loop {
+            self.buffer.clear();
+            self.start = 0;
+            if !matches!(self.fill().await,Ok(true)) {
+                return;
+            }
+        }
+    }
+
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.reader
+    }
+
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+
+    fn decode<M: ControlMessage>(&self,message_type: u32,payload: &[u8]) -> io::Result<M> {
+        if message_type != M::MESSAGE_TYPE {
+            return Err(io::Error::new(io::ErrorKind::InvalidData,format!("unexpected message type {}",message_type)));
+        }
+        let mut ctx = DecodeContext::new(self.limits);
+        let (length,message) = M::decode_with(payload,&mut ctx).map_err(invalid)?;
+        if length != payload.len() {
+            return Err(io::Error::new(io::ErrorKind::InvalidData,"trailing bytes in frame"));
+        }
+        Ok(message)
+    }
+
+    // Read more bytes, returns false if the stream was closed. Closing the
+    // stream halfway through a frame is an error. The buffer grows with the
+    // bytes that arrive, not with the length in the header, so a hostile
+    // header costs no more memory than the bytes sent after it. Frames that
+    // were received are only removed from the buffer here, all at once, so
+    // many small frames from one read aren't moved again for each frame.
+    async fn fill(&mut self) -> io::Result<bool> {
+        self.buffer.drain(..self.start);
+        self.start = 0;
+        self.buffer.reserve(READ_SIZE);
+        if self.reader.read_buf(&mut self.buffer).await? == 0 {
+            if self.buffer.is_empty() {
+                return Ok(false);
+            }
+            return Err(io::Error::new(io::ErrorKind::UnexpectedEof,"stream closed inside a frame"));
+        }
+        Ok(true)
+    }
+}
+
+// Payload length and message type of the frame at the front of `frame`.
+fn header(frame: &[u8],limits: &DecodeLimits) -> io::Result<(usize,u32)> {
+    let (_,length) = u32::decode(&frame[0..4]).map_err(invalid)?;
+    let (_,message_type) = u32::decode(&frame[4..8]).map_err(invalid)?;
+    if length as usize > limits.max_alloc {
+        return Err(io::Error::new(io::ErrorKind::InvalidData,format!("frame of {} bytes too big",length)));
+    }
+    Ok((length as usize,message_type))
+}
+
+fn invalid(e: codec::DecodeError) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData,e)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use {
+        crate::*,
+        tokio::io::AsyncWriteExt,
+    };
+
+    #[test]
+    fn test_frames() {
+        let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+        runtime.block_on(async {
+
+            // two messages coalesced into a single write
+            let mut data = Vec::<u8>::new();
+            send_message(&mut data,ParticipantToParticipant::DropPub(1)).await;
+            send_message(&mut data,ParticipantToParticipant::DropSub(2)).await;
+
+            // followed by one that arrives in pieces
+            let mut last = Vec::<u8>::new();
+            send_message(&mut last,ParticipantToParticipant::NewPub(3,PublisherRef { topic: "camera".to_string(), type_hash: 0x0123_4567_89AB_CDEF, })).await;
+
+            let (mut client,server) = tokio::io::duplex(64);
+            let sender = tokio::spawn(async move {
+                client.write_all(&data).await.unwrap();
+                for piece in last.chunks(3) {
+                    client.write_all(piece).await.unwrap();
+                    client.flush().await.unwrap();
+                    tokio::task::yield_now().await;
+                }
+            });
+
+            let mut reader = FrameReader::new(server);
+            assert!(matches!(reader.recv::<ParticipantToParticipant>().await,Ok(Some(ParticipantToParticipant::DropPub(1)))));
+            assert!(matches!(reader.recv::<ParticipantToParticipant>().await,Ok(Some(ParticipantToParticipant::DropSub(2)))));
+            match reader.recv::<ParticipantToParticipant>().await {
+                Ok(Some(ParticipantToParticipant::NewPub(3,publisher))) => assert_eq!(publisher.topic,"camera"),
+                _ => panic!("decode failed"),
+            }
+            sender.await.unwrap();
+            assert!(matches!(reader.recv::<ParticipantToParticipant>().await,Ok(None)));
+        });
+    }
+
+    #[test]
+    fn test_many_frames() {
+        let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+        runtime.block_on(async {
+            let mut data = Vec::<u8>::new();
+            for i in 0..1000 {
+                send_message(&mut data,ParticipantToParticipant::DropPub(i)).await;
+            }
+            let mut reader = FrameReader::new(&data[..]);
+            for i in 0..1000 {
+                assert!(matches!(reader.recv::<ParticipantToParticipant>().await,Ok(Some(ParticipantToParticipant::DropPub(id))) if id == i));
+            }
+
+            // the frames that came in with the last read stay until the next
+            assert!(reader.start > 1000);
+            assert_eq!(reader.start,reader.buffer.len());
+            assert!(matches!(reader.recv::<ParticipantToParticipant>().await,Ok(None)));
+            assert!(reader.buffer.is_empty());
+        });
+    }
+
+    #[test]
+    fn test_frame_invalid() {
+        let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+        runtime.block_on(async {
+
+            // wrong message type
+            let mut data = Vec::<u8>::new();
+            send_message(&mut data,ParticipantToSubscriber::Init).await;
+            let mut reader = FrameReader::new(&data[..]);
+            assert!(reader.recv::<ParticipantToParticipant>().await.is_err());
+
+            // stream closed inside a frame
+            let mut reader = FrameReader::new(&data[..6]);
+            assert!(reader.recv::<ParticipantToSubscriber>().await.is_err());
+
+            // frame too big
+            let data = vec![0xFF,0xFF,0xFF,0xFF,0,0,0,0];
+            let mut reader = FrameReader::with_limits(&data[..],codec::DecodeLimits { max_alloc: 1024, max_elements: 16, max_depth: 4, });
+            assert!(reader.recv::<ParticipantToParticipant>().await.is_err());
+
+            // a header promising a big frame doesn't allocate it
+            let data = vec![0x00,0x00,0x00,0x0F,0,0,0,0,1,2,3];
+            let mut reader = FrameReader::new(&data[..]);
+            assert!(reader.recv::<ParticipantToParticipant>().await.is_err());
+            assert!(reader.buffer.capacity() <= 4 * super::READ_SIZE);
+        });
+    }
+}
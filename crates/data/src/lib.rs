@@ -3,6 +3,9 @@
 mod protocol;
 pub use protocol::*;
 
+mod frame;
+pub use frame::*;
+
 mod participant;
 pub use participant::*;
 
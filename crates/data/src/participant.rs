@@ -8,9 +8,11 @@ use {
         net,
         time,
         sync::Mutex,
-        io::AsyncReadExt,
     },
-    codec::Codec,
+    codec::{
+        Codec,
+        Encode,
+    },
     std::{
         collections::HashMap,
         sync::Arc,
@@ -33,14 +35,37 @@ pub struct PeerRef {
 pub struct LocalPublisherRef {
     pub stream: io::WriteHalf<net::TcpStream>,
     pub topic: String,
+    pub type_hash: u64,
 }
 
 pub struct LocalSubscriberRef {
     pub stream: io::WriteHalf<net::TcpStream>,
     pub topic: String,
+    pub type_hash: u64,
     pub address: SocketAddr,
 }
 
+// Whether two type hashes agree, an unknown one (from an older participant)
+// agrees with any.
+fn same_type(pub_type_hash: u64,sub_type_hash: u64) -> bool {
+    (pub_type_hash == sub_type_hash) || (pub_type_hash == UNKNOWN_TYPE_HASH) || (sub_type_hash == UNKNOWN_TYPE_HASH)
+}
+
+// Whether the messages of a publisher go to a subscriber: the topics have to
+// be the same, and so do the message types.
+fn matches(pub_topic: &str,pub_type_hash: u64,sub_topic: &str,sub_type_hash: u64) -> bool {
+    pub_topic == sub_topic && same_type(pub_type_hash,sub_type_hash)
+}
+
+// Like `matches`, for new connections, also reports subscribers that expect
+// another message type than the publisher sends.
+fn connects(pub_topic: &str,pub_type_hash: u64,sub_topic: &str,sub_type_hash: u64) -> bool {
+    if pub_topic == sub_topic && !same_type(pub_type_hash,sub_type_hash) {
+        println!("subscriber of \"{}\" expects type {:016X}, but publisher sends {:016X}, not connecting",sub_topic,sub_type_hash,pub_type_hash);
+    }
+    matches(pub_topic,pub_type_hash,sub_topic,sub_type_hash)
+}
+
 pub struct Participant {
     pub id: ParticipantId,
     pub domain: String,
@@ -144,10 +169,10 @@ impl Participant {
         loop {
 
             // receive beacon
-            let (_,address) = socket.recv_from(&mut buffer).await.expect("cannot receive beacon");
+            let (length,address) = socket.recv_from(&mut buffer).await.expect("cannot receive beacon");
 
             // decode beacon
-            if let Some((_,beacon)) = Beacon::decode(&buffer) {
+            if let Ok((_,beacon)) = Beacon::decode(&buffer[..length]) {
 
                 // if this is not a local echo, and it's the correct domain
                 if (beacon.id != self.id) && (beacon.domain == self.domain) {
@@ -201,49 +226,47 @@ impl Participant {
         loop {
 
             // accept the connection
-            let (mut stream,_) = self.listener.accept().await.expect("cannot accept connection from local endpoint");
+            let (stream,_) = self.listener.accept().await.expect("cannot accept connection from local endpoint");
 
             // spawn local 
             let this = Arc::clone(&self);
             task::spawn(async move {
 
-                let mut buffer = vec![0u8; 65536];
+                // split stream read and write ends
+                let (stream_read,stream_write) = io::split(stream);
+                let mut reader = FrameReader::new(stream_read);
 
                 // read first message, should be ToPart::InitPub or ToPart::InitSub
-                if let Ok(_) = stream.read(&mut buffer).await {
-                    if let Some((_,message)) = ToParticipant::decode(&buffer) {
-                        match message {
+                if let Ok(Some(message)) = reader.recv::<ToParticipant>().await {
+                    match message {
 
-                            ToParticipant::InitPub(id,domain,publisher) => {
-                                this.run_publisher(stream,id,domain,publisher).await;
-                            },
+                        ToParticipant::InitPub(id,domain,publisher) => {
+                            this.run_publisher(reader,stream_write,id,domain,publisher).await;
+                        },
 
-                            ToParticipant::InitSub(id,domain,subscriber) => {
-                                this.run_subscriber(stream,id,domain,subscriber).await;
-                            },
-                        }
-                    }    
+                        ToParticipant::InitSub(id,domain,subscriber) => {
+                            this.run_subscriber(reader,stream_write,id,domain,subscriber).await;
+                        },
+                    }
                 }
             });
         }
     }
 
-    async fn run_publisher(self: &Arc<Participant>,stream: net::TcpStream,id: PublisherId,domain: String,publisher: PublisherRef) {
+    async fn run_publisher(self: &Arc<Participant>,mut reader: FrameReader<io::ReadHalf<net::TcpStream>>,mut stream_write: io::WriteHalf<net::TcpStream>,id: PublisherId,domain: String,publisher: PublisherRef) {
 
         // This task runs communication with the local publisher (currently no traffic).
 
         // make sure it's the same domain
         if domain == self.domain {
 
-            // split stream read and write ends
-            let (mut stream_read,stream_write) = io::split(stream);
-
             // create local publisher reference
             {
                 let mut state_pubs = self.pubs.lock().await;
                 state_pubs.insert(id,LocalPublisherRef {
                     stream: stream_write,
                     topic: publisher.topic.clone(),
+                    type_hash: publisher.type_hash,
                 });
             }
 
@@ -252,10 +275,11 @@ impl Participant {
             {
                 let state_subs = self.subs.lock().await;
                 for (id,s) in state_subs.iter() {
-                    if s.topic == publisher.topic {
+                    if connects(&publisher.topic,publisher.type_hash,&s.topic,s.type_hash) {
                         local_subs.insert(*id,SubscriberRef {
                             address: SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127,0,0,1)),s.address.port()),
                             topic: s.topic.clone(),
+                            type_hash: s.type_hash,
                         });
                     }
                 }
@@ -265,9 +289,13 @@ impl Participant {
                 let state_peers = self.peers.lock().await;
                 for (_,peer) in state_peers.iter() {
                     for (id,s) in &peer.subs {
+                        if !connects(&publisher.topic,publisher.type_hash,&s.topic,s.type_hash) {
+                            continue;
+                        }
                         peer_subs.insert(*id,SubscriberRef {
                             address: SocketAddr::new(peer.ip,s.address.port()),
                             topic: s.topic.clone(),
+                            type_hash: s.type_hash,
                         });
                     }
                 }
@@ -287,12 +315,7 @@ impl Participant {
             }
 
             // wait for connection to break
-            let mut buffer = vec![0u8; 65536];
-            while let Ok(length) = stream_read.read(&mut buffer).await {
-                if length == 0 {
-                    break;
-                }
-            }
+            reader.closed().await;
 
             // inform peers that publisher is lost
             {
@@ -310,22 +333,17 @@ impl Participant {
         }
         else {
             // initialization failed
-            let mut state_pubs = self.pubs.lock().await;
-            let p = state_pubs.get_mut(&id).unwrap();
-            send_message(&mut p.stream,ParticipantToPublisher::InitFailed(PubInitFailed::DomainMismatch)).await;
+            send_message(&mut stream_write,ParticipantToPublisher::InitFailed(PubInitFailed::DomainMismatch)).await;
         }
     }
 
-    async fn run_subscriber(self: &Arc<Participant>,stream: net::TcpStream,id: SubscriberId,domain: String,subscriber: SubscriberRef) {
+    async fn run_subscriber(self: &Arc<Participant>,mut reader: FrameReader<io::ReadHalf<net::TcpStream>>,mut stream_write: io::WriteHalf<net::TcpStream>,id: SubscriberId,domain: String,subscriber: SubscriberRef) {
 
         // This task runs communication with the local subscriber.
 
         // make sure it's the same domain
         if domain == self.domain {
 
-            // split stream read and write ends
-            let (mut stream_read,stream_write) = io::split(stream);
-
             // create local subscriber reference
             {
                 let mut state_subs = self.subs.lock().await;
@@ -333,6 +351,7 @@ impl Participant {
                     stream: stream_write,
                     address: subscriber.address,
                     topic: subscriber.topic.clone(),
+                    type_hash: subscriber.type_hash,
                 });
             }
 
@@ -347,10 +366,11 @@ impl Participant {
             {
                 let mut state_pubs = self.pubs.lock().await;
                 for (_,p) in state_pubs.iter_mut() {
-                    if p.topic == subscriber.topic {
+                    if connects(&p.topic,p.type_hash,&subscriber.topic,subscriber.type_hash) {
                         send_message(&mut p.stream,ParticipantToPublisher::NewLocalSub(id,SubscriberRef {
                             address: SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127,0,0,1)),subscriber.address.port()),
                             topic: subscriber.topic.clone(),
+                            type_hash: subscriber.type_hash,
                         })).await;
                     }
                 }
@@ -365,12 +385,7 @@ impl Participant {
             }
 
             // wait for connection to break
-            let mut buffer = vec![0u8; 65536];
-            while let Ok(length) = stream_read.read(&mut buffer).await {
-                if length == 0 {
-                    break;
-                }
-            }
+            reader.closed().await;
 
             // destroy local subscriber reference
             {
@@ -390,7 +405,7 @@ impl Participant {
             {
                 let mut state_pubs = self.pubs.lock().await;
                 for (_,p) in state_pubs.iter_mut() {
-                    if p.topic == subscriber.topic {
+                    if matches(&p.topic,p.type_hash,&subscriber.topic,subscriber.type_hash) {
                         send_message(&mut p.stream,ParticipantToPublisher::DropLocalSub(id)).await;
                     }
                 }
@@ -398,9 +413,7 @@ impl Participant {
 
         }
         else {
-            let mut state_subs = self.subs.lock().await;
-            let p = state_subs.get_mut(&id).unwrap();
-            send_message(&mut p.stream,ParticipantToSubscriber::InitFailed(SubInitFailed::DomainMismatch)).await;
+            send_message(&mut stream_write,ParticipantToSubscriber::InitFailed(SubInitFailed::DomainMismatch)).await;
         }
     }
 
@@ -411,7 +424,8 @@ impl Participant {
         let address = stream.peer_addr().unwrap();
 
         // split stream read and write ends
-        let (mut stream_read,stream_write) = io::split(stream);
+        let (stream_read,stream_write) = io::split(stream);
+        let mut reader = FrameReader::new(stream_read);
 
         // create peer
         let mut peer = PeerRef {
@@ -429,6 +443,7 @@ impl Participant {
                 for (id,p) in state_pubs.iter() {
                     pubs.insert(*id,PublisherRef {
                         topic: p.topic.clone(),
+                        type_hash: p.type_hash,
                     });
                 }
                 pubs
@@ -440,6 +455,7 @@ impl Participant {
                     subs.insert(*id,SubscriberRef {
                         address: s.address,
                         topic: s.topic.clone(),
+                        type_hash: s.type_hash,
                     });
                 }
                 subs
@@ -453,63 +469,62 @@ impl Participant {
         send_message(&mut peer.stream,message).await;
 
         // get counter announcement from passive side
-        let mut recv_buffer = vec![0u8; 65536];
-        if let Ok(_) = stream_read.read(&mut recv_buffer).await {
-            if let Some((_,message)) = ParticipantAnnounce::decode(&recv_buffer) {
-
-                peer.pubs = message.pubs;
-                for (id,s) in message.subs.iter() {
-                    peer.subs.insert(*id,SubscriberRef {
-                        address: SocketAddr::new(address.ip(),s.address.port()),
-                        topic: s.topic.clone(),
-                    });
-                }
+        if let Ok(Some(message)) = reader.recv::<ParticipantAnnounce>().await {
+
+            peer.pubs = message.pubs;
+            for (id,s) in message.subs.iter() {
+                peer.subs.insert(*id,SubscriberRef {
+                    address: SocketAddr::new(address.ip(),s.address.port()),
+                    topic: s.topic.clone(),
+                    type_hash: s.type_hash,
+                });
+            }
 
-                // and make peer reference live
-                {
-                    let mut state_peers = self.peers.lock().await;
-                    state_peers.insert(peer_id,peer);
-                }
+            // and make peer reference live
+            {
+                let mut state_peers = self.peers.lock().await;
+                state_peers.insert(peer_id,peer);
+            }
 
-                // notify relevant local publishers of the new subscribers
-                {
-                    let state_peers = self.peers.lock().await;
-                    let mut state_pubs = self.pubs.lock().await;
-                    let peer = state_peers.get(&peer_id).unwrap();
-                    for (_,p) in state_pubs.iter_mut() {
-                        for (sid,s) in peer.subs.iter() {
-                            if p.topic == s.topic {
-                                send_message(&mut p.stream,ParticipantToPublisher::NewPeerSub(*sid,SubscriberRef {
-                                    address: SocketAddr::new(peer.ip,s.address.port()),
-                                    topic: s.topic.clone(),
-                                })).await;
-                            }
+            // notify relevant local publishers of the new subscribers
+            {
+                let state_peers = self.peers.lock().await;
+                let mut state_pubs = self.pubs.lock().await;
+                let peer = state_peers.get(&peer_id).unwrap();
+                for (_,p) in state_pubs.iter_mut() {
+                    for (sid,s) in peer.subs.iter() {
+                        if connects(&p.topic,p.type_hash,&s.topic,s.type_hash) {
+                            send_message(&mut p.stream,ParticipantToPublisher::NewPeerSub(*sid,SubscriberRef {
+                                address: SocketAddr::new(peer.ip,s.address.port()),
+                                topic: s.topic.clone(),
+                                type_hash: s.type_hash,
+                            })).await;
                         }
                     }
                 }
+            }
 
-                // handle rest of the messages
-                self.run_peer(stream_read,peer_id).await;
+            // handle rest of the messages
+            self.run_peer(reader,peer_id).await;
 
-                // notify relevant local publishers of lost subscribers
-                {
-                    let state_peers = self.peers.lock().await;
-                    let mut state_pubs = self.pubs.lock().await;
-                    let peer = state_peers.get(&peer_id).unwrap();
-                    for (_,p) in state_pubs.iter_mut() {
-                        for (sid,s) in peer.subs.iter() {
-                            if p.topic == s.topic {
-                                send_message(&mut p.stream,ParticipantToPublisher::DropPeerSub(*sid)).await;
-                            }
+            // notify relevant local publishers of lost subscribers
+            {
+                let state_peers = self.peers.lock().await;
+                let mut state_pubs = self.pubs.lock().await;
+                let peer = state_peers.get(&peer_id).unwrap();
+                for (_,p) in state_pubs.iter_mut() {
+                    for (sid,s) in peer.subs.iter() {
+                        if matches(&p.topic,p.type_hash,&s.topic,s.type_hash) {
+                            send_message(&mut p.stream,ParticipantToPublisher::DropPeerSub(*sid)).await;
                         }
                     }
                 }
+            }
 
-                // remove peer reference
-                {
-                    let mut state_peers = self.peers.lock().await;
-                    state_peers.remove(&peer_id);
-                }
+            // remove peer reference
+            {
+                let mut state_peers = self.peers.lock().await;
+                state_peers.remove(&peer_id);
             }
         }
     }
@@ -521,165 +536,161 @@ impl Participant {
         let address = stream.peer_addr().unwrap();
 
         // split stream read and write ends
-        let (mut stream_read,stream_write) = io::split(stream);
+        let (stream_read,stream_write) = io::split(stream);
+        let mut reader = FrameReader::new(stream_read);
 
         // get announcement from active side
-        let mut recv_buffer = vec![0u8; 65536];
-        if let Ok(_) = stream_read.read(&mut recv_buffer).await {
-            if let Some((_,message)) = ParticipantAnnounce::decode(&recv_buffer) {
+        if let Ok(Some(message)) = reader.recv::<ParticipantAnnounce>().await {
 
-                // store new peer ID
-                let peer_id = message.id;
+            // store new peer ID
+            let peer_id = message.id;
 
-                // create peer
-                let mut peer = PeerRef {
-                    ip: address.ip(),
-                    stream: stream_write,
-                    pubs: message.pubs,
-                    subs: HashMap::new(),
-                };
-                for (id,s) in message.subs.iter() {
-                    peer.subs.insert(*id,SubscriberRef {
-                        address: SocketAddr::new(address.ip(),s.address.port()),
-                        topic: s.topic.clone(),
-                    });
-                }
+            // create peer
+            let mut peer = PeerRef {
+                ip: address.ip(),
+                stream: stream_write,
+                pubs: message.pubs,
+                subs: HashMap::new(),
+            };
+            for (id,s) in message.subs.iter() {
+                peer.subs.insert(*id,SubscriberRef {
+                    address: SocketAddr::new(address.ip(),s.address.port()),
+                    topic: s.topic.clone(),
+                    type_hash: s.type_hash,
+                });
+            }
 
-                // send response to active side
-                let message = {
-                    let pubs = {
-                        let state_pubs = self.pubs.lock().await;
-                        let mut pubs = HashMap::<PublisherId,PublisherRef>::new();
-                        for (id,p) in state_pubs.iter() {
-                            pubs.insert(*id,PublisherRef {
-                                topic: p.topic.clone(),
-                            });
-                        }
-                        pubs
-                    };
-                    let subs = {
-                        let state_subs = self.subs.lock().await;
-                        let mut subs = HashMap::<SubscriberId,SubscriberRef>::new();
-                        for (id,s) in state_subs.iter() {
-                            subs.insert(*id,SubscriberRef {
-                                address: s.address,
-                                topic: s.topic.clone(),
-                            });
-                        }
-                        subs
-                    };
-                    ParticipantAnnounce {
-                        id: self.id,
-                        pubs: pubs,
-                        subs: subs,
+            // send response to active side
+            let message = {
+                let pubs = {
+                    let state_pubs = self.pubs.lock().await;
+                    let mut pubs = HashMap::<PublisherId,PublisherRef>::new();
+                    for (id,p) in state_pubs.iter() {
+                        pubs.insert(*id,PublisherRef {
+                            topic: p.topic.clone(),
+                            type_hash: p.type_hash,
+                        });
                     }
+                    pubs
                 };
-                send_message(&mut peer.stream,message).await;
-
-                // and make peer reference live
-                {
-                    let mut state_peers = self.peers.lock().await;
-                    state_peers.insert(peer_id,peer);
+                let subs = {
+                    let state_subs = self.subs.lock().await;
+                    let mut subs = HashMap::<SubscriberId,SubscriberRef>::new();
+                    for (id,s) in state_subs.iter() {
+                        subs.insert(*id,SubscriberRef {
+                            address: s.address,
+                            topic: s.topic.clone(),
+                            type_hash: s.type_hash,
+                        });
+                    }
+                    subs
+                };
+                ParticipantAnnounce {
+                    id: self.id,
+                    pubs: pubs,
+                    subs: subs,
                 }
+            };
+            send_message(&mut peer.stream,message).await;
 
-                // notify relevant local publishers of the new subscribers
-                {
-                    let state_peers = self.peers.lock().await;
-                    let mut state_pubs = self.pubs.lock().await;
-                    let peer = state_peers.get(&peer_id).unwrap();
-                    for (_,p) in state_pubs.iter_mut() {
-                        for (sid,s) in peer.subs.iter() {
-                            if p.topic == s.topic {
-                                send_message(&mut p.stream,ParticipantToPublisher::NewPeerSub(*sid,SubscriberRef {
-                                    address: SocketAddr::new(peer.ip,s.address.port()),
-                                    topic: s.topic.clone(),
-                                })).await;
-                            }
+            // and make peer reference live
+            {
+                let mut state_peers = self.peers.lock().await;
+                state_peers.insert(peer_id,peer);
+            }
+
+            // notify relevant local publishers of the new subscribers
+            {
+                let state_peers = self.peers.lock().await;
+                let mut state_pubs = self.pubs.lock().await;
+                let peer = state_peers.get(&peer_id).unwrap();
+                for (_,p) in state_pubs.iter_mut() {
+                    for (sid,s) in peer.subs.iter() {
+                        if connects(&p.topic,p.type_hash,&s.topic,s.type_hash) {
+                            send_message(&mut p.stream,ParticipantToPublisher::NewPeerSub(*sid,SubscriberRef {
+                                address: SocketAddr::new(peer.ip,s.address.port()),
+                                topic: s.topic.clone(),
+                                type_hash: s.type_hash,
+                            })).await;
                         }
                     }
                 }
+            }
 
-                // handle rest of the messages
-                self.run_peer(stream_read,peer_id).await;
+            // handle rest of the messages
+            self.run_peer(reader,peer_id).await;
 
-                // notify relevant local publishers of lost subscribers
-                {
-                    let state_peers = self.peers.lock().await;
-                    let mut state_pubs = self.pubs.lock().await;
-                    let peer = state_peers.get(&peer_id).unwrap();
-                    for (_,p) in state_pubs.iter_mut() {
-                        for (sid,s) in peer.subs.iter() {
-                            if p.topic == s.topic {
-                                send_message(&mut p.stream,ParticipantToPublisher::DropPeerSub(*sid)).await;
-                            }
+            // notify relevant local publishers of lost subscribers
+            {
+                let state_peers = self.peers.lock().await;
+                let mut state_pubs = self.pubs.lock().await;
+                let peer = state_peers.get(&peer_id).unwrap();
+                for (_,p) in state_pubs.iter_mut() {
+                    for (sid,s) in peer.subs.iter() {
+                        if matches(&p.topic,p.type_hash,&s.topic,s.type_hash) {
+                            send_message(&mut p.stream,ParticipantToPublisher::DropPeerSub(*sid)).await;
                         }
                     }
                 }
+            }
 
-                // remove peer reference
-                {
-                    let mut state_peers = self.peers.lock().await;
-                    state_peers.remove(&peer_id);
-                }
+            // remove peer reference
+            {
+                let mut state_peers = self.peers.lock().await;
+                state_peers.remove(&peer_id);
             }
         }
     }
 
-    async fn run_peer(self: &Arc<Participant>,mut stream: io::ReadHalf<net::TcpStream>,peer_id: ParticipantId) {
+    async fn run_peer(self: &Arc<Participant>,mut reader: FrameReader<io::ReadHalf<net::TcpStream>>,peer_id: ParticipantId) {
 
-        let mut buffer = vec![0u8; 65536];
+        while let Ok(Some(message)) = reader.recv::<ParticipantToParticipant>().await {
+            match message {
 
-        while let Ok(length) = stream.read(&mut buffer).await {
-            if length == 0 {
-                break;
-            }
-            if let Some((_,message)) = ParticipantToParticipant::decode(&buffer) {
-                match message {
-
-                    // peer has new publisher
-                    ParticipantToParticipant::NewPub(id,publisher) => {
-                        let mut state_peers = self.peers.lock().await;
-                        let peer = state_peers.get_mut(&peer_id).expect(&format!("cannot find participant reference {:016X}",peer_id));
-                        peer.pubs.insert(id,publisher);
-                    },
-
-                    // peer lost publisher
-                    ParticipantToParticipant::DropPub(id) => {
-                        let mut state_peers = self.peers.lock().await;
-                        let peer = state_peers.get_mut(&peer_id).expect(&format!("cannot find participant reference {:016X}",peer_id));
-                        peer.pubs.remove(&id);
-                    },
-
-                    // peer has new subscriber
-                    ParticipantToParticipant::NewSub(id,subscriber) => {
-                        let mut state_peers = self.peers.lock().await;
-                        let peer = state_peers.get_mut(&peer_id).expect(&format!("cannot find participant reference {:016X}",peer_id));
-                        let mut state_pubs = self.pubs.lock().await;
-                        for (_,p) in state_pubs.iter_mut() {
-                            if p.topic == subscriber.topic {
-                                send_message(&mut p.stream,ParticipantToPublisher::NewPeerSub(id,SubscriberRef {
-                                    address: SocketAddr::new(peer.ip,subscriber.address.port()),
-                                    topic: subscriber.topic.clone(),
-                                })).await;
-                            }
+                // peer has new publisher
+                ParticipantToParticipant::NewPub(id,publisher) => {
+                    let mut state_peers = self.peers.lock().await;
+                    let peer = state_peers.get_mut(&peer_id).expect(&format!("cannot find participant reference {:016X}",peer_id));
+                    peer.pubs.insert(id,publisher);
+                },
+
+                // peer lost publisher
+                ParticipantToParticipant::DropPub(id) => {
+                    let mut state_peers = self.peers.lock().await;
+                    let peer = state_peers.get_mut(&peer_id).expect(&format!("cannot find participant reference {:016X}",peer_id));
+                    peer.pubs.remove(&id);
+                },
+
+                // peer has new subscriber
+                ParticipantToParticipant::NewSub(id,subscriber) => {
+                    let mut state_peers = self.peers.lock().await;
+                    let peer = state_peers.get_mut(&peer_id).expect(&format!("cannot find participant reference {:016X}",peer_id));
+                    let mut state_pubs = self.pubs.lock().await;
+                    for (_,p) in state_pubs.iter_mut() {
+                        if connects(&p.topic,p.type_hash,&subscriber.topic,subscriber.type_hash) {
+                            send_message(&mut p.stream,ParticipantToPublisher::NewPeerSub(id,SubscriberRef {
+                                address: SocketAddr::new(peer.ip,subscriber.address.port()),
+                                topic: subscriber.topic.clone(),
+                                type_hash: subscriber.type_hash,
+                            })).await;
                         }
-                        peer.subs.insert(id,subscriber);
-                    },
-
-                    // peer lost subscriber
-                    ParticipantToParticipant::DropSub(id) => {
-                        let mut state_peers = self.peers.lock().await;
-                        let peer = state_peers.get_mut(&peer_id).expect(&format!("cannot find participant reference {:016X}",peer_id));
-                        let topic = peer.subs.get(&id).unwrap().topic.clone();
-                        let mut state_pubs = self.pubs.lock().await;
-                        for (_,p) in state_pubs.iter_mut() {
-                            if p.topic == topic {
-                                send_message(&mut p.stream,ParticipantToPublisher::DropPeerSub(id)).await;
-                            }
+                    }
+                    peer.subs.insert(id,subscriber);
+                },
+
+                // peer lost subscriber
+                ParticipantToParticipant::DropSub(id) => {
+                    let mut state_peers = self.peers.lock().await;
+                    let peer = state_peers.get_mut(&peer_id).expect(&format!("cannot find participant reference {:016X}",peer_id));
+                    let s = peer.subs.get(&id).unwrap();
+                    let mut state_pubs = self.pubs.lock().await;
+                    for (_,p) in state_pubs.iter_mut() {
+                        if matches(&p.topic,p.type_hash,&s.topic,s.type_hash) {
+                            send_message(&mut p.stream,ParticipantToPublisher::DropPeerSub(id)).await;
                         }
-                        peer.subs.remove(&id);
-                    },
-                }
+                    }
+                    peer.subs.remove(&id);
+                },
             }
         }
     }
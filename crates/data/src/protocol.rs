@@ -1,11 +1,13 @@
 // Echidna - Data
 
 use {
-    tokio::{
-        io,
-        io::AsyncWriteExt,
+    crate::*,
+    codec::{
+        Codec,
+        DecodeLimits,
+        Timestamp,
+        Varint,
     },
-    codec::Codec,
     std::{
         collections::HashMap,
         net::SocketAddr,
@@ -27,55 +29,104 @@ pub struct SharedMemory {
     pub data: Vec<u8>,
 }
 
-#[derive(Codec)]
-pub struct Chunk {
-    pub ts: u64,
+#[derive(Codec,Debug,PartialEq)]
+pub struct Chunk<'a> {
+    pub ts: Timestamp,
     pub id: MessageId,
     pub total_bytes: u64,
     pub chunk_size: u32,
     pub total: u32,
     pub index: u32,
-    pub data: Vec<u8>,
+    pub data: &'a [u8],
+}
+
+impl<'a> Chunk<'a> {
+
+    /// Check that the chunk header is consistent and within `limits`.
+    ///
+    /// The header describes the buffer the subscriber allocates for the
+    /// whole message, so it is checked before anything is allocated.
+    pub fn is_valid(&self,limits: &DecodeLimits) -> bool {
+        if (self.total_bytes == 0) || (self.total_bytes > limits.max_alloc as u64) {
+            return false;
+        }
+        if (self.total == 0) || (self.total as usize > limits.max_elements) || (self.chunk_size == 0) {
+            return false;
+        }
+        if (self.total as u64) != self.total_bytes.div_ceil(self.chunk_size as u64) {
+            return false;
+        }
+        if self.index >= self.total {
+            return false;
+        }
+        let start = self.index as u64 * self.chunk_size as u64;
+        (self.data.len() <= self.chunk_size as usize) && (start + self.data.len() as u64 <= self.total_bytes)
+    }
 }
 
-#[derive(Codec)]
-pub enum PublisherToSubscriber {
+#[derive(Codec,Debug,PartialEq)]
+pub enum PublisherToSubscriber<'a> {
     Heartbeat(MessageId),
-    Chunk(Chunk),
+    Chunk(Chunk<'a>),
 }
 
-#[derive(Codec)]
+#[derive(Codec,Debug,PartialEq)]
 pub enum SubscriberToPublisher {
-    Ack(MessageId,u32),
-    NAck(MessageId,u32,u32),
+    Ack(MessageId,Varint<u32>),
+    NAck(MessageId,Varint<u32>,Varint<u32>),
 }
 
-#[derive(Codec)]
+// Beacons and announcements go to every participant on the network, which
+// may run an older or newer version. Add fields at the end, with
+// `#[codec(since = N)]`.
+#[derive(Codec,Debug,PartialEq)]
+#[codec(versioned)]
 pub struct Beacon {
     pub id: ParticipantId,
     pub domain: String,
     pub port: u16,
 }
 
-#[derive(Clone,Codec)]
+/// Type hash of topics whose messages are raw bytes instead of encoded values.
+pub const RAW_TYPE_HASH: u64 = 0;
+
+/// Type hash of publishers and subscribers of participants that don't send
+/// one, they connect regardless of the type.
+pub const UNKNOWN_TYPE_HASH: u64 = u64::MAX;
+
+fn unknown_type_hash() -> u64 {
+    UNKNOWN_TYPE_HASH
+}
+
+// The type hash is `Encode::type_hash` of the messages of the topic, a
+// publisher and a subscriber only connect if they agree on it. Both are part
+// of announcements, so they are versioned too.
+#[derive(Clone,Codec,Debug,PartialEq)]
+#[codec(versioned)]
 pub struct PublisherRef {
     pub topic: String,
+    #[codec(since = 1,default = "unknown_type_hash")]
+    pub type_hash: u64,
 }
 
-#[derive(Clone,Codec)]
+#[derive(Clone,Codec,Debug,PartialEq)]
+#[codec(versioned)]
 pub struct SubscriberRef {
     pub address: SocketAddr,
     pub topic: String,
+    #[codec(since = 1,default = "unknown_type_hash")]
+    pub type_hash: u64,
 }
 
-#[derive(Codec)]
+#[derive(Codec,Debug,PartialEq)]
+#[codec(versioned)]
 pub struct ParticipantAnnounce {
     pub id: ParticipantId,
     pub pubs: HashMap<PublisherId,PublisherRef>,
     pub subs: HashMap<SubscriberId,SubscriberRef>,
 }
 
-#[derive(Codec)]
+#[derive(Codec,Debug,PartialEq)]
 pub enum ParticipantToParticipant {
     NewPub(PublisherId,PublisherRef),
     DropPub(PublisherId),
@@ -83,18 +134,19 @@ pub enum ParticipantToParticipant {
     DropSub(SubscriberId),
 }
 
-#[derive(Codec)]
+#[derive(Codec,Debug,PartialEq)]
 pub enum ToParticipant {
     InitPub(PublisherId,String,PublisherRef),
     InitSub(SubscriberId,String,SubscriberRef),
 }
 
-#[derive(Codec)]
+#[derive(Codec,Debug,PartialEq)]
+#[codec(tag_type = "u8")]
 pub enum PubInitFailed {
     DomainMismatch,
 }
 
-#[derive(Codec)]
+#[derive(Codec,Debug,PartialEq)]
 pub enum ParticipantToPublisher {
     Init(HashMap<SubscriberId,SubscriberRef>,HashMap<SubscriberId,SubscriberRef>),
     InitFailed(PubInitFailed),
@@ -104,19 +156,79 @@ pub enum ParticipantToPublisher {
     DropPeerSub(SubscriberId),
 }
 
-#[derive(Codec)]
+#[derive(Codec,Debug,PartialEq)]
+#[codec(tag_type = "u8")]
 pub enum SubInitFailed {
     DomainMismatch,
 }
 
-#[derive(Codec)]
+#[derive(Codec,Debug,PartialEq)]
 pub enum ParticipantToSubscriber {
     Init,
     InitFailed(SubInitFailed),
 }
 
-pub async fn send_message<S: io::AsyncWrite + Unpin,M: Codec>(stream: &mut S,message: M) {
-    let mut send_buffer = Vec::new();
-    message.encode(&mut send_buffer);
-    stream.write_all(&send_buffer).await.expect("cannot send message");
+impl ControlMessage for ToParticipant {
+    const MESSAGE_TYPE: u32 = 1;
+}
+
+impl ControlMessage for ParticipantAnnounce {
+    const MESSAGE_TYPE: u32 = 2;
+}
+
+impl ControlMessage for ParticipantToParticipant {
+    const MESSAGE_TYPE: u32 = 3;
+}
+
+impl ControlMessage for ParticipantToPublisher {
+    const MESSAGE_TYPE: u32 = 4;
+}
+
+impl ControlMessage for ParticipantToSubscriber {
+    const MESSAGE_TYPE: u32 = 5;
+}
+
+#[cfg(test)]
+mod tests {
+
+    use {
+        crate::*,
+        codec::{
+            Codec,
+            DecodeLimits,
+            Encode,
+            Timestamp,
+        },
+    };
+
+    // `PublisherRef` before it had a type hash.
+    #[derive(Codec)]
+    #[codec(versioned)]
+    struct OldPublisherRef {
+        topic: String,
+    }
+
+    #[test]
+    fn test_chunk_valid() {
+        let data = [0u8; 4];
+        let chunk = Chunk { ts: Timestamp::default(), id: 1, total_bytes: 10, chunk_size: 4, total: 3, index: 2, data: &data[..2], };
+        assert!(chunk.is_valid(&DecodeLimits::default()));
+        assert!(!Chunk { total: 2, ..chunk }.is_valid(&DecodeLimits::default()));
+
+        // a hostile total doesn't overflow without an allocation limit
+        let chunk = Chunk { total_bytes: u64::MAX, chunk_size: 0x8000_0000, total: u32::MAX, index: 0, data: &data, ..chunk };
+        assert!(!chunk.is_valid(&DecodeLimits::unlimited()));
+    }
+
+    #[test]
+    fn test_old_refs() {
+        let mut buffer = Vec::<u8>::new();
+        OldPublisherRef { topic: "camera".to_string(), }.encode(&mut buffer);
+        assert_eq!(PublisherRef::decode(&buffer),Ok((buffer.len(),PublisherRef { topic: "camera".to_string(), type_hash: UNKNOWN_TYPE_HASH, })));
+
+        // and an old participant reads new ones
+        let mut buffer = Vec::<u8>::new();
+        PublisherRef { topic: "camera".to_string(), type_hash: 0x0123_4567_89AB_CDEF, }.encode(&mut buffer);
+        assert_eq!(OldPublisherRef::decode(&buffer).map(|(len,old)| (len,old.topic)),Ok((buffer.len(),"camera".to_string())));
+    }
 }
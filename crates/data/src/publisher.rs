@@ -2,11 +2,16 @@
 
 use {
     crate::*,
-    codec::Codec,
+    codec::{
+        BufferPool,
+        Codec,
+        Encode,
+        Timestamp,
+        Varint,
+    },
     tokio::{
         net,
         task,
-        io::AsyncReadExt,
         sync::Mutex,
         time,
     },
@@ -24,6 +29,9 @@ use {
     },
 };
 
+/// Chunk buffers kept for the next message, more are freed after sending.
+const MAX_POOLED_CHUNKS: usize = 1024;
+
 pub struct SubscriberControl {
     pub address: SocketAddr,
     pub socket: net::UdpSocket,
@@ -33,6 +41,7 @@ pub struct Publisher {
     pub id: PublisherId,
     pub domain: String,
     pub topic: String,
+    pub type_hash: u64,
     pub chunk_size: usize,
     pub chunks_per_heartbeat: usize,
     pub transmit_interval_usec: u64,
@@ -44,6 +53,7 @@ pub struct Publisher {
     pub tasks: Mutex<HashMap<SubscriberId,task::JoinHandle<()>>>,
     pub finished: Arc<Mutex<HashMap<SubscriberId,bool>>>,
     pub shmem: Shmem,
+    pub chunk_pool: BufferPool,
 }
 
 impl Publisher {
@@ -51,6 +61,7 @@ impl Publisher {
         pubsub_port: u16,
         domain: &str,
         topic: &str,
+        type_hash: u64,
         chunk_size: usize,
         chunks_per_heartbeat: usize,
         transmit_interval_usec: u64,
@@ -67,6 +78,7 @@ impl Publisher {
             id: id,
             domain: domain.to_string(),
             topic: topic.to_string(),
+            type_hash: type_hash,
             chunk_size: chunk_size,
             chunks_per_heartbeat: chunks_per_heartbeat,
             transmit_interval_usec: transmit_interval_usec,
@@ -78,6 +90,7 @@ impl Publisher {
             tasks: Mutex::new(HashMap::new()),
             finished: Arc::new(Mutex::new(HashMap::new())),
             shmem: ShmemConf::new().size(16384).os_id(topic).create().expect("cannot create shared memory"),
+            chunk_pool: BufferPool::new(MAX_POOLED_CHUNKS),
         });
 
         // spawn participant receiver
@@ -94,9 +107,10 @@ impl Publisher {
     pub async fn new_default(
         pubsub_port: u16,
         domain: &str,
-        topic: &str
+        topic: &str,
+        type_hash: u64,
     ) -> Arc<Publisher> {
-        Publisher::new(pubsub_port,domain,topic,51200,3,300,0,100,false).await
+        Publisher::new(pubsub_port,domain,topic,type_hash,51200,3,300,0,100,false).await
     }
 
     pub async fn run_participant_connection(self: &Arc<Publisher>,pubsub_port: u16) {
@@ -109,66 +123,62 @@ impl Publisher {
                 // announce publisher to participant
                 send_message(&mut stream,ToParticipant::InitPub(self.id,self.domain.clone(),PublisherRef {
                     topic: self.topic.clone(),
+                    type_hash: self.type_hash,
                 })).await;
 
                 // receive participant messages
-                let mut recv_buffer = vec![0u8; 65536];
-                while let Ok(length) = stream.read(&mut recv_buffer).await {
-                    if length == 0 {
-                        break;
-                    }
-                    if let Some((_,message)) = ParticipantToPublisher::decode(&recv_buffer) {
-                        match message {
-                            ParticipantToPublisher::Init(local_subs,peer_subs) => {
-                                let mut state_subs = self.local_subs.lock().await;
-                                for(id,s) in local_subs.iter() {
-                                    println!("local subscriber {:016X} found at {}",id,s.address);
-                                    state_subs.insert(*id,Arc::new(SubscriberControl {
-                                        address: s.address,
-                                        socket: net::UdpSocket::bind("0.0.0.0:0").await.expect("cannot create publisher socket"),
-                                    }));
-                                }
-                                let mut state_subs = self.peer_subs.lock().await;
-                                for(id,s) in peer_subs.iter() {
-                                    println!("peer subscriber {:016X} found at {}",id,s.address);
-                                    state_subs.insert(*id,Arc::new(SubscriberControl {
-                                        address: s.address,
-                                        socket: net::UdpSocket::bind("0.0.0.0:0").await.expect("cannot create publisher socket"),
-                                    }));
-                                }
-                            },
-                            ParticipantToPublisher::InitFailed(reason) => {
-                                match reason {
-                                    PubInitFailed::DomainMismatch => { println!("Publisher initialization failed: domain mismatch."); },
-                                }
-                            },
-                            ParticipantToPublisher::NewLocalSub(id,subscriber) => {
-                                println!("local subscriber {:016X} found at {}",id,subscriber.address);
-                                let mut state_subs = self.local_subs.lock().await;
-                                state_subs.insert(id,Arc::new(SubscriberControl {
-                                    address: subscriber.address,
+                let mut reader = FrameReader::new(stream);
+                while let Ok(Some(message)) = reader.recv::<ParticipantToPublisher>().await {
+                    match message {
+                        ParticipantToPublisher::Init(local_subs,peer_subs) => {
+                            let mut state_subs = self.local_subs.lock().await;
+                            for(id,s) in local_subs.iter() {
+                                println!("local subscriber {:016X} found at {}",id,s.address);
+                                state_subs.insert(*id,Arc::new(SubscriberControl {
+                                    address: s.address,
                                     socket: net::UdpSocket::bind("0.0.0.0:0").await.expect("cannot create publisher socket"),
                                 }));
-                            },
-                            ParticipantToPublisher::NewPeerSub(id,subscriber) => {
-                                println!("peer subscriber {:016X} found at {}",id,subscriber.address);
-                                let mut state_subs = self.peer_subs.lock().await;
-                                state_subs.insert(id,Arc::new(SubscriberControl {
-                                    address: subscriber.address,
+                            }
+                            let mut state_subs = self.peer_subs.lock().await;
+                            for(id,s) in peer_subs.iter() {
+                                println!("peer subscriber {:016X} found at {}",id,s.address);
+                                state_subs.insert(*id,Arc::new(SubscriberControl {
+                                    address: s.address,
                                     socket: net::UdpSocket::bind("0.0.0.0:0").await.expect("cannot create publisher socket"),
                                 }));
-                            },
-                            ParticipantToPublisher::DropLocalSub(id) => {
-                                let mut state_subs = self.local_subs.lock().await;
-                                state_subs.remove(&id);
-                                println!("subscriber {:016X} lost",id);
-                            },
-                            ParticipantToPublisher::DropPeerSub(id) => {
-                                let mut state_subs = self.peer_subs.lock().await;
-                                state_subs.remove(&id);
-                                println!("subscriber {:016X} lost",id);
-                            },
-                        }
+                            }
+                        },
+                        ParticipantToPublisher::InitFailed(reason) => {
+                            match reason {
+                                PubInitFailed::DomainMismatch => { println!("Publisher initialization failed: domain mismatch."); },
+                            }
+                        },
+                        ParticipantToPublisher::NewLocalSub(id,subscriber) => {
+                            println!("local subscriber {:016X} found at {}",id,subscriber.address);
+                            let mut state_subs = self.local_subs.lock().await;
+                            state_subs.insert(id,Arc::new(SubscriberControl {
+                                address: subscriber.address,
+                                socket: net::UdpSocket::bind("0.0.0.0:0").await.expect("cannot create publisher socket"),
+                            }));
+                        },
+                        ParticipantToPublisher::NewPeerSub(id,subscriber) => {
+                            println!("peer subscriber {:016X} found at {}",id,subscriber.address);
+                            let mut state_subs = self.peer_subs.lock().await;
+                            state_subs.insert(id,Arc::new(SubscriberControl {
+                                address: subscriber.address,
+                                socket: net::UdpSocket::bind("0.0.0.0:0").await.expect("cannot create publisher socket"),
+                            }));
+                        },
+                        ParticipantToPublisher::DropLocalSub(id) => {
+                            let mut state_subs = self.local_subs.lock().await;
+                            state_subs.remove(&id);
+                            println!("subscriber {:016X} lost",id);
+                        },
+                        ParticipantToPublisher::DropPeerSub(id) => {
+                            let mut state_subs = self.peer_subs.lock().await;
+                            state_subs.remove(&id);
+                            println!("subscriber {:016X} lost",id);
+                        },
                     }
                 }
                 println!("participant lost...");
@@ -238,8 +248,9 @@ impl Publisher {
             }
             println!("sending message of {} bytes in {} chunks",total_bytes,total);
             
-            // prepare chunks
+            // prepare chunks, all stamped with the time the message was set
             let id = rand::random::<u64>();
+            let ts = Timestamp::now();
             let mut chunks = Vec::new();
 
             // build chunks
@@ -257,17 +268,17 @@ impl Publisher {
                     }
                 };
                 let chunk = Chunk {
-                    ts: 0,
+                    ts: ts,
                     id: id,
                     total_bytes: total_bytes as u64,
                     chunk_size: self.chunk_size as u32,
                     total: total as u32,
                     index: index,
-                    data: Vec::<u8>::from(&message[offset..offset + size]),
+                    data: &message[offset..offset + size],
                 };
 
-                // encode
-                let mut buffer = Vec::<u8>::new();
+                // encode into a recycled buffer
+                let mut buffer = self.chunk_pool.get();
                 PublisherToSubscriber::Chunk(chunk).encode(&mut buffer);
 
                 // store
@@ -315,6 +326,11 @@ impl Publisher {
                     let mut done = false;
                     let mut interval = time::interval(time::Duration::from_micros(transmit_interval_usec));
 
+                    // the heartbeat is the same every time
+                    let mut heartbeat = [0u8; 16];
+                    let heartbeat_length = PublisherToSubscriber::Heartbeat(id).encode_into(&mut heartbeat).expect("heartbeat does not fit");
+                    let mut buffer = vec![0u8; 65536];
+
                     while !done {
 
                         let mut indices = Vec::<u32>::new();
@@ -366,24 +382,21 @@ impl Publisher {
 
                         // send heartbeat
                         //println!("send heartbeat");
-                        let mut send_buffer = Vec::<u8>::new();
-                        PublisherToSubscriber::Heartbeat(id).encode(&mut send_buffer);
-                        control.socket.send_to(&send_buffer,control.address).await.expect("error sending heartbeat");
+                        control.socket.send_to(&heartbeat[..heartbeat_length],control.address).await.expect("error sending heartbeat");
 
                         // flush incoming acks and nacks
                         // TODO: it's currently not exactly flushing, but rather processing at most one message
-                        let mut buffer = vec![0u8; 65536];
 
                         if let Err(_) = time::timeout(time::Duration::from_micros(transmit_interval_usec),control.socket.recv_from(&mut buffer)).await {
                             dead_counter += 1;
                         }
                         else {
             
-                            if let Some((_,stp)) = SubscriberToPublisher::decode(&buffer) {
+                            if let Ok((_,stp)) = SubscriberToPublisher::decode(&buffer) {
             
                                 match stp {
             
-                                    SubscriberToPublisher::Ack(message_id,index) => {
+                                    SubscriberToPublisher::Ack(message_id,Varint(index)) => {
             
                                         // subscriber has everything until index
             
@@ -398,7 +411,7 @@ impl Publisher {
             
                                     },
             
-                                    SubscriberToPublisher::NAck(message_id,first,last) => {
+                                    SubscriberToPublisher::NAck(message_id,Varint(first),Varint(last)) => {
             
                                         // subscriber is missing first..last
             
@@ -5,11 +5,16 @@ use {
     tokio::{
         net,
         task,
-        io::AsyncReadExt,
         sync::Mutex,
         time,
     },
-    codec::Codec,
+    codec::{
+        BorrowDecode,
+        Encode,
+        DecodeContext,
+        DecodeLimits,
+        Varint,
+    },
     std::{
         sync::Arc,
         net::SocketAddr,
@@ -31,14 +36,20 @@ pub struct Subscriber {
     pub id: PublisherId,
     pub domain: String,
     pub topic: String,
+    pub type_hash: u64,
     pub socket: net::UdpSocket,
     pub address: SocketAddr,
+    pub limits: DecodeLimits,
     pub state: Mutex<SubscriberState>,
     pub shmem: Shmem,
 }
 
 impl Subscriber {
-    pub async fn new(pubsub_port: u16,domain: &str,topic: &str,on_data: impl Fn(&[u8]) + Send + 'static) -> Arc<Subscriber> {
+    pub async fn new(pubsub_port: u16,domain: &str,topic: &str,type_hash: u64,on_data: impl Fn(&[u8]) + Send + 'static) -> Arc<Subscriber> {
+        Subscriber::new_with_limits(pubsub_port,domain,topic,type_hash,DecodeLimits::default(),on_data).await
+    }
+
+    pub async fn new_with_limits(pubsub_port: u16,domain: &str,topic: &str,type_hash: u64,limits: DecodeLimits,on_data: impl Fn(&[u8]) + Send + 'static) -> Arc<Subscriber> {
 
         // new ID
         let id = rand::random::<u64>();
@@ -52,8 +63,10 @@ impl Subscriber {
             id: id,
             domain: domain.to_string(),
             topic: topic.to_string(),
+            type_hash: type_hash,
             socket: socket,
             address: address,
+            limits: limits,
             state: Mutex::new(SubscriberState {
                 id: 0,
                 buffer: Vec::new(),
@@ -90,23 +103,19 @@ impl Subscriber {
                 send_message(&mut stream,ToParticipant::InitSub(self.id,self.domain.clone(),SubscriberRef {
                     address: self.address,
                     topic: self.topic.clone(),
+                    type_hash: self.type_hash,
                 })).await;
 
                 // receive participant messages
-                let mut recv_buffer = vec![0u8; 65536];
-                while let Ok(length) = stream.read(&mut recv_buffer).await {
-                    if length == 0 {
-                        break;
-                    }
-                    if let Some((_,message)) = ParticipantToSubscriber::decode(&recv_buffer) {
-                        match message {
-                            ParticipantToSubscriber::Init => { },
-                            ParticipantToSubscriber::InitFailed(reason) => {
-                                match reason {
-                                    SubInitFailed::DomainMismatch => { println!("Subscriber initialization failed: domain mismatch."); },
-                                }
-                            },
-                        }
+                let mut reader = FrameReader::new(stream);
+                while let Ok(Some(message)) = reader.recv::<ParticipantToSubscriber>().await {
+                    match message {
+                        ParticipantToSubscriber::Init => { },
+                        ParticipantToSubscriber::InitFailed(reason) => {
+                            match reason {
+                                SubInitFailed::DomainMismatch => { println!("Subscriber initialization failed: domain mismatch."); },
+                            }
+                        },
                     }
                 }
 
@@ -129,6 +138,9 @@ impl Subscriber {
 
         let mut buffer = vec![0u8; 65536];
 
+        // Ack and NAck replies are at most 22 bytes
+        let mut send_buffer = [0u8; 32];
+
         let mut first_missing = 0u32;
         let mut last_missing: Option<u32> = None;
 
@@ -142,9 +154,10 @@ impl Subscriber {
         loop {
 
             // receive heartbeat or chunk
-            let (_,address) = self.socket.recv_from(&mut buffer).await.expect("error receiving");
+            let (length,address) = self.socket.recv_from(&mut buffer).await.expect("error receiving");
 
-            if let Some((_,pts)) = PublisherToSubscriber::decode(&buffer) {
+            let mut ctx = DecodeContext::new(self.limits);
+            if let Ok((_,pts)) = PublisherToSubscriber::borrow_decode_with(&buffer[..length],&mut ctx) {
 
                 match pts {
 
@@ -153,34 +166,38 @@ impl Subscriber {
 
                         let state = self.state.lock().await;
 
-                        let mut send_buffer = Vec::<u8>::new();
-
                         // only respond if this is for the current message
                         if id == state.id {
                
                             //println!("receive heartbeat");
 
-                            if let Some(last) = last_missing {
+                            let reply = if let Some(last) = last_missing {
                                 if last > first_missing {
                                     //println!("send nack {}-{}",first_missing,last);
-                                    SubscriberToPublisher::NAck(id,first_missing,last).encode(&mut send_buffer);
+                                    SubscriberToPublisher::NAck(id,Varint(first_missing),Varint(last))
                                 }
                                 else {
                                     //println!("send ack {}",first_missing);
-                                    SubscriberToPublisher::Ack(id,first_missing).encode(&mut send_buffer);
+                                    SubscriberToPublisher::Ack(id,Varint(first_missing))
                                 }
                             }
                             else {
                                 //println!("send ack {}",first_missing);
-                                SubscriberToPublisher::Ack(id,first_missing).encode(&mut send_buffer);
-                            }
-                            self.socket.send_to(&mut send_buffer,address).await.expect("error sending retransmit request");
+                                SubscriberToPublisher::Ack(id,Varint(first_missing))
+                            };
+                            let length = reply.encode_into(&mut send_buffer).expect("reply does not fit send buffer");
+                            self.socket.send_to(&send_buffer[..length],address).await.expect("error sending retransmit request");
                         }
                     },
 
                     // chunk
                     PublisherToSubscriber::Chunk(chunk) => {
 
+                        // ignore chunks with a broken or oversized header
+                        if !chunk.is_valid(&self.limits) {
+                            continue;
+                        }
+
                         let mut state = self.state.lock().await;
 
                         // if this is a new chunk, reset state
@@ -196,6 +213,11 @@ impl Subscriber {
                             chunks_ignored = 0;
                         }
 
+                        // ignore chunks that disagree with the header of the current message
+                        else if (chunk.total_bytes as usize != state.buffer.len()) || (chunk.total as usize != state.received.len()) {
+                            continue;
+                        }
+
                         chunks_total += 1;
                 
                         // if we don't already have this chunk
@@ -204,9 +226,9 @@ impl Subscriber {
                             //println!("receive {}",chunk.index);
 
                             // copy data into final message buffer
-                            let start = (chunk.index * chunk.chunk_size) as usize;
+                            let start = chunk.index as usize * chunk.chunk_size as usize;
                             let end = start + chunk.data.len();
-                            state.buffer[start..end].copy_from_slice(&chunk.data);
+                            state.buffer[start..end].copy_from_slice(chunk.data);
                 
                             // mark the chunk as received
                             state.received[chunk.index as usize] = true;